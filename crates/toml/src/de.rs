@@ -4,6 +4,16 @@
 //! into Rust structures. Note that some top-level functions here are also
 //! provided at the top of the crate.
 
+#[cfg(feature = "parse")]
+mod item;
+#[cfg(feature = "parse")]
+mod parser;
+#[cfg(feature = "parse")]
+mod unknown;
+
+#[cfg(feature = "parse")]
+pub use unknown::UnknownKey;
+
 /// Deserializes a string into a type.
 ///
 /// This function will attempt to interpret `s` as a TOML document and
@@ -11,6 +21,9 @@
 ///
 /// To deserializes TOML values, instead of documents, see [`ValueDeserializer`].
 ///
+/// Strings and keys that need no unescaping may be borrowed from `s`, as `&str` or
+/// `#[serde(borrow)] Cow<str>` fields.  An escaped string fails to deserialize into `&str`.
+///
 /// # Examples
 ///
 /// ```
@@ -38,37 +51,214 @@
 /// assert_eq!(config.owner.name, "Lisa");
 /// ```
 #[cfg(feature = "parse")]
-pub fn from_str<T>(s: &'_ str) -> Result<T, Error>
+pub fn from_str<'de, T>(s: &'de str) -> Result<T, Error>
 where
-    T: serde::de::DeserializeOwned,
+    T: serde::de::Deserialize<'de>,
 {
     T::deserialize(Deserializer::new(s))
 }
 
+/// Deserializes bytes into a type.
+///
+/// The bytes must be UTF-8, with an optional byte order mark.  UTF-16 and invalid UTF-8 are
+/// reported with the span of the offending bytes.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Config {
+///     title: String,
+/// }
+///
+/// let config: Config = toml::from_slice(b"\xEF\xBB\xBFtitle = 'TOML Example'").unwrap();
+/// assert_eq!(config.title, "TOML Example");
+///
+/// let err = toml::from_slice::<Config>(b"title = '\xFF'").unwrap_err();
+/// assert_eq!(err.span(), Some(9..10));
+/// ```
+#[cfg(feature = "parse")]
+pub fn from_slice<'de, T>(bytes: &'de [u8]) -> Result<T, Error>
+where
+    T: serde::de::Deserialize<'de>,
+{
+    from_str(decode(bytes)?)
+}
+
+/// Deserializes a type from an IO stream, see [`from_slice`].
+///
+/// The whole stream is read before deserializing.
+#[cfg(feature = "parse")]
+pub fn from_reader<R, T>(mut reader: R) -> Result<T, Error>
+where
+    R: std::io::Read,
+    T: serde::de::DeserializeOwned,
+{
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(<Error as serde::de::Error>::custom)?;
+    from_slice(&bytes)
+}
+
+/// Check the bytes are UTF-8, the byte order mark is left to the parser
+#[cfg(feature = "parse")]
+fn decode(bytes: &[u8]) -> Result<&str, Error> {
+    // UTF-16 starts with its byte order mark, or has a zero byte in the first ASCII character
+    let utf16 = if bytes.starts_with(b"\xFF\xFE") || bytes.starts_with(b"\xFE\xFF") {
+        Some(0..2)
+    } else {
+        bytes.iter().take(2).position(|b| *b == 0).map(|i| i..i + 1)
+    };
+    let (message, span) = match (utf16, std::str::from_utf8(bytes)) {
+        (Some(span), _) => ("UTF-16 is not supported, TOML must be UTF-8", span),
+        (None, Ok(s)) => return Ok(s),
+        (None, Err(e)) => {
+            let start = e.valid_up_to();
+            let len = e.error_len().unwrap_or(bytes.len() - start);
+            ("invalid UTF-8", start..start + len)
+        }
+    };
+    let mut err = Error::custom(message, Some(span));
+    err.set_original(Some(String::from_utf8_lossy(bytes).into_owned()));
+    Err(err)
+}
+
+/// Deserializes a string into a type, collecting every error instead of stopping at the first.
+///
+/// When a string, number, boolean, array or table does not fit the type the target asks for,
+/// like a string for a `u16`, its error is recorded and the target gets a default of that type
+/// instead (`0`, `false`, `""`, `[]`, ...), so deserialization goes on to the remaining values.
+/// Collection stops at an error that cannot be replaced, like a missing field or an unknown
+/// enum variant.  It also stops when the target rejects a table or array holding a default,
+/// like a failed validation through `#[serde(try_from)]`, but that error is not reported, as it
+/// may only come from the default.
+///
+/// The spans of the errors refer to `s`, and they render with source snippets of `s`.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Config {
+///     name: String,
+///     port: u16,
+///     debug: bool,
+/// }
+///
+/// let errors = toml::de::from_str_all::<Config>(r#"
+///     name = 1
+///     port = 'http'
+///     debug = true
+/// "#).unwrap_err();
+///
+/// assert_eq!(errors.len(), 2);
+/// assert_eq!(errors[0].keys(), ["name"]);
+/// assert_eq!(errors[1].keys(), ["port"]);
+/// ```
+#[cfg(feature = "parse")]
+pub fn from_str_all<T>(s: &'_ str) -> Result<T, Vec<Error>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    // An invalid document, or one beyond the limits of the parser, has a single error
+    let root = match parser::parse_document(s) {
+        Some(root) => root,
+        None => return from_str(s).map_err(|e| vec![e]),
+    };
+    let mut recovery = item::Recovery::default();
+    let result = T::deserialize(
+        item::ItemDeserializer::new(parser::Item::Table(root)).with_recovery(&mut recovery),
+    );
+    recovery.finish(result).map_err(|mut errors| {
+        for e in &mut errors {
+            e.set_original(Some(s.to_owned()));
+        }
+        errors
+    })
+}
+
 /// Errors that can occur when deserializing a type.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Error {
-    inner: crate::edit::de::Error,
+    message: String,
+    keys: Vec<String>,
+    span: Option<std::ops::Range<usize>>,
+    /// The document shown in the source snippet of the error
+    original: Option<String>,
+    /// About a key of a table, like a missing field, rather than about a value
+    key_error: bool,
 }
 
 impl Error {
-    fn new(inner: crate::edit::de::Error) -> Self {
-        Self { inner }
+    pub(crate) fn custom<T>(msg: T, span: Option<std::ops::Range<usize>>) -> Self
+    where
+        T: std::fmt::Display,
+    {
+        Self {
+            message: msg.to_string(),
+            keys: Vec::new(),
+            span,
+            original: None,
+            key_error: false,
+        }
     }
 
+    #[cfg(feature = "parse")]
+    fn from_edit(err: toml_edit::de::Error, original: Option<&str>) -> Self {
+        Self {
+            message: err.message().to_owned(),
+            keys: err.keys().to_vec(),
+            span: err.span(),
+            original: original.map(ToOwned::to_owned),
+            key_error: false,
+        }
+    }
+
+    fn key_error(msg: Message) -> Self {
+        let mut err = Error::custom(msg, None);
+        err.key_error = true;
+        err
+    }
+
+    /// Add key while unwinding
     pub(crate) fn add_key(&mut self, key: String) {
-        self.inner.add_key(key)
+        // Without the parser, errors only come from `Value`s and have no location to report
+        #[cfg(feature = "parse")]
+        self.keys.insert(0, key);
+        #[cfg(not(feature = "parse"))]
+        let _ = key;
+    }
+
+    #[cfg(feature = "parse")]
+    pub(crate) fn set_span(&mut self, span: Option<std::ops::Range<usize>>) {
+        self.span = span;
+    }
+
+    #[cfg(feature = "parse")]
+    pub(crate) fn set_original(&mut self, original: Option<String>) {
+        self.original = original;
     }
 
     /// What went wrong
     pub fn message(&self) -> &str {
-        self.inner.message()
+        &self.message
     }
 
     /// The start/end index into the original document where the error occurred
     #[cfg(feature = "parse")]
     pub fn span(&self) -> Option<std::ops::Range<usize>> {
-        self.inner.span()
+        self.span.clone()
+    }
+
+    /// The keys of the tables down to where the error occurred
+    #[cfg(feature = "parse")]
+    pub fn keys(&self) -> &[String] {
+        &self.keys
     }
 }
 
@@ -77,47 +267,242 @@ impl serde::de::Error for Error {
     where
         T: std::fmt::Display,
     {
-        Error::new(crate::edit::de::Error::custom(msg))
+        Error::custom(msg, None)
+    }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        Error::key_error(Message::unknown_field(field, expected))
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Error::key_error(Message::missing_field(field))
+    }
+
+    fn duplicate_field(field: &'static str) -> Self {
+        Error::key_error(Message::duplicate_field(field))
+    }
+}
+
+/// The messages of `serde`, for the errors that are built differently
+struct Message(String);
+
+impl serde::de::Error for Message {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: std::fmt::Display,
+    {
+        Message(msg.to_string())
     }
 }
 
+impl std::fmt::Debug for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for Message {}
+
+/// Displays the error like `toml_edit` does, with a source snippet when the document is known
+///
+/// # Example
+///
+/// TOML parse error at line 1, column 10
+///   |
+/// 1 | 00:32:00.a999999
+///   |          ^
+/// Unexpected `a`
+/// Expected `digit`
+/// While parsing a Time
+/// While parsing a Date-Time
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.inner.fmt(f)
+        let mut context = false;
+        if let (Some(original), Some(span)) = (&self.original, self.span.clone()) {
+            context = true;
+
+            let (line, column) = translate_position(original.as_bytes(), span.start);
+            let line_num = line + 1;
+            let col_num = column + 1;
+            let gutter = line_num.to_string().len();
+            let content = original.split('\n').nth(line).expect("valid line number");
+
+            writeln!(
+                f,
+                "TOML parse error at line {}, column {}",
+                line_num, col_num
+            )?;
+            //   |
+            for _ in 0..=gutter {
+                write!(f, " ")?;
+            }
+            writeln!(f, "|")?;
+
+            // 1 | 00:32:00.a999999
+            write!(f, "{} | ", line_num)?;
+            writeln!(f, "{}", content)?;
+
+            //   |          ^
+            for _ in 0..=gutter {
+                write!(f, " ")?;
+            }
+            write!(f, "|")?;
+            for _ in 0..=column {
+                write!(f, " ")?;
+            }
+            // The span will be empty at eof, so we need to make sure we always print at least
+            // one `^`
+            write!(f, "^")?;
+            for _ in (span.start + 1)..(span.end.min(span.start + content.len())) {
+                write!(f, "^")?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "{}", self.message)?;
+        if !context && !self.keys.is_empty() {
+            writeln!(f, "in `{}`", self.keys.join("."))?;
+        }
+
+        Ok(())
     }
 }
 
 impl std::error::Error for Error {}
 
+/// Line and column of a byte index, both counted from 0, the column in characters
+fn translate_position(input: &[u8], index: usize) -> (usize, usize) {
+    if input.is_empty() {
+        return (0, index);
+    }
+
+    let safe_index = index.min(input.len() - 1);
+    let column_offset = index - safe_index;
+    let index = safe_index;
+
+    let nl = input[0..index]
+        .iter()
+        .rev()
+        .enumerate()
+        .find(|(_, b)| **b == b'\n')
+        .map(|(nl, _)| index - nl - 1);
+    let line_start = match nl {
+        Some(nl) => nl + 1,
+        None => 0,
+    };
+    let line = input[0..line_start].iter().filter(|b| **b == b'\n').count();
+
+    let column = std::str::from_utf8(&input[line_start..=index])
+        .map(|s| s.chars().count() - 1)
+        .unwrap_or_else(|_| index - line_start);
+    let column = column + column_offset;
+
+    (line, column)
+}
+
 /// Deserialization TOML document
 ///
 /// To deserializes TOML values, instead of documents, see [`ValueDeserializer`].
+///
+/// The document is parsed into tables that borrow the strings and keys needing no unescaping
+/// from the input, so `&str` and `#[serde(borrow)] Cow<str>` fields can borrow them too.
 #[cfg(feature = "parse")]
 pub struct Deserializer<'a> {
     input: &'a str,
+    unknown: Option<&'a mut Vec<UnknownKey>>,
 }
 
 #[cfg(feature = "parse")]
 impl<'a> Deserializer<'a> {
     /// Deserialization implementation for TOML.
     pub fn new(input: &'a str) -> Self {
-        Self { input }
+        Self {
+            input,
+            unknown: None,
+        }
+    }
+
+    /// Collect the keys in the document that the target type never consumes
+    ///
+    /// Unlike `#[serde(deny_unknown_fields)]`, deserialization still succeeds, so the caller
+    /// can warn about typos while newer documents keep working with older programs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     server: Server,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Server {
+    ///     port: u16,
+    /// }
+    ///
+    /// let input = "[server]\nport = 80\nprot = 80\n";
+    /// let mut unknown = Vec::new();
+    /// let config = Config::deserialize(
+    ///     toml::Deserializer::new(input).unknown_keys(&mut unknown)
+    /// ).unwrap();
+    ///
+    /// assert_eq!(config.server.port, 80);
+    /// assert_eq!(unknown[0].path(), "server.prot");
+    /// assert_eq!(&input[unknown[0].span().unwrap()], "prot");
+    /// ```
+    pub fn unknown_keys(mut self, unknown: &'a mut Vec<UnknownKey>) -> Self {
+        self.unknown = Some(unknown);
+        self
     }
 }
 
 #[cfg(feature = "parse")]
-impl<'de, 'a> serde::Deserializer<'de> for Deserializer<'a> {
+macro_rules! deserialize_inner {
+    ($de:ident . $method:ident ($($arg:expr),*)) => {{
+        let input = $de.input;
+        match parser::parse_document(input) {
+            Some(root) => {
+                let inner = item::ItemDeserializer::new(parser::Item::Table(root));
+                deserialize_inner!(@track $de, inner.$method($($arg),*)).map_err(|mut e| {
+                    e.set_original(Some(input.to_owned()));
+                    e
+                })
+            }
+            // `toml_edit` reports the error, or deserializes a document beyond the limits of
+            // the parser, like when its `unbounded` feature is enabled
+            None => {
+                let inner = input
+                    .parse::<toml_edit::de::Deserializer>()
+                    .map_err(|e| Error::from_edit(e, Some(input)))?;
+                deserialize_inner!(@track $de, inner.$method($($arg),*))
+                    .map_err(|e| Error::from_edit(e, Some(input)))
+            }
+        }
+    }};
+    (@track $de:ident, $inner:ident . $method:ident ($($arg:expr),*)) => {
+        match $de.unknown {
+            Some(unknown) => unknown::Track::new($inner, unknown).$method($($arg),*),
+            None => $inner.$method($($arg),*),
+        }
+    };
+}
+
+#[cfg(feature = "parse")]
+impl<'de, 'a: 'de> serde::Deserializer<'de> for Deserializer<'a> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        let inner = self
-            .input
-            .parse::<toml_edit::de::Deserializer>()
-            .map_err(Error::new)?;
-        inner.deserialize_any(visitor).map_err(Error::new)
+        deserialize_inner!(self.deserialize_any(visitor))
     }
 
     // `None` is interpreted as a missing field so be sure to implement `Some`
@@ -126,11 +511,7 @@ impl<'de, 'a> serde::Deserializer<'de> for Deserializer<'a> {
     where
         V: serde::de::Visitor<'de>,
     {
-        let inner = self
-            .input
-            .parse::<toml_edit::de::Deserializer>()
-            .map_err(Error::new)?;
-        inner.deserialize_option(visitor).map_err(Error::new)
+        deserialize_inner!(self.deserialize_option(visitor))
     }
 
     fn deserialize_newtype_struct<V>(
@@ -141,13 +522,7 @@ impl<'de, 'a> serde::Deserializer<'de> for Deserializer<'a> {
     where
         V: serde::de::Visitor<'de>,
     {
-        let inner = self
-            .input
-            .parse::<toml_edit::de::Deserializer>()
-            .map_err(Error::new)?;
-        inner
-            .deserialize_newtype_struct(name, visitor)
-            .map_err(Error::new)
+        deserialize_inner!(self.deserialize_newtype_struct(name, visitor))
     }
 
     fn deserialize_struct<V>(
@@ -159,13 +534,7 @@ impl<'de, 'a> serde::Deserializer<'de> for Deserializer<'a> {
     where
         V: serde::de::Visitor<'de>,
     {
-        let inner = self
-            .input
-            .parse::<toml_edit::de::Deserializer>()
-            .map_err(Error::new)?;
-        inner
-            .deserialize_struct(name, fields, visitor)
-            .map_err(Error::new)
+        deserialize_inner!(self.deserialize_struct(name, fields, visitor))
     }
 
     // Called when the type to deserialize is an enum, as opposed to a field in the type.
@@ -178,13 +547,7 @@ impl<'de, 'a> serde::Deserializer<'de> for Deserializer<'a> {
     where
         V: serde::de::Visitor<'de>,
     {
-        let inner = self
-            .input
-            .parse::<toml_edit::de::Deserializer>()
-            .map_err(Error::new)?;
-        inner
-            .deserialize_enum(name, variants, visitor)
-            .map_err(Error::new)
+        deserialize_inner!(self.deserialize_enum(name, variants, visitor))
     }
 
     serde::forward_to_deserialize_any! {
@@ -230,6 +593,12 @@ impl<'a> ValueDeserializer<'a> {
     pub fn new(input: &'a str) -> Self {
         Self { input }
     }
+
+    fn inner(&self) -> Result<toml_edit::de::ValueDeserializer, Error> {
+        self.input
+            .parse::<toml_edit::de::ValueDeserializer>()
+            .map_err(|e| Error::from_edit(e, Some(self.input)))
+    }
 }
 
 #[cfg(feature = "parse")]
@@ -240,11 +609,10 @@ impl<'de, 'a> serde::Deserializer<'de> for ValueDeserializer<'a> {
     where
         V: serde::de::Visitor<'de>,
     {
-        let inner = self
-            .input
-            .parse::<toml_edit::de::ValueDeserializer>()
-            .map_err(Error::new)?;
-        inner.deserialize_any(visitor).map_err(Error::new)
+        let inner = self.inner()?;
+        inner
+            .deserialize_any(visitor)
+            .map_err(|e| Error::from_edit(e, None))
     }
 
     // `None` is interpreted as a missing field so be sure to implement `Some`
@@ -253,11 +621,10 @@ impl<'de, 'a> serde::Deserializer<'de> for ValueDeserializer<'a> {
     where
         V: serde::de::Visitor<'de>,
     {
-        let inner = self
-            .input
-            .parse::<toml_edit::de::ValueDeserializer>()
-            .map_err(Error::new)?;
-        inner.deserialize_option(visitor).map_err(Error::new)
+        let inner = self.inner()?;
+        inner
+            .deserialize_option(visitor)
+            .map_err(|e| Error::from_edit(e, None))
     }
 
     fn deserialize_newtype_struct<V>(
@@ -268,13 +635,10 @@ impl<'de, 'a> serde::Deserializer<'de> for ValueDeserializer<'a> {
     where
         V: serde::de::Visitor<'de>,
     {
-        let inner = self
-            .input
-            .parse::<toml_edit::de::ValueDeserializer>()
-            .map_err(Error::new)?;
+        let inner = self.inner()?;
         inner
             .deserialize_newtype_struct(name, visitor)
-            .map_err(Error::new)
+            .map_err(|e| Error::from_edit(e, None))
     }
 
     fn deserialize_struct<V>(
@@ -286,13 +650,10 @@ impl<'de, 'a> serde::Deserializer<'de> for ValueDeserializer<'a> {
     where
         V: serde::de::Visitor<'de>,
     {
-        let inner = self
-            .input
-            .parse::<toml_edit::de::ValueDeserializer>()
-            .map_err(Error::new)?;
+        let inner = self.inner()?;
         inner
             .deserialize_struct(name, fields, visitor)
-            .map_err(Error::new)
+            .map_err(|e| Error::from_edit(e, None))
     }
 
     // Called when the type to deserialize is an enum, as opposed to a field in the type.
@@ -305,13 +666,10 @@ impl<'de, 'a> serde::Deserializer<'de> for ValueDeserializer<'a> {
     where
         V: serde::de::Visitor<'de>,
     {
-        let inner = self
-            .input
-            .parse::<toml_edit::de::ValueDeserializer>()
-            .map_err(Error::new)?;
+        let inner = self.inner()?;
         inner
             .deserialize_enum(name, variants, visitor)
-            .map_err(Error::new)
+            .map_err(|e| Error::from_edit(e, None))
     }
 
     serde::forward_to_deserialize_any! {
@@ -0,0 +1,805 @@
+//! Deserialize the tables of the [parser][super::parser], borrowing the strings and keys that
+//! need no unescaping.
+//!
+//! The behavior, the errors and their spans follow `toml_edit::de`, which works on an owned
+//! `toml_edit::Document` instead.
+//!
+//! For [`from_str_all`][super::from_str_all], a [`Recovery`] records the values that do not
+//! fit the type the target asks for, and the target gets a default of that type instead, so
+//! deserialization goes on to the remaining values.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::Error as _;
+use serde::de::IntoDeserializer as _;
+use serde::de::Unexpected;
+use serde_spanned::__unstable as spanned;
+
+use super::parser::{Item, Key, Table};
+use super::Error;
+
+/// Fill in the span of an error that has none
+fn with_span(span: Option<Range<usize>>) -> impl FnOnce(Error) -> Error {
+    move |mut e| {
+        if e.span().is_none() {
+            e.set_span(span);
+        }
+        e
+    }
+}
+
+/// Errors recorded while deserialization goes on past the values that fail
+#[derive(Default)]
+pub(crate) struct Recovery {
+    errors: Vec<Error>,
+    /// Whether the error being unwound may only follow from the values replaced before it,
+    /// decided where it leaves the first value
+    replaced: Option<bool>,
+}
+
+impl Recovery {
+    /// Look at the result of a value, `before` being the number of errors recorded when the
+    /// value started
+    fn unwind<T>(&mut self, before: usize, result: &Result<T, Error>) {
+        match result {
+            Ok(_) => self.replaced = None,
+            Err(e) => {
+                if self.replaced.is_none() {
+                    self.replaced = Some(before < self.errors.len() && !e.key_error);
+                }
+            }
+        }
+    }
+
+    /// The recorded errors, followed by the error deserialization stopped at, unless it may
+    /// only follow from the replaced values
+    pub(crate) fn finish<T>(mut self, result: Result<T, Error>) -> Result<T, Vec<Error>> {
+        self.unwind(0, &result);
+        match result {
+            Ok(value) if self.errors.is_empty() => Ok(value),
+            Ok(_) => Err(self.errors),
+            Err(e) => {
+                if self.replaced != Some(true) {
+                    self.errors.push(e);
+                }
+                Err(self.errors)
+            }
+        }
+    }
+}
+
+/// Kind of value asked for by a `deserialize_*` method, for replacing a value of another kind
+#[derive(Copy, Clone)]
+enum Kind {
+    Bool,
+    Integer(i128, i128),
+    Float,
+    Char,
+    Str,
+    Seq,
+    Map,
+}
+
+/// Deserializer for a value or a table
+pub(crate) struct ItemDeserializer<'r, 'de> {
+    item: Item<'de>,
+    validate_struct_keys: bool,
+    recovery: Option<&'r mut Recovery>,
+}
+
+impl<'r, 'de> ItemDeserializer<'r, 'de> {
+    pub(crate) fn new(item: Item<'de>) -> Self {
+        Self {
+            item,
+            validate_struct_keys: false,
+            recovery: None,
+        }
+    }
+
+    /// Record the values that do not fit the target and go on with a default instead
+    pub(crate) fn with_recovery(mut self, recovery: &'r mut Recovery) -> Self {
+        self.recovery = Some(recovery);
+        self
+    }
+
+    fn with_struct_key_validation(mut self) -> Self {
+        self.validate_struct_keys = true;
+        self
+    }
+
+    fn child(item: Item<'de>, recovery: Option<&'r mut Recovery>) -> Self {
+        Self {
+            item,
+            validate_struct_keys: false,
+            recovery,
+        }
+    }
+
+    fn deserialize_kind<V>(self, kind: Kind, visitor: V) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if self.recovery.is_none() {
+            return serde::Deserializer::deserialize_any(self, visitor);
+        }
+        let error = match (kind, &self.item) {
+            (Kind::Bool, Item::Boolean(..))
+            | (Kind::Float, Item::Float(..) | Item::Integer(..))
+            | (Kind::Str, Item::String(..))
+            | (Kind::Seq, Item::Array(..) | Item::ArrayOfTables(..))
+            | (Kind::Map, Item::Table(..) | Item::InlineTable(..) | Item::Datetime(..)) => None,
+            (Kind::Integer(min, max), Item::Integer(i, _)) => {
+                if min <= i128::from(*i) && i128::from(*i) <= max {
+                    None
+                } else {
+                    Some(Error::invalid_value(Unexpected::Signed(*i), &visitor))
+                }
+            }
+            (Kind::Char, Item::String(s, _)) => {
+                if s.chars().count() == 1 {
+                    None
+                } else {
+                    Some(Error::invalid_value(Unexpected::Str(s), &visitor))
+                }
+            }
+            (_, item) => Some(Error::invalid_type(unexpected(item), &visitor)),
+        };
+        let mut error = match error {
+            Some(error) => error,
+            None => return serde::Deserializer::deserialize_any(self, visitor),
+        };
+
+        let span = self.item.span();
+        error.set_span(span.clone());
+        if let Some(recovery) = self.recovery {
+            recovery.errors.push(error);
+        }
+        match kind {
+            Kind::Bool => visitor.visit_bool(false),
+            Kind::Integer(..) => visitor.visit_i64(0),
+            Kind::Float => visitor.visit_f64(0.0),
+            Kind::Char => visitor.visit_char('\0'),
+            Kind::Str => visitor.visit_borrowed_str(""),
+            Kind::Seq => visitor.visit_seq(ArraySeqAccess::new(Vec::new(), None)),
+            Kind::Map => visitor.visit_map(TableMapAccess::new(Table::default(), None)),
+        }
+        .map_err(with_span(span))
+    }
+}
+
+/// What the visitor would be given for the item
+fn unexpected<'i>(item: &'i Item<'_>) -> Unexpected<'i> {
+    match item {
+        Item::String(s, _) => Unexpected::Str(s),
+        Item::Integer(i, _) => Unexpected::Signed(*i),
+        Item::Float(f, _) => Unexpected::Float(*f),
+        Item::Boolean(b, _) => Unexpected::Bool(*b),
+        Item::Array(..) | Item::ArrayOfTables(..) => Unexpected::Seq,
+        Item::Datetime(..) | Item::InlineTable(..) | Item::Table(..) => Unexpected::Map,
+    }
+}
+
+macro_rules! deserialize_kind {
+    ($($method:ident => $kind:expr,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+            where
+                V: serde::de::Visitor<'de>,
+            {
+                self.deserialize_kind($kind, visitor)
+            }
+        )*
+    };
+}
+
+impl<'r, 'de> serde::Deserializer<'de> for ItemDeserializer<'r, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let span = self.item.span();
+        let recovery = self.recovery;
+        match self.item {
+            Item::String(Cow::Borrowed(s), _) => visitor.visit_borrowed_str(s),
+            Item::String(Cow::Owned(s), _) => visitor.visit_string(s),
+            Item::Integer(i, _) => visitor.visit_i64(i),
+            Item::Float(f, _) => visitor.visit_f64(f),
+            Item::Boolean(b, _) => visitor.visit_bool(b),
+            Item::Datetime(date, _) => visitor.visit_map(DatetimeDeserializer { date: Some(date) }),
+            Item::Array(values, _) => visitor.visit_seq(ArraySeqAccess::new(values, recovery)),
+            Item::InlineTable(table) | Item::Table(table) => {
+                visitor.visit_map(TableMapAccess::new(table, recovery))
+            }
+            Item::ArrayOfTables(tables, _) => visitor.visit_seq(ArraySeqAccess::new(
+                tables.into_iter().map(Item::Table).collect(),
+                recovery,
+            )),
+        }
+        .map_err(with_span(span))
+    }
+
+    // `None` is interpreted as a missing field so be sure to implement `Some`
+    // as a present field.
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let span = self.item.span();
+        visitor.visit_some(self).map_err(with_span(span))
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let span = self.item.span();
+        visitor.visit_newtype_struct(self).map_err(with_span(span))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if spanned::is_spanned(name, fields) {
+            if let Some(span) = self.item.span() {
+                return visitor.visit_map(SpannedDeserializer::new(self, span));
+            }
+        }
+
+        if name == toml_datetime::__unstable::NAME && fields == [toml_datetime::__unstable::FIELD] {
+            let span = self.item.span();
+            if let Item::Datetime(date, _) = self.item {
+                return visitor
+                    .visit_map(DatetimeDeserializer { date: Some(date) })
+                    .map_err(with_span(span));
+            }
+        }
+
+        if self.validate_struct_keys {
+            if let Item::Table(table) | Item::InlineTable(table) = &self.item {
+                validate_struct_keys(table, fields).map_err(with_span(table.span.clone()))?;
+            }
+        }
+
+        self.deserialize_any(visitor)
+    }
+
+    // Called when the type to deserialize is an enum, as opposed to a field in the type.
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let span = self.item.span();
+        match self.item {
+            Item::String(Cow::Borrowed(s), _) => {
+                visitor.visit_enum(BorrowedStrDeserializer::new(s))
+            }
+            Item::String(Cow::Owned(s), _) => visitor.visit_enum(s.into_deserializer()),
+            Item::InlineTable(table) | Item::Table(table) => {
+                if table.entries.is_empty() {
+                    Err(Error::custom(
+                        "wanted exactly 1 element, found 0 elements",
+                        table.span,
+                    ))
+                } else if table.entries.len() != 1 {
+                    Err(Error::custom(
+                        "wanted exactly 1 element, more than 1 element",
+                        table.span,
+                    ))
+                } else {
+                    visitor.visit_enum(TableMapAccess::new(table, self.recovery))
+                }
+            }
+            item => Err(Error::custom("wanted string or table", item.span())),
+        }
+        .map_err(with_span(span))
+    }
+
+    deserialize_kind! {
+        deserialize_bool => Kind::Bool,
+        deserialize_i8 => Kind::Integer(i8::MIN.into(), i8::MAX.into()),
+        deserialize_i16 => Kind::Integer(i16::MIN.into(), i16::MAX.into()),
+        deserialize_i32 => Kind::Integer(i32::MIN.into(), i32::MAX.into()),
+        deserialize_i64 => Kind::Integer(i64::MIN.into(), i64::MAX.into()),
+        deserialize_u8 => Kind::Integer(0, u8::MAX.into()),
+        deserialize_u16 => Kind::Integer(0, u16::MAX.into()),
+        deserialize_u32 => Kind::Integer(0, u32::MAX.into()),
+        deserialize_u64 => Kind::Integer(0, u64::MAX.into()),
+        deserialize_f32 => Kind::Float,
+        deserialize_f64 => Kind::Float,
+        deserialize_char => Kind::Char,
+        deserialize_str => Kind::Str,
+        deserialize_string => Kind::Str,
+        deserialize_seq => Kind::Seq,
+        deserialize_map => Kind::Map,
+    }
+
+    serde::forward_to_deserialize_any! {
+        bytes byte_buf unit
+        ignored_any unit_struct tuple_struct tuple identifier
+    }
+}
+
+impl<'r, 'de> serde::de::IntoDeserializer<'de, Error> for ItemDeserializer<'r, 'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+fn validate_struct_keys(table: &Table<'_>, fields: &'static [&'static str]) -> Result<(), Error> {
+    let extra_keys = table
+        .entries
+        .iter()
+        .filter(|(key, _)| !fields.contains(&key.name.as_ref()))
+        .map(|(key, _)| key)
+        .collect::<Vec<_>>();
+
+    match extra_keys.first() {
+        None => Ok(()),
+        Some(first) => Err(Error::custom(
+            format!(
+                "unexpected keys in table: {}, available keys: {}",
+                extra_keys
+                    .iter()
+                    .map(|k| k.name.as_ref())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                fields.join(", "),
+            ),
+            Some(first.span.clone()),
+        )),
+    }
+}
+
+struct TableMapAccess<'r, 'de> {
+    iter: std::vec::IntoIter<(Key<'de>, Item<'de>)>,
+    span: Option<Range<usize>>,
+    value: Option<(Key<'de>, Item<'de>)>,
+    recovery: Option<&'r mut Recovery>,
+}
+
+impl<'r, 'de> TableMapAccess<'r, 'de> {
+    fn new(table: Table<'de>, recovery: Option<&'r mut Recovery>) -> Self {
+        Self {
+            iter: table.entries.into_iter(),
+            span: table.span,
+            value: None,
+            recovery,
+        }
+    }
+}
+
+impl<'r, 'de> serde::de::MapAccess<'de> for TableMapAccess<'r, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: serde::de::DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                let span = Some(key.span.clone());
+                let ret = seed
+                    .deserialize(KeyDeserializer::new(key.name.clone(), span.clone()))
+                    .map(Some)
+                    .map_err(with_span(span));
+                self.value = Some((key, value));
+                ret
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some((key, value)) => {
+                let span = value.span();
+                let before = self.recovery.as_ref().map(|r| r.errors.len());
+                let ret = seed
+                    .deserialize(ItemDeserializer::child(value, self.recovery.as_deref_mut()))
+                    .map_err(|e| {
+                        let mut e = with_span(span)(e);
+                        e.add_key(key.name.to_string());
+                        e
+                    });
+                if let (Some(recovery), Some(before)) = (self.recovery.as_deref_mut(), before) {
+                    for e in &mut recovery.errors[before..] {
+                        e.add_key(key.name.to_string());
+                    }
+                    recovery.unwind(before, &ret);
+                }
+                ret
+            }
+            None => {
+                panic!("no more values in next_value_seed, internal error in ItemDeserializer")
+            }
+        }
+    }
+}
+
+impl<'r, 'de> serde::de::EnumAccess<'de> for TableMapAccess<'r, 'de> {
+    type Error = Error;
+    type Variant = TableEnumDeserializer<'r, 'de>;
+
+    fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self::Variant), Error>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        let (key, value) = match self.iter.next() {
+            Some(pair) => pair,
+            None => {
+                return Err(Error::custom(
+                    "expected table with exactly 1 entry, found empty table",
+                    self.span,
+                ));
+            }
+        };
+
+        let val = seed
+            .deserialize(KeyDeserializer::new(key.name, None))
+            .map_err(with_span(Some(key.span)))?;
+
+        Ok((
+            val,
+            TableEnumDeserializer {
+                value,
+                recovery: self.recovery,
+            },
+        ))
+    }
+}
+
+struct TableEnumDeserializer<'r, 'de> {
+    value: Item<'de>,
+    recovery: Option<&'r mut Recovery>,
+}
+
+impl<'r, 'de> serde::de::VariantAccess<'de> for TableEnumDeserializer<'r, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.value {
+            Item::Table(table) | Item::InlineTable(table) => {
+                if table.entries.is_empty() {
+                    Ok(())
+                } else {
+                    Err(Error::custom("expected empty table", table.span))
+                }
+            }
+            e => Err(Error::custom(
+                format!("expected table, found {}", e.type_name()),
+                e.span(),
+            )),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(ItemDeserializer::child(self.value, self.recovery))
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        match self.value {
+            Item::Table(table) | Item::InlineTable(table) => {
+                let mut values = Vec::with_capacity(len);
+                for (index, (key, value)) in table.entries.into_iter().enumerate() {
+                    if key.name.parse::<usize>().ok() != Some(index) {
+                        return Err(Error::custom(
+                            format!("expected table key `{}`, but was `{}`", index, key.name),
+                            Some(key.span),
+                        ));
+                    }
+                    values.push(value);
+                }
+
+                if values.len() == len {
+                    visitor.visit_seq(ArraySeqAccess::new(values, self.recovery))
+                } else {
+                    Err(Error::custom(
+                        format!("expected tuple with length {}", len),
+                        table.span,
+                    ))
+                }
+            }
+            e => Err(Error::custom(
+                format!("expected table, found {}", e.type_name()),
+                e.span(),
+            )),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        serde::de::Deserializer::deserialize_struct(
+            ItemDeserializer::child(self.value, self.recovery).with_struct_key_validation(),
+            "", // TODO: this should be the variant name
+            fields,
+            visitor,
+        )
+    }
+}
+
+struct ArraySeqAccess<'r, 'de> {
+    iter: std::vec::IntoIter<Item<'de>>,
+    recovery: Option<&'r mut Recovery>,
+}
+
+impl<'r, 'de> ArraySeqAccess<'r, 'de> {
+    fn new(values: Vec<Item<'de>>, recovery: Option<&'r mut Recovery>) -> Self {
+        Self {
+            iter: values.into_iter(),
+            recovery,
+        }
+    }
+}
+
+impl<'r, 'de> serde::de::SeqAccess<'de> for ArraySeqAccess<'r, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some(value) => {
+                let before = self.recovery.as_ref().map(|r| r.errors.len());
+                let ret =
+                    seed.deserialize(ItemDeserializer::child(value, self.recovery.as_deref_mut()));
+                if let (Some(recovery), Some(before)) = (self.recovery.as_deref_mut(), before) {
+                    recovery.unwind(before, &ret);
+                }
+                ret.map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Deserializer for a key, borrowed from the document when it needs no unescaping
+struct KeyDeserializer<'de> {
+    key: Cow<'de, str>,
+    span: Option<Range<usize>>,
+}
+
+impl<'de> KeyDeserializer<'de> {
+    fn new(key: Cow<'de, str>, span: Option<Range<usize>>) -> Self {
+        Self { key, span }
+    }
+}
+
+impl<'de> serde::Deserializer<'de> for KeyDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        match self.key {
+            Cow::Borrowed(key) => visitor.visit_borrowed_str(key),
+            Cow::Owned(key) => visitor.visit_string(key),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if spanned::is_spanned(name, fields) {
+            if let Some(span) = self.span {
+                let key = KeyDeserializer::new(self.key, None);
+                return visitor.visit_map(SpannedDeserializer::new(key, span));
+            }
+        }
+        self.deserialize_any(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string seq
+        bytes byte_buf map option unit newtype_struct
+        ignored_any unit_struct tuple_struct tuple identifier
+    }
+}
+
+impl<'de> serde::de::IntoDeserializer<'de, Error> for KeyDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> serde::de::EnumAccess<'de> for KeyDeserializer<'de> {
+    type Error = Error;
+    type Variant = UnitOnly;
+
+    fn variant_seed<T>(self, seed: T) -> Result<(T::Value, Self::Variant), Error>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self).map(|value| (value, UnitOnly))
+    }
+}
+
+/// The variant of an enum given by a key alone
+struct UnitOnly;
+
+impl<'de> serde::de::VariantAccess<'de> for UnitOnly {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, _seed: T) -> Result<T::Value, Error>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        Err(serde::de::Error::invalid_type(
+            serde::de::Unexpected::UnitVariant,
+            &"newtype variant",
+        ))
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        Err(serde::de::Error::invalid_type(
+            serde::de::Unexpected::UnitVariant,
+            &"tuple variant",
+        ))
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        Err(serde::de::Error::invalid_type(
+            serde::de::Unexpected::UnitVariant,
+            &"struct variant",
+        ))
+    }
+}
+
+struct DatetimeDeserializer {
+    date: Option<toml_datetime::Datetime>,
+}
+
+impl<'de> serde::de::MapAccess<'de> for DatetimeDeserializer {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: serde::de::DeserializeSeed<'de>,
+    {
+        if self.date.is_some() {
+            seed.deserialize(BorrowedStrDeserializer::new(
+                toml_datetime::__unstable::FIELD,
+            ))
+            .map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        if let Some(date) = self.date.take() {
+            seed.deserialize(date.to_string().into_deserializer())
+        } else {
+            panic!("next_value_seed called before next_key_seed")
+        }
+    }
+}
+
+struct SpannedDeserializer<T> {
+    start: Option<usize>,
+    end: Option<usize>,
+    value: Option<T>,
+}
+
+impl<T> SpannedDeserializer<T> {
+    fn new(value: T, span: Range<usize>) -> Self {
+        Self {
+            start: Some(span.start),
+            end: Some(span.end),
+            value: Some(value),
+        }
+    }
+}
+
+impl<'de, T> serde::de::MapAccess<'de> for SpannedDeserializer<T>
+where
+    T: serde::de::IntoDeserializer<'de, Error>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: serde::de::DeserializeSeed<'de>,
+    {
+        let field = if self.start.is_some() {
+            spanned::START_FIELD
+        } else if self.end.is_some() {
+            spanned::END_FIELD
+        } else if self.value.is_some() {
+            spanned::VALUE_FIELD
+        } else {
+            return Ok(None);
+        };
+        seed.deserialize(BorrowedStrDeserializer::new(field))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        if let Some(start) = self.start.take() {
+            seed.deserialize(start.into_deserializer())
+        } else if let Some(end) = self.end.take() {
+            seed.deserialize(end.into_deserializer())
+        } else if let Some(value) = self.value.take() {
+            seed.deserialize(value.into_deserializer())
+        } else {
+            panic!("next_value_seed called before next_key_seed")
+        }
+    }
+}
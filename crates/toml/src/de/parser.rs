@@ -0,0 +1,890 @@
+//! Parse a document into tables that borrow their keys and strings from the input.
+//!
+//! This follows the grammar and the table rules of `toml_edit`'s parser, but keeps no
+//! formatting, and a string is only allocated when it needs unescaping.  Invalid documents are
+//! only detected here, `toml_edit` is left to describe what is wrong with them.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+
+use toml_datetime::{Date, Datetime, Offset, Time};
+
+/// Nesting of arrays and inline tables, and number of keys in a dotted key, that `toml_edit`
+/// refuses
+const RECURSION_LIMIT: usize = 128;
+
+/// A key with its span, quotes included
+#[derive(Clone)]
+pub(crate) struct Key<'i> {
+    pub(crate) name: Cow<'i, str>,
+    pub(crate) span: Range<usize>,
+}
+
+/// A value, or a table of the document
+pub(crate) enum Item<'i> {
+    String(Cow<'i, str>, Range<usize>),
+    Integer(i64, Range<usize>),
+    Float(f64, Range<usize>),
+    Boolean(bool, Range<usize>),
+    Datetime(Datetime, Range<usize>),
+    Array(Vec<Item<'i>>, Range<usize>),
+    InlineTable(Table<'i>),
+    Table(Table<'i>),
+    ArrayOfTables(Vec<Table<'i>>, Option<Range<usize>>),
+}
+
+impl<'i> Item<'i> {
+    pub(crate) fn span(&self) -> Option<Range<usize>> {
+        match self {
+            Item::String(_, span)
+            | Item::Integer(_, span)
+            | Item::Float(_, span)
+            | Item::Boolean(_, span)
+            | Item::Datetime(_, span)
+            | Item::Array(_, span) => Some(span.clone()),
+            Item::InlineTable(table) | Item::Table(table) => table.span.clone(),
+            Item::ArrayOfTables(_, span) => span.clone(),
+        }
+    }
+
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            Item::String(..) => "string",
+            Item::Integer(..) => "integer",
+            Item::Float(..) => "float",
+            Item::Boolean(..) => "boolean",
+            Item::Datetime(..) => "datetime",
+            Item::Array(..) => "array",
+            Item::InlineTable(..) => "inline table",
+            Item::Table(..) => "table",
+            Item::ArrayOfTables(..) => "array of tables",
+        }
+    }
+}
+
+/// Key-value pairs in document order
+#[derive(Default)]
+pub(crate) struct Table<'i> {
+    pub(crate) entries: Vec<(Key<'i>, Item<'i>)>,
+    index: HashMap<Cow<'i, str>, usize>,
+    pub(crate) span: Option<Range<usize>>,
+    /// Created as the parent of another table, not by its own header
+    implicit: bool,
+    /// Created by a dotted key
+    dotted: bool,
+}
+
+impl<'i> Table<'i> {
+    fn get_mut(&mut self, name: &str) -> Option<&mut Item<'i>> {
+        let i = *self.index.get(name)?;
+        Some(&mut self.entries[i].1)
+    }
+
+    fn entry(&mut self, key: &Key<'i>, default: impl FnOnce() -> Item<'i>) -> &mut Item<'i> {
+        let i = match self.index.get(key.name.as_ref()) {
+            Some(i) => *i,
+            None => {
+                self.index.insert(key.name.clone(), self.entries.len());
+                self.entries.push((key.clone(), default()));
+                self.entries.len() - 1
+            }
+        };
+        &mut self.entries[i].1
+    }
+
+    /// Add a new key, `false` when it is already present
+    fn insert(&mut self, key: Key<'i>, item: Item<'i>) -> bool {
+        if self.index.contains_key(key.name.as_ref()) {
+            return false;
+        }
+        self.index.insert(key.name.clone(), self.entries.len());
+        self.entries.push((key, item));
+        true
+    }
+
+    /// Remove a key, keeping the order of the others
+    fn remove(&mut self, name: &str) -> Option<Item<'i>> {
+        let i = self.index.remove(name)?;
+        for j in self.index.values_mut() {
+            if i < *j {
+                *j -= 1;
+            }
+        }
+        Some(self.entries.remove(i).1)
+    }
+}
+
+/// Parse a document, `None` when it is not valid TOML
+pub(crate) fn parse_document(input: &str) -> Option<Table<'_>> {
+    let mut parser = Parser { input, pos: 0 };
+    let mut state = State::default();
+    parser.document(&mut state)?;
+    state.into_root()
+}
+
+/// Where the key-value pairs go, see `toml_edit`'s `ParseState`
+struct State<'i> {
+    root: Table<'i>,
+    current: Table<'i>,
+    current_is_array: bool,
+    current_path: Vec<Key<'i>>,
+}
+
+impl<'i> Default for State<'i> {
+    fn default() -> Self {
+        Self {
+            root: Table::default(),
+            current: Table {
+                span: Some(0..0),
+                ..Table::default()
+            },
+            current_is_array: false,
+            current_path: Vec::new(),
+        }
+    }
+}
+
+impl<'i> State<'i> {
+    fn into_root(mut self) -> Option<Table<'i>> {
+        self.finalize_table()?;
+        Some(self.root)
+    }
+
+    fn on_keyval(&mut self, path: Vec<Key<'i>>, key: Key<'i>, value: Item<'i>) -> Option<()> {
+        if let (Some(existing), Some(value)) = (self.current.span.clone(), value.span()) {
+            self.current.span = Some(existing.start..value.end);
+        }
+        let table = descend_path(&mut self.current, &path, true)?;
+        // Dotted keys cannot extend a table defined by a header, and the other way around
+        if table.dotted == path.is_empty() {
+            return None;
+        }
+        if table.insert(key, value) {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn on_header(&mut self, mut path: Vec<Key<'i>>, array: bool, span: Range<usize>) -> Option<()> {
+        self.finalize_table()?;
+        let key = path.pop()?;
+        let parent = descend_path(&mut self.root, &path, false)?;
+        if array {
+            let entry = parent.entry(&key, || Item::ArrayOfTables(Vec::new(), None));
+            if !matches!(entry, Item::ArrayOfTables(..)) {
+                return None;
+            }
+        } else if let Some(entry) = parent.remove(&key.name) {
+            // Only the tables implied by earlier headers can be defined later
+            match entry {
+                Item::Table(table) if table.implicit && !table.dotted => self.current = table,
+                _ => return None,
+            }
+        }
+        path.push(key);
+
+        self.current.implicit = false;
+        self.current.dotted = false;
+        self.current.span = Some(span);
+        self.current_is_array = array;
+        self.current_path = path;
+        Some(())
+    }
+
+    fn finalize_table(&mut self) -> Option<()> {
+        let mut table = std::mem::take(&mut self.current);
+        let mut path = std::mem::take(&mut self.current_path);
+        let key = match path.pop() {
+            Some(key) => key,
+            None => {
+                self.root = table;
+                return Some(());
+            }
+        };
+
+        let parent = descend_path(&mut self.root, &path, false)?;
+        if self.current_is_array {
+            match parent.entry(&key, || Item::ArrayOfTables(Vec::new(), None)) {
+                Item::ArrayOfTables(tables, span) => {
+                    tables.push(table);
+                    *span = match (
+                        tables.first().and_then(|t| t.span.clone()),
+                        tables.last().and_then(|t| t.span.clone()),
+                    ) {
+                        (Some(first), Some(last)) => Some(first.start..last.end),
+                        _ => None,
+                    };
+                }
+                _ => return None,
+            }
+        } else {
+            match parent.get_mut(&key.name) {
+                // The header of a table came after the headers of its children
+                Some(Item::Table(existing)) if existing.implicit => {
+                    std::mem::swap(existing, &mut table);
+                }
+                Some(_) => return None,
+                None => {
+                    parent.insert(key, Item::Table(table));
+                }
+            }
+        }
+        Some(())
+    }
+}
+
+/// Find the table at `path`, creating implicit tables on the way
+fn descend_path<'t, 'i>(
+    mut table: &'t mut Table<'i>,
+    path: &[Key<'i>],
+    dotted: bool,
+) -> Option<&'t mut Table<'i>> {
+    for key in path {
+        let entry = table.entry(key, || {
+            Item::Table(Table {
+                implicit: true,
+                dotted,
+                ..Table::default()
+            })
+        });
+        table = match entry {
+            Item::ArrayOfTables(tables, _) => tables.last_mut()?,
+            Item::Table(child) => {
+                if dotted && !child.implicit {
+                    return None;
+                }
+                child
+            }
+            _ => return None,
+        };
+    }
+    Some(table)
+}
+
+/// Find the inline table at `path`, creating the tables of dotted keys on the way
+fn descend_inline_path<'t, 'i>(
+    mut table: &'t mut Table<'i>,
+    path: &[Key<'i>],
+) -> Option<&'t mut Table<'i>> {
+    for key in path {
+        let entry = table.entry(key, || {
+            Item::InlineTable(Table {
+                dotted: true,
+                ..Table::default()
+            })
+        });
+        table = match entry {
+            Item::InlineTable(child) => child,
+            _ => return None,
+        };
+    }
+    Some(table)
+}
+
+struct Parser<'i> {
+    input: &'i str,
+    pos: usize,
+}
+
+impl<'i> Parser<'i> {
+    fn peek(&self) -> Option<u8> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<u8> {
+        self.input.as_bytes().get(self.pos + offset).copied()
+    }
+
+    fn rest(&self) -> &'i str {
+        &self.input[self.pos..]
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        let eaten = self.peek() == Some(b);
+        if eaten {
+            self.pos += 1;
+        }
+        eaten
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        let eaten = self.rest().starts_with(s);
+        if eaten {
+            self.pos += s.len();
+        }
+        eaten
+    }
+
+    /// Count the bytes from the current position that match
+    fn count(&self, mut f: impl FnMut(u8) -> bool) -> usize {
+        self.input.as_bytes()[self.pos..]
+            .iter()
+            .take_while(|b| f(**b))
+            .count()
+    }
+
+    fn ws(&mut self) {
+        self.pos += self.count(|b| b == b' ' || b == b'\t');
+    }
+
+    fn newline(&mut self) -> bool {
+        self.eat(b'\n') || self.eat_str("\r\n")
+    }
+
+    fn comment(&mut self) {
+        if self.eat(b'#') {
+            self.pos += self.count(|b| b == b'\t' || (0x20..=0x7e).contains(&b) || 0x80 <= b);
+        }
+    }
+
+    fn line_ending(&mut self) -> Option<()> {
+        if self.peek().is_none() || self.newline() {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn line_trailing(&mut self) -> Option<()> {
+        self.ws();
+        self.comment();
+        self.line_ending()
+    }
+
+    fn ws_comment_newline(&mut self) {
+        loop {
+            match self.peek() {
+                Some(b' ' | b'\t' | b'\n') => self.pos += 1,
+                Some(b'\r') if self.peek_at(1) == Some(b'\n') => self.pos += 2,
+                Some(b'#') => self.comment(),
+                _ => return,
+            }
+        }
+    }
+
+    fn document(&mut self, state: &mut State<'i>) -> Option<()> {
+        if self.rest().starts_with('\u{feff}') {
+            self.pos += '\u{feff}'.len_utf8();
+        }
+        self.ws();
+        while let Some(b) = self.peek() {
+            match b {
+                b'#' => {
+                    self.comment();
+                    self.line_ending()?;
+                }
+                b'[' => self.header(state)?,
+                b'\n' | b'\r' => {
+                    if !self.newline() {
+                        return None;
+                    }
+                }
+                _ => self.keyval(state)?,
+            }
+            self.ws();
+        }
+        Some(())
+    }
+
+    fn header(&mut self, state: &mut State<'i>) -> Option<()> {
+        let start = self.pos;
+        let array = self.eat_str("[[");
+        if !array {
+            self.pos += 1;
+        }
+        let path = self.key()?;
+        if !self.eat_str(if array { "]]" } else { "]" }) {
+            return None;
+        }
+        let span = start..self.pos;
+        self.line_trailing()?;
+        state.on_header(path, array, span)
+    }
+
+    fn keyval(&mut self, state: &mut State<'i>) -> Option<()> {
+        let mut path = self.key()?;
+        if !self.eat(b'=') {
+            return None;
+        }
+        self.ws();
+        let value = self.value(0)?;
+        self.line_trailing()?;
+        let key = path.pop()?;
+        state.on_keyval(path, key, value)
+    }
+
+    fn key(&mut self) -> Option<Vec<Key<'i>>> {
+        let mut path = Vec::new();
+        loop {
+            self.ws();
+            path.push(self.simple_key()?);
+            self.ws();
+            if !self.eat(b'.') {
+                break;
+            }
+        }
+        if RECURSION_LIMIT <= path.len() {
+            return None;
+        }
+        Some(path)
+    }
+
+    fn simple_key(&mut self) -> Option<Key<'i>> {
+        let start = self.pos;
+        let name = match self.peek()? {
+            b'"' => self.basic_string()?,
+            b'\'' => Cow::Borrowed(self.literal_string()?),
+            _ => {
+                let len = self.count(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+                if len == 0 {
+                    return None;
+                }
+                self.pos += len;
+                Cow::Borrowed(&self.input[start..self.pos])
+            }
+        };
+        Some(Key {
+            name,
+            span: start..self.pos,
+        })
+    }
+
+    fn value(&mut self, depth: usize) -> Option<Item<'i>> {
+        let start = self.pos;
+        let item = match self.peek()? {
+            b'"' if self.rest().starts_with("\"\"\"") => {
+                Item::String(self.ml_basic_string()?, 0..0)
+            }
+            b'"' => Item::String(self.basic_string()?, 0..0),
+            b'\'' if self.rest().starts_with("'''") => {
+                Item::String(self.ml_literal_string()?, 0..0)
+            }
+            b'\'' => Item::String(Cow::Borrowed(self.literal_string()?), 0..0),
+            b'[' => return self.array(depth),
+            b'{' => return self.inline_table(depth),
+            b't' if self.eat_str("true") => Item::Boolean(true, 0..0),
+            b'f' if self.eat_str("false") => Item::Boolean(false, 0..0),
+            b'i' if self.eat_str("inf") => Item::Float(f64::INFINITY, 0..0),
+            b'n' if self.eat_str("nan") => Item::Float(f64::NAN, 0..0),
+            b'+' | b'-' | b'0'..=b'9' => self.number_or_datetime()?,
+            _ => return None,
+        };
+        let span = start..self.pos;
+        Some(match item {
+            Item::String(s, _) => Item::String(s, span),
+            Item::Integer(i, _) => Item::Integer(i, span),
+            Item::Float(f, _) => Item::Float(f, span),
+            Item::Boolean(b, _) => Item::Boolean(b, span),
+            Item::Datetime(d, _) => Item::Datetime(d, span),
+            item => item,
+        })
+    }
+
+    fn array(&mut self, depth: usize) -> Option<Item<'i>> {
+        let start = self.pos;
+        self.pos += 1;
+        let depth = depth + 1;
+        if RECURSION_LIMIT <= depth {
+            return None;
+        }
+        let mut values = Vec::new();
+        loop {
+            self.ws_comment_newline();
+            if self.eat(b']') {
+                break;
+            }
+            values.push(self.value(depth)?);
+            self.ws_comment_newline();
+            if self.eat(b']') {
+                break;
+            }
+            if !self.eat(b',') {
+                return None;
+            }
+        }
+        Some(Item::Array(values, start..self.pos))
+    }
+
+    fn inline_table(&mut self, depth: usize) -> Option<Item<'i>> {
+        let start = self.pos;
+        self.pos += 1;
+        let depth = depth + 1;
+        if RECURSION_LIMIT <= depth {
+            return None;
+        }
+        let mut table = Table::default();
+        self.ws();
+        if !self.eat(b'}') {
+            loop {
+                let mut path = self.key()?;
+                if !self.eat(b'=') {
+                    return None;
+                }
+                self.ws();
+                let value = self.value(depth)?;
+                self.ws();
+                let key = path.pop()?;
+                if !descend_inline_path(&mut table, &path)?.insert(key, value) {
+                    return None;
+                }
+                if self.eat(b'}') {
+                    break;
+                }
+                if !self.eat(b',') {
+                    return None;
+                }
+            }
+        }
+        table.span = Some(start..self.pos);
+        Some(Item::InlineTable(table))
+    }
+
+    /// The string between double quotes, only allocated when it has escapes
+    fn basic_string(&mut self) -> Option<Cow<'i, str>> {
+        self.pos += 1;
+        let start = self.pos;
+        let mut owned: Option<String> = None;
+        let mut chunk = start;
+        loop {
+            match self.peek()? {
+                b'"' => break,
+                b'\\' => {
+                    let s = owned.get_or_insert_with(String::new);
+                    s.push_str(&self.input[chunk..self.pos]);
+                    self.pos += 1;
+                    s.push(self.escape()?);
+                    chunk = self.pos;
+                }
+                b'\t' | 0x20..=0x7e | 0x80..=0xff => self.pos += 1,
+                _ => return None,
+            }
+        }
+        let end = self.pos;
+        self.pos += 1;
+        Some(match owned {
+            Some(mut s) => {
+                s.push_str(&self.input[chunk..end]);
+                Cow::Owned(s)
+            }
+            None => Cow::Borrowed(&self.input[start..end]),
+        })
+    }
+
+    fn ml_basic_string(&mut self) -> Option<Cow<'i, str>> {
+        self.pos += 3;
+        self.newline();
+        let start = self.pos;
+        let mut owned: Option<String> = None;
+        let mut chunk = start;
+        let end = loop {
+            match self.peek()? {
+                b'"' => {
+                    if let Some(end) = self.ml_quotes(b'"')? {
+                        break end;
+                    }
+                }
+                b'\\' => {
+                    let s = owned.get_or_insert_with(String::new);
+                    s.push_str(&self.input[chunk..self.pos]);
+                    self.pos += 1;
+                    let escape = self.pos;
+                    self.ws();
+                    if self.newline() {
+                        // A line ending backslash trims the whitespace up to the next content
+                        while self.newline() || self.eat(b' ') || self.eat(b'\t') {}
+                    } else {
+                        self.pos = escape;
+                        s.push(self.escape()?);
+                    }
+                    chunk = self.pos;
+                }
+                b'\r' => self.crlf(&mut owned, &mut chunk)?,
+                b'\t' | b'\n' | 0x20..=0x7e | 0x80..=0xff => self.pos += 1,
+                _ => return None,
+            }
+        };
+        Some(match owned {
+            Some(mut s) => {
+                s.push_str(&self.input[chunk..end]);
+                Cow::Owned(s)
+            }
+            None => Cow::Borrowed(&self.input[start..end]),
+        })
+    }
+
+    fn literal_string(&mut self) -> Option<&'i str> {
+        self.pos += 1;
+        let start = self.pos;
+        loop {
+            match self.peek()? {
+                b'\'' => break,
+                b'\t' | 0x20..=0x26 | 0x28..=0x7e | 0x80..=0xff => self.pos += 1,
+                _ => return None,
+            }
+        }
+        let end = self.pos;
+        self.pos += 1;
+        Some(&self.input[start..end])
+    }
+
+    fn ml_literal_string(&mut self) -> Option<Cow<'i, str>> {
+        self.pos += 3;
+        self.newline();
+        let start = self.pos;
+        let mut owned: Option<String> = None;
+        let mut chunk = start;
+        let end = loop {
+            match self.peek()? {
+                b'\'' => {
+                    if let Some(end) = self.ml_quotes(b'\'')? {
+                        break end;
+                    }
+                }
+                b'\r' => self.crlf(&mut owned, &mut chunk)?,
+                b'\t' | b'\n' | 0x20..=0x26 | 0x28..=0x7e | 0x80..=0xff => self.pos += 1,
+                _ => return None,
+            }
+        };
+        Some(match owned {
+            Some(mut s) => {
+                s.push_str(&self.input[chunk..end]);
+                Cow::Owned(s)
+            }
+            None => Cow::Borrowed(&self.input[start..end]),
+        })
+    }
+
+    /// Skip a run of quotes in a multi-line string, returning the end of the string when the
+    /// run closes it
+    ///
+    /// Up to two quotes before the closing delimiter belong to the string.
+    fn ml_quotes(&mut self, quote: u8) -> Option<Option<usize>> {
+        let run = self.count(|b| b == quote);
+        self.pos += run;
+        match run {
+            1 | 2 => Some(None),
+            3..=5 => Some(Some(self.pos - 3)),
+            _ => None,
+        }
+    }
+
+    /// Normalize a CRLF of a multi-line string to LF
+    fn crlf(&mut self, owned: &mut Option<String>, chunk: &mut usize) -> Option<()> {
+        if self.peek_at(1) != Some(b'\n') {
+            return None;
+        }
+        let s = owned.get_or_insert_with(String::new);
+        s.push_str(&self.input[*chunk..self.pos]);
+        s.push('\n');
+        self.pos += 2;
+        *chunk = self.pos;
+        Some(())
+    }
+
+    /// The character of an escape, after the backslash
+    fn escape(&mut self) -> Option<char> {
+        let c = match self.peek()? {
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'\\' => '\\',
+            b'"' => '"',
+            b'u' => return self.hex_escape(4),
+            b'U' => return self.hex_escape(8),
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn hex_escape(&mut self, len: usize) -> Option<char> {
+        self.pos += 1;
+        let digits = self.input.get(self.pos..self.pos + len)?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        self.pos += len;
+        char::from_u32(u32::from_str_radix(digits, 16).ok()?)
+    }
+
+    fn number_or_datetime(&mut self) -> Option<Item<'i>> {
+        let digits = self.count(|b| b.is_ascii_digit());
+        if 4 <= digits && self.peek_at(4) == Some(b'-') {
+            return self.datetime().map(|d| Item::Datetime(d, 0..0));
+        }
+        if 2 <= digits && self.peek_at(2) == Some(b':') {
+            let time = self.time()?;
+            return Some(Item::Datetime(time.into(), 0..0));
+        }
+        self.number()
+    }
+
+    fn datetime(&mut self) -> Option<Datetime> {
+        let year = self.digits(4)? as u16;
+        self.pos += 1;
+        let month = self.digits(2).filter(|m| (1..=12).contains(m))? as u8;
+        if !self.eat(b'-') {
+            return None;
+        }
+        let day = self.digits(2).filter(|d| (1..=31).contains(d))? as u8;
+        let date = Date { year, month, day };
+
+        // Only a valid hour followed by a colon makes the time part of the datetime
+        let hour = self.input.get(self.pos + 1..self.pos + 3);
+        let has_time = matches!(self.peek(), Some(b'T' | b't' | b' '))
+            && hour.map_or(false, |h| h.parse::<u8>().map_or(false, |h| h <= 23))
+            && self.peek_at(3) == Some(b':');
+        if !has_time {
+            return Some(date.into());
+        }
+        self.pos += 1;
+        let time = self.time()?;
+        let offset = match self.peek() {
+            Some(b'Z' | b'z') => {
+                self.pos += 1;
+                Some(Offset::Z)
+            }
+            Some(sign @ (b'+' | b'-')) => {
+                self.pos += 1;
+                let hours = self.digits(2).filter(|h| *h <= 23)? as i16;
+                if !self.eat(b':') {
+                    return None;
+                }
+                let minutes = self.digits(2).filter(|m| *m <= 59)? as i16;
+                let minutes = hours * 60 + minutes;
+                Some(Offset::Custom {
+                    minutes: if sign == b'-' { -minutes } else { minutes },
+                })
+            }
+            _ => None,
+        };
+        Some(Datetime {
+            date: Some(date),
+            time: Some(time),
+            offset,
+        })
+    }
+
+    fn time(&mut self) -> Option<Time> {
+        let hour = self.digits(2).filter(|h| *h <= 23)? as u8;
+        self.pos += 1;
+        let minute = self.digits(2).filter(|m| *m <= 59)? as u8;
+        if !self.eat(b':') {
+            return None;
+        }
+        let second = self.digits(2).filter(|s| *s <= 60)? as u8;
+        let mut nanosecond = 0;
+        if self.peek() == Some(b'.') && matches!(self.peek_at(1), Some(b'0'..=b'9')) {
+            self.pos += 1;
+            let len = self.count(|b| b.is_ascii_digit());
+            // Digits beyond nanoseconds are ignored
+            let digits = &self.input[self.pos..self.pos + len.min(9)];
+            nanosecond = digits.parse::<u32>().ok()? * 10u32.pow(9 - digits.len() as u32);
+            self.pos += len;
+        }
+        Some(Time {
+            hour,
+            minute,
+            second,
+            nanosecond,
+        })
+    }
+
+    /// Exactly `len` digits
+    fn digits(&mut self, len: usize) -> Option<u32> {
+        let digits = self.input.get(self.pos..self.pos + len)?;
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        self.pos += len;
+        digits.parse().ok()
+    }
+
+    fn number(&mut self) -> Option<Item<'i>> {
+        let start = self.pos;
+        let radix = match self.rest().get(..2) {
+            Some("0x") => Some(16),
+            Some("0o") => Some(8),
+            Some("0b") => Some(2),
+            _ => None,
+        };
+        if let Some(radix) = radix {
+            self.pos += 2;
+            self.underscored_digits(|b| (b as char).is_digit(radix))?;
+            let digits = self.input[start + 2..self.pos].replace('_', "");
+            let value = i64::from_str_radix(&digits, radix).ok()?;
+            return Some(Item::Integer(value, 0..0));
+        }
+
+        if matches!(self.peek(), Some(b'+' | b'-')) {
+            self.pos += 1;
+        }
+        if self.eat_str("inf") {
+            let sign = if self.input.as_bytes()[start] == b'-' {
+                -1.0
+            } else {
+                1.0
+            };
+            return Some(Item::Float(sign * f64::INFINITY, 0..0));
+        }
+        if self.eat_str("nan") {
+            let nan = if self.input.as_bytes()[start] == b'-' {
+                -f64::NAN
+            } else {
+                f64::NAN
+            };
+            return Some(Item::Float(nan, 0..0));
+        }
+        match self.peek()? {
+            b'0' => self.pos += 1,
+            b'1'..=b'9' => self.underscored_digits(|b| b.is_ascii_digit())?,
+            _ => return None,
+        }
+
+        let mut float = false;
+        if self.eat(b'.') {
+            float = true;
+            self.underscored_digits(|b| b.is_ascii_digit())?;
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            float = true;
+            self.pos += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            self.underscored_digits(|b| b.is_ascii_digit())?;
+        }
+
+        let text = self.input[start..self.pos].replace('_', "");
+        if float {
+            // `f64` overflows to infinity, only `-inf` is left alone like `toml_edit` does
+            let value = text.parse::<f64>().ok()?;
+            if value == f64::INFINITY {
+                return None;
+            }
+            Some(Item::Float(value, 0..0))
+        } else {
+            text.parse::<i64>().ok().map(|i| Item::Integer(i, 0..0))
+        }
+    }
+
+    /// Digits with single underscores between them
+    fn underscored_digits(&mut self, digit: impl Fn(u8) -> bool) -> Option<()> {
+        if !self.peek().map_or(false, &digit) {
+            return None;
+        }
+        self.pos += 1;
+        loop {
+            match self.peek() {
+                Some(b) if digit(b) => self.pos += 1,
+                Some(b'_') if self.peek_at(1).map_or(false, &digit) => self.pos += 2,
+                Some(b'_') => return None,
+                _ => return Some(()),
+            }
+        }
+    }
+}
@@ -0,0 +1,910 @@
+//! Track the keys that the target type never consumes.
+//!
+//! The wrappers forward everything to the inner deserializer and visitor,
+//! only recording the path down to each value.  When a value of some key is
+//! skipped through `deserialize_ignored_any`, that key is unknown to the target.
+
+use std::ops::Range;
+
+use serde::de::value::{BorrowedStrDeserializer, StrDeserializer, StringDeserializer};
+use serde::de::IntoDeserializer as _;
+use serde::de::{
+    DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use serde_spanned::__unstable as spanned;
+
+const SPANNED_FIELDS: &[&str] = &[
+    spanned::START_FIELD,
+    spanned::END_FIELD,
+    spanned::VALUE_FIELD,
+];
+
+/// A key present in the input but never consumed by the target type
+///
+/// See [`Deserializer::unknown_keys`][crate::de::Deserializer::unknown_keys].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct UnknownKey {
+    keys: Vec<String>,
+    span: Option<Range<usize>>,
+}
+
+impl UnknownKey {
+    /// Keys from the root down to the unknown key, array elements are by index
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Dotted path to the key, like `servers.0.prot`
+    pub fn path(&self) -> String {
+        self.to_string()
+    }
+
+    /// The start/end index into the original document where the key is
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.span.clone()
+    }
+}
+
+impl std::fmt::Display for UnknownKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, key) in self.keys.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            f.write_str(&toml_edit::Key::new(key.as_str()).display_repr())?;
+        }
+        Ok(())
+    }
+}
+
+/// Where a value is in the document
+#[derive(Clone, Default)]
+struct Path {
+    keys: Vec<String>,
+    /// Span of the key, `None` when the value is not the value of a key
+    key: Option<Option<Range<usize>>>,
+}
+
+impl Path {
+    fn key(&self, key: String, span: Option<Range<usize>>) -> Self {
+        let mut keys = self.keys.clone();
+        keys.push(key);
+        Self {
+            keys,
+            key: Some(span),
+        }
+    }
+
+    fn index(&self, index: usize) -> Self {
+        let mut keys = self.keys.clone();
+        keys.push(index.to_string());
+        Self { keys, key: None }
+    }
+}
+
+/// Deserializer that reports the unknown keys below it
+pub(crate) struct Track<'u, D> {
+    de: D,
+    path: Path,
+    unknown: &'u mut Vec<UnknownKey>,
+}
+
+impl<'u, D> Track<'u, D> {
+    pub(crate) fn new(de: D, unknown: &'u mut Vec<UnknownKey>) -> Self {
+        Self {
+            de,
+            path: Path::default(),
+            unknown,
+        }
+    }
+
+    fn wrap<V>(self, visitor: V) -> (D, Wrap<'u, V>) {
+        let wrap = Wrap {
+            visitor,
+            path: self.path,
+            unknown: self.unknown,
+            spanned: false,
+        };
+        (self.de, wrap)
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident ($($arg:ident : $ty:ty),*))*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, D::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let (de, wrap) = self.wrap(visitor);
+                de.$method($($arg,)* wrap)
+            }
+        )*
+    };
+}
+
+impl<'de, 'u, D> Deserializer<'de> for Track<'u, D>
+where
+    D: Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if let Some(span) = self.path.key {
+            self.unknown.push(UnknownKey {
+                keys: self.path.keys,
+                span,
+            });
+        }
+        self.de.deserialize_ignored_any(visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let (de, mut wrap) = self.wrap(visitor);
+        wrap.spanned = spanned::is_spanned(name, fields);
+        de.deserialize_struct(name, fields, wrap)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.de.is_human_readable()
+    }
+
+    forward_deserialize! {
+        deserialize_any()
+        deserialize_bool()
+        deserialize_i8()
+        deserialize_i16()
+        deserialize_i32()
+        deserialize_i64()
+        deserialize_i128()
+        deserialize_u8()
+        deserialize_u16()
+        deserialize_u32()
+        deserialize_u64()
+        deserialize_u128()
+        deserialize_f32()
+        deserialize_f64()
+        deserialize_char()
+        deserialize_str()
+        deserialize_string()
+        deserialize_bytes()
+        deserialize_byte_buf()
+        deserialize_option()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_seq()
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_map()
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier()
+    }
+}
+
+/// Seed that deserializes through `Track`
+struct TrackSeed<'u, S> {
+    seed: S,
+    path: Path,
+    unknown: &'u mut Vec<UnknownKey>,
+}
+
+impl<'de, 'u, S> DeserializeSeed<'de> for TrackSeed<'u, S>
+where
+    S: DeserializeSeed<'de>,
+{
+    type Value = S::Value;
+
+    fn deserialize<D>(self, de: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let track = Track {
+            de,
+            path: self.path,
+            unknown: self.unknown,
+        };
+        self.seed.deserialize(track)
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident ($ty:ty))*) => {
+        $(
+            fn $method<E>(self, v: $ty) -> Result<V::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                self.visitor.$method(v)
+            }
+        )*
+    };
+}
+
+/// Seed that hands the key deserializer to the target, observing the key and its span
+struct KeySeed<S> {
+    seed: S,
+}
+
+impl<'de, S> DeserializeSeed<'de> for KeySeed<S>
+where
+    S: DeserializeSeed<'de>,
+{
+    type Value = (S::Value, String, Option<Range<usize>>);
+
+    fn deserialize<D>(self, de: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        de.deserialize_struct(
+            spanned::NAME,
+            SPANNED_FIELDS,
+            KeyVisitor { seed: self.seed },
+        )
+    }
+}
+
+/// Read the key with its span when the deserializer knows it, as a string otherwise
+struct KeyVisitor<S> {
+    seed: S,
+}
+
+impl<'de, S> Visitor<'de> for KeyVisitor<S>
+where
+    S: DeserializeSeed<'de>,
+{
+    type Value = (S::Value, String, Option<Range<usize>>);
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("a key")
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let value = self.seed.deserialize(StrDeserializer::new(s))?;
+        Ok((value, s.to_owned(), None))
+    }
+
+    fn visit_borrowed_str<E>(self, s: &'de str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let value = self.seed.deserialize(BorrowedStrDeserializer::new(s))?;
+        Ok((value, s.to_owned(), None))
+    }
+
+    fn visit_string<E>(self, s: String) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let key = s.clone();
+        let value = self.seed.deserialize(StringDeserializer::new(s))?;
+        Ok((value, key, None))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut seed = Some(self.seed);
+        let mut start = None;
+        let mut end = None;
+        let mut key = String::new();
+        let mut value = None;
+        while let Some(field) = map.next_key::<String>()? {
+            match field.as_str() {
+                spanned::START_FIELD => start = Some(map.next_value()?),
+                spanned::END_FIELD => end = Some(map.next_value()?),
+                _ => {
+                    let seed = seed
+                        .take()
+                        .ok_or_else(|| serde::de::Error::duplicate_field(spanned::VALUE_FIELD))?;
+                    value = Some(map.next_value_seed(ObserveSeed {
+                        seed,
+                        span: start.zip(end).map(|(start, end)| start..end),
+                        key: &mut key,
+                    })?);
+                }
+            }
+        }
+        let value = value.ok_or_else(|| serde::de::Error::missing_field(spanned::VALUE_FIELD))?;
+        let span = start.zip(end).map(|(start, end)| start..end);
+        Ok((value, key, span))
+    }
+}
+
+/// Seed that deserializes through `ObserveKey`
+struct ObserveSeed<'k, S> {
+    seed: S,
+    span: Option<Range<usize>>,
+    key: &'k mut String,
+}
+
+impl<'de, 'k, S> DeserializeSeed<'de> for ObserveSeed<'k, S>
+where
+    S: DeserializeSeed<'de>,
+{
+    type Value = S::Value;
+
+    fn deserialize<D>(self, de: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.seed.deserialize(ObserveKey {
+            de,
+            span: self.span,
+            key: self.key,
+        })
+    }
+}
+
+/// Key deserializer that records the key as the target reads it
+///
+/// The span was already read to find where the key is, so it is given again to a target
+/// asking for `Spanned`.
+struct ObserveKey<'k, D> {
+    de: D,
+    span: Option<Range<usize>>,
+    key: &'k mut String,
+}
+
+impl<'k, D> ObserveKey<'k, D> {
+    fn wrap<V>(self, visitor: V) -> (D, Observe<'k, V>) {
+        let observe = Observe {
+            visitor,
+            key: self.key,
+        };
+        (self.de, observe)
+    }
+}
+
+impl<'de, 'k, D> Deserializer<'de> for ObserveKey<'k, D>
+where
+    D: Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if spanned::is_spanned(name, fields) {
+            if let Some(span) = self.span.clone() {
+                return visitor.visit_map(SpannedKey {
+                    start: Some(span.start),
+                    end: Some(span.end),
+                    value: Some(ObserveKey {
+                        de: self.de,
+                        span: None,
+                        key: self.key,
+                    }),
+                });
+            }
+        }
+        let (de, observe) = self.wrap(visitor);
+        de.deserialize_struct(name, fields, observe)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.de.is_human_readable()
+    }
+
+    forward_deserialize! {
+        deserialize_any()
+        deserialize_bool()
+        deserialize_i8()
+        deserialize_i16()
+        deserialize_i32()
+        deserialize_i64()
+        deserialize_i128()
+        deserialize_u8()
+        deserialize_u16()
+        deserialize_u32()
+        deserialize_u64()
+        deserialize_u128()
+        deserialize_f32()
+        deserialize_f64()
+        deserialize_char()
+        deserialize_str()
+        deserialize_string()
+        deserialize_bytes()
+        deserialize_byte_buf()
+        deserialize_option()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_seq()
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_map()
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier()
+        deserialize_ignored_any()
+    }
+}
+
+/// `Spanned` of a key, with the key deserializer as value
+struct SpannedKey<'k, D> {
+    start: Option<usize>,
+    end: Option<usize>,
+    value: Option<ObserveKey<'k, D>>,
+}
+
+impl<'de, 'k, D> MapAccess<'de> for SpannedKey<'k, D>
+where
+    D: Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, D::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let field = if self.start.is_some() {
+            spanned::START_FIELD
+        } else if self.end.is_some() {
+            spanned::END_FIELD
+        } else if self.value.is_some() {
+            spanned::VALUE_FIELD
+        } else {
+            return Ok(None);
+        };
+        seed.deserialize(BorrowedStrDeserializer::new(field))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, D::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        if let Some(start) = self.start.take() {
+            seed.deserialize(start.into_deserializer())
+        } else if let Some(end) = self.end.take() {
+            seed.deserialize(end.into_deserializer())
+        } else if let Some(value) = self.value.take() {
+            seed.deserialize(value)
+        } else {
+            panic!("next_value_seed called before next_key_seed")
+        }
+    }
+}
+
+/// Visitor that records the key it is given
+struct Observe<'k, V> {
+    visitor: V,
+    key: &'k mut String,
+}
+
+impl<'de, 'k, V> Visitor<'de> for Observe<'k, V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.visitor.expecting(formatter)
+    }
+
+    forward_visit! {
+        visit_bool(bool)
+        visit_i8(i8)
+        visit_i16(i16)
+        visit_i32(i32)
+        visit_i64(i64)
+        visit_i128(i128)
+        visit_u8(u8)
+        visit_u16(u16)
+        visit_u32(u32)
+        visit_u64(u64)
+        visit_u128(u128)
+        visit_f32(f32)
+        visit_f64(f64)
+        visit_char(char)
+        visit_bytes(&[u8])
+        visit_borrowed_bytes(&'de [u8])
+        visit_byte_buf(Vec<u8>)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<V::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        *self.key = v.to_owned();
+        self.visitor.visit_str(v)
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<V::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        *self.key = v.to_owned();
+        self.visitor.visit_borrowed_str(v)
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<V::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.key.clone_from(&v);
+        self.visitor.visit_string(v)
+    }
+
+    fn visit_none<E>(self) -> Result<V::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visitor.visit_none()
+    }
+
+    fn visit_unit<E>(self) -> Result<V::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visitor.visit_unit()
+    }
+
+    fn visit_some<D>(self, de: D) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.visitor.visit_some(de)
+    }
+
+    fn visit_newtype_struct<D>(self, de: D) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.visitor.visit_newtype_struct(de)
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<V::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.visitor.visit_seq(seq)
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<V::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.visitor.visit_map(map)
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<V::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        self.visitor.visit_enum(ObserveEnum {
+            data,
+            key: self.key,
+        })
+    }
+}
+
+/// Enum given by a key, whose variant is the key
+struct ObserveEnum<'k, A> {
+    data: A,
+    key: &'k mut String,
+}
+
+impl<'de, 'k, A> EnumAccess<'de> for ObserveEnum<'k, A>
+where
+    A: EnumAccess<'de>,
+{
+    type Error = A::Error;
+    type Variant = A::Variant;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), A::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        self.data.variant_seed(ObserveSeed {
+            seed,
+            span: None,
+            key: self.key,
+        })
+    }
+}
+
+/// Visitor that wraps the nested structures it is given
+struct Wrap<'u, V> {
+    visitor: V,
+    path: Path,
+    unknown: &'u mut Vec<UnknownKey>,
+    /// Visiting `Spanned`, whose fields are not keys of the document
+    spanned: bool,
+}
+
+impl<'de, 'u, V> Visitor<'de> for Wrap<'u, V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.visitor.expecting(formatter)
+    }
+
+    forward_visit! {
+        visit_bool(bool)
+        visit_i8(i8)
+        visit_i16(i16)
+        visit_i32(i32)
+        visit_i64(i64)
+        visit_i128(i128)
+        visit_u8(u8)
+        visit_u16(u16)
+        visit_u32(u32)
+        visit_u64(u64)
+        visit_u128(u128)
+        visit_f32(f32)
+        visit_f64(f64)
+        visit_char(char)
+        visit_str(&str)
+        visit_borrowed_str(&'de str)
+        visit_string(String)
+        visit_bytes(&[u8])
+        visit_borrowed_bytes(&'de [u8])
+        visit_byte_buf(Vec<u8>)
+    }
+
+    fn visit_none<E>(self) -> Result<V::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visitor.visit_none()
+    }
+
+    fn visit_unit<E>(self) -> Result<V::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visitor.visit_unit()
+    }
+
+    fn visit_some<D>(self, de: D) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.visitor.visit_some(Track {
+            de,
+            path: self.path,
+            unknown: self.unknown,
+        })
+    }
+
+    fn visit_newtype_struct<D>(self, de: D) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.visitor.visit_newtype_struct(Track {
+            de,
+            path: self.path,
+            unknown: self.unknown,
+        })
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<V::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.visitor.visit_seq(TrackSeq {
+            seq,
+            path: self.path,
+            index: 0,
+            unknown: self.unknown,
+        })
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<V::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let map = TrackMap {
+            map,
+            path: self.path,
+            key: None,
+            unknown: self.unknown,
+            spanned: self.spanned,
+        };
+        self.visitor.visit_map(map)
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<V::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        self.visitor.visit_enum(TrackEnum {
+            data,
+            path: self.path,
+            unknown: self.unknown,
+        })
+    }
+}
+
+struct TrackSeq<'u, A> {
+    seq: A,
+    path: Path,
+    index: usize,
+    unknown: &'u mut Vec<UnknownKey>,
+}
+
+impl<'de, 'u, A> SeqAccess<'de> for TrackSeq<'u, A>
+where
+    A: SeqAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let path = self.path.index(self.index);
+        self.index += 1;
+        self.seq.next_element_seed(TrackSeed {
+            seed,
+            path,
+            unknown: self.unknown,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.seq.size_hint()
+    }
+}
+
+struct TrackMap<'u, A> {
+    map: A,
+    path: Path,
+    /// The path of the value for the last key
+    key: Option<Path>,
+    unknown: &'u mut Vec<UnknownKey>,
+    spanned: bool,
+}
+
+impl<'de, 'u, A> MapAccess<'de> for TrackMap<'u, A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.spanned {
+            self.key = Some(self.path.clone());
+            return self.map.next_key_seed(seed);
+        }
+        match self.map.next_key_seed(KeySeed { seed })? {
+            Some((value, key, span)) => {
+                self.key = Some(self.path.key(key, span));
+                Ok(Some(value))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, A::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let path = self.key.take().unwrap_or_else(|| self.path.clone());
+        self.map.next_value_seed(TrackSeed {
+            seed,
+            path,
+            unknown: self.unknown,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.map.size_hint()
+    }
+}
+
+struct TrackEnum<'u, A> {
+    data: A,
+    path: Path,
+    unknown: &'u mut Vec<UnknownKey>,
+}
+
+impl<'de, 'u, A> EnumAccess<'de> for TrackEnum<'u, A>
+where
+    A: EnumAccess<'de>,
+{
+    type Error = A::Error;
+    type Variant = TrackVariant<'u, A::Variant>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), A::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let ((value, key, span), variant) = self.data.variant_seed(KeySeed { seed })?;
+        let path = self.path.key(key, span);
+        Ok((
+            value,
+            TrackVariant {
+                variant,
+                path,
+                unknown: self.unknown,
+            },
+        ))
+    }
+}
+
+struct TrackVariant<'u, A> {
+    variant: A,
+    path: Path,
+    unknown: &'u mut Vec<UnknownKey>,
+}
+
+impl<'de, 'u, A> VariantAccess<'de> for TrackVariant<'u, A>
+where
+    A: VariantAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.variant.unit_variant()
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.variant.newtype_variant_seed(TrackSeed {
+            seed,
+            path: self.path,
+            unknown: self.unknown,
+        })
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let wrap = Wrap {
+            visitor,
+            path: self.path,
+            unknown: self.unknown,
+            spanned: false,
+        };
+        self.variant.tuple_variant(len, wrap)
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let wrap = Wrap {
+            visitor,
+            path: self.path,
+            unknown: self.unknown,
+            spanned: false,
+        };
+        self.variant.struct_variant(fields, wrap)
+    }
+}
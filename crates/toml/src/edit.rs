@@ -1,46 +1,3 @@
-#[cfg(feature = "parse")]
-pub(crate) mod de {
-    pub(crate) use toml_edit::de::Error;
-}
-
-#[cfg(not(feature = "parse"))]
-pub(crate) mod de {
-    /// Errors that can occur when deserializing a type.
-    #[derive(Debug, Clone, PartialEq, Eq)]
-    pub struct Error {
-        inner: String,
-    }
-
-    impl Error {
-        /// Add key while unwinding
-        pub fn add_key(&mut self, _key: String) {}
-
-        /// What went wrong
-        pub fn message(&self) -> &str {
-            self.inner.as_str()
-        }
-    }
-
-    impl serde::de::Error for Error {
-        fn custom<T>(msg: T) -> Self
-        where
-            T: std::fmt::Display,
-        {
-            Error {
-                inner: msg.to_string(),
-            }
-        }
-    }
-
-    impl std::fmt::Display for Error {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            self.inner.fmt(f)
-        }
-    }
-
-    impl std::error::Error for Error {}
-}
-
 #[cfg(feature = "display")]
 pub(crate) mod ser {
     pub(crate) use toml_edit::ser::Error;
@@ -1,9 +1,12 @@
+use crate::ser::{Comments, Settings};
+
 #[derive(Copy, Clone, Default)]
-pub(crate) struct DocumentFormatter {
-    pub(crate) multiline_array: bool,
+pub(crate) struct DocumentFormatter<'c> {
+    pub(crate) settings: Settings,
+    pub(crate) comments: Option<&'c Comments>,
 }
 
-impl toml_edit::visit_mut::VisitMut for DocumentFormatter {
+impl<'c> toml_edit::visit_mut::VisitMut for DocumentFormatter<'c> {
     fn visit_document_mut(&mut self, node: &mut toml_edit::Document) {
         toml_edit::visit_mut::visit_document_mut(self, node);
     }
@@ -35,26 +38,194 @@ impl toml_edit::visit_mut::VisitMut for DocumentFormatter {
         }
 
         toml_edit::visit_mut::visit_table_mut(self, node);
+
+        // Nested tables are already formatted, now decide how they are written in this one
+        for (key, item) in node.iter_mut() {
+            if let Some(width) = self.settings.inline_table_width {
+                if let Some(inline) = fit_inline(key.get(), item, width) {
+                    *item = toml_edit::Item::Value(inline);
+                    continue;
+                }
+            }
+            if self.settings.dotted_keys {
+                if let Some(table) = item.as_table_mut() {
+                    let sparse = match table.iter().next() {
+                        Some((_, toml_edit::Item::Value(_))) => true,
+                        Some((_, toml_edit::Item::Table(table))) => table.is_dotted(),
+                        _ => false,
+                    };
+                    if table.len() == 1 && sparse {
+                        table.set_dotted(true);
+                    }
+                }
+            }
+        }
     }
 
     fn visit_value_mut(&mut self, node: &mut toml_edit::Value) {
         node.decor_mut().clear();
 
+        if let (Some(literal), toml_edit::Value::String(s)) =
+            (self.settings.literal_strings, &*node)
+        {
+            if let Some(value) = string_repr(s.value(), literal).and_then(|r| r.parse().ok()) {
+                *node = value;
+            }
+        }
+
         toml_edit::visit_mut::visit_value_mut(self, node);
     }
 
     fn visit_array_mut(&mut self, node: &mut toml_edit::Array) {
         toml_edit::visit_mut::visit_array_mut(self, node);
 
-        if !self.multiline_array || (0..=1).contains(&node.len()) {
-            node.set_trailing("");
-            node.set_trailing_comma(false);
-        } else {
+        node.set_trailing("");
+        node.set_trailing_comma(false);
+        let too_long = self
+            .settings
+            .array_wrap_len
+            .map_or(false, |len| node.len() > len);
+        let too_wide = self.settings.array_wrap_width.map_or(false, |width| {
+            let line = node.to_string();
+            !node.is_empty() && (line.len() > width || line.contains('\n'))
+        });
+        if too_long || too_wide {
+            let indent = format!("\n{:indent$}", "", indent = self.settings.indent);
             for item in node.iter_mut() {
-                item.decor_mut().set_prefix("\n    ");
+                item.decor_mut().set_prefix(indent.as_str());
             }
             node.set_trailing("\n");
             node.set_trailing_comma(true);
         }
     }
 }
+
+/// Convert the table to inline table if `key = { .. }` fits in width
+fn fit_inline(key: &str, item: &toml_edit::Item, width: usize) -> Option<toml_edit::Value> {
+    let table = item.as_table()?;
+    let mut inline = table.clone().into_inline_table();
+    inline.fmt();
+    let inline = toml_edit::Value::InlineTable(inline);
+    let line = format!("{} = {}", toml_edit::Key::new(key).display_repr(), inline);
+    if line.len() <= width && !line.contains('\n') {
+        Some(inline)
+    } else {
+        None
+    }
+}
+
+/// Write string as literal or basic string, `None` if literal string cannot represent it
+fn string_repr(value: &str, literal: bool) -> Option<String> {
+    let multiline = value.contains('\n');
+    if literal {
+        let control = value
+            .chars()
+            .any(|c| (c <= '\u{1f}' && c != '\t' && c != '\n') || c == '\u{7f}');
+        if control || value.contains("\r\n") {
+            return None;
+        }
+        if !multiline && !value.contains('\'') {
+            return Some(format!("'{}'", value));
+        }
+        if multiline && !value.contains("'''") && !value.ends_with('\'') {
+            return Some(format!("'''\n{}'''", value));
+        }
+        return None;
+    }
+
+    let quote = if multiline { "\"\"\"\n" } else { "\"" };
+    let mut output = String::from(quote);
+    for c in value.chars() {
+        match c {
+            '\n' if multiline => output.push('\n'),
+            '\n' => output.push_str("\\n"),
+            '\t' => output.push_str("\\t"),
+            '\r' => output.push_str("\\r"),
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            c if c <= '\u{1f}' || c == '\u{7f}' => {
+                output.push_str(&format!("\\u{:04X}", c as u32));
+            }
+            c => output.push(c),
+        }
+    }
+    output.push_str(quote.trim_end());
+    Some(output)
+}
+
+/// Write the comment before the line of the key at `path`, `None` if the key is not found
+pub(crate) fn write_comment(
+    root: &mut toml_edit::Table,
+    path: &[toml_edit::Key],
+    comment: &str,
+) -> Option<()> {
+    // A value in dotted keys or inline table shares the line of its outermost key
+    let mut host = path.len().checked_sub(1)?;
+    let mut header = false;
+    let mut table: &dyn toml_edit::TableLike = root;
+    let mut line = None;
+    for (i, key) in path.iter().enumerate() {
+        let item = table.get(key.get())?;
+        if i + 1 == path.len() {
+            header = line.is_none() && (item.is_array_of_tables() || is_header(item));
+            host = line.unwrap_or(i);
+            break;
+        }
+        table = match item {
+            toml_edit::Item::Table(t) => {
+                if !t.is_dotted() {
+                    line = None;
+                } else if line.is_none() {
+                    line = Some(i);
+                }
+                t
+            }
+            toml_edit::Item::ArrayOfTables(a) => {
+                line = None;
+                a.get(0)?
+            }
+            toml_edit::Item::Value(toml_edit::Value::InlineTable(t)) => {
+                line = line.or(Some(i));
+                t
+            }
+            _ => return None,
+        };
+    }
+
+    let mut table: &mut dyn toml_edit::TableLike = root;
+    for key in &path[..host] {
+        table = match table.get_mut(key.get())? {
+            toml_edit::Item::Table(t) => t,
+            toml_edit::Item::ArrayOfTables(a) => a.get_mut(0)?,
+            toml_edit::Item::Value(toml_edit::Value::InlineTable(t)) => t,
+            _ => return None,
+        };
+    }
+
+    let key = path[host].get();
+    let mut lines = String::new();
+    for line in comment.lines() {
+        if line.is_empty() {
+            lines.push_str("#\n");
+        } else {
+            lines.push_str(&format!("# {}\n", line));
+        }
+    }
+    if header {
+        let table = match table.get_mut(key)? {
+            toml_edit::Item::Table(t) => t,
+            toml_edit::Item::ArrayOfTables(a) => a.get_mut(0)?,
+            _ => return None,
+        };
+        // Header is required to hold the comment
+        table.set_implicit(false);
+        table.decor_mut().set_prefix(format!("\n{}", lines));
+    } else {
+        table.key_decor_mut(key)?.set_prefix(lines);
+    }
+    Some(())
+}
+
+fn is_header(item: &toml_edit::Item) -> bool {
+    item.as_table().map_or(false, |t| !t.is_dotted())
+}
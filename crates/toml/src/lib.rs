@@ -165,10 +165,10 @@ mod table;
 
 #[cfg(feature = "parse")]
 #[doc(inline)]
-pub use crate::de::{from_str, Deserializer};
+pub use crate::de::{from_reader, from_slice, from_str, Deserializer};
 #[cfg(feature = "display")]
 #[doc(inline)]
-pub use crate::ser::{to_string, to_string_pretty, Serializer};
+pub use crate::ser::{to_string, to_string_pretty, to_writer, to_writer_pretty, Serializer};
 #[doc(inline)]
 pub use crate::value::Value;
 
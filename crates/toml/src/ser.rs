@@ -4,6 +4,12 @@
 //! into TOML documents (as strings). Note that some top-level functions here
 //! are also provided at the top of the crate.
 
+#[cfg(feature = "display")]
+mod stream;
+
+#[cfg(feature = "display")]
+pub use stream::StreamSerializer;
+
 /// Serialize the given data structure as a String of TOML.
 ///
 /// Serialization can fail if `T`'s implementation of `Serialize` decides to
@@ -73,6 +79,86 @@ where
     Ok(output)
 }
 
+/// Serialize the given data structure as TOML into the IO stream.
+///
+/// The output is the same as [`to_string`], written table by table as it is serialized, see
+/// [`StreamSerializer`] for what is still held in memory.  The value is serialized once without
+/// writing first, so nothing is written when it can't be serialized, only the tables that values
+/// come after are held back, and the document is built in memory like with [`to_string`] when it
+/// can't be streamed.  As many small writes are done, consider wrapping the writer in a
+/// [`std::io::BufWriter`].
+///
+/// # Examples
+///
+/// ```
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Config {
+///     name: String,
+///     port: u16,
+/// }
+///
+/// let config = Config {
+///     name: "app".to_owned(),
+///     port: 8080,
+/// };
+///
+/// let mut output = Vec::new();
+/// toml::to_writer(&mut output, &config).unwrap();
+/// assert_eq!(output, b"name = \"app\"\nport = 8080\n");
+/// ```
+#[cfg(feature = "display")]
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<(), Error>
+where
+    W: std::io::Write,
+    T: ?Sized + serde::ser::Serialize,
+{
+    write_stream(writer, Settings::new(), value)
+}
+
+/// Serialize the given data structure as "pretty" TOML into the IO stream.
+///
+/// This is identical to `to_writer` except the output has a more "pretty" output.
+/// See `Serializer::pretty` for more details.
+#[cfg(feature = "display")]
+pub fn to_writer_pretty<W, T>(writer: W, value: &T) -> Result<(), Error>
+where
+    W: std::io::Write,
+    T: ?Sized + serde::ser::Serialize,
+{
+    write_stream(writer, Settings::pretty(), value)
+}
+
+#[cfg(feature = "display")]
+fn write_stream<W, T>(writer: W, settings: Settings, value: &T) -> Result<(), Error>
+where
+    W: std::io::Write,
+    T: ?Sized + serde::ser::Serialize,
+{
+    let mut writer = stream::IoWriter {
+        dst: writer,
+        error: None,
+    };
+    // Serialization failing halfway would leave a partial document behind, and finding the tables
+    // that values come after lets all the others be written as they come
+    let mut check = StreamSerializer::with_settings(String::new(), settings).discard();
+    let result = if value.serialize(&mut check).is_ok() {
+        let plan = check.into_plan();
+        value.serialize(&mut StreamSerializer::with_settings(&mut writer, settings).with_plan(plan))
+    } else {
+        // Some documents, like an array mixing tables and values, can only be written once built
+        let mut output = String::new();
+        value.serialize(Serializer::with_settings(&mut output, settings))?;
+        std::fmt::Write::write_str(&mut writer, &output)
+            .map_err(|_| Error::new("failed to write the document"))
+    };
+    match writer.error {
+        Some(error) => Err(Error::new(error)),
+        None => result,
+    }
+}
+
 /// Errors that can occur when serializing a type.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Error {
@@ -142,7 +228,7 @@ impl std::error::Error for Error {}
 #[cfg(feature = "display")]
 pub struct Serializer<'d> {
     dst: &'d mut String,
-    settings: crate::fmt::DocumentFormatter,
+    settings: crate::fmt::DocumentFormatter<'d>,
 }
 
 #[cfg(feature = "display")]
@@ -163,9 +249,196 @@ impl<'d> Serializer<'d> {
     /// For greater customization, instead serialize to a
     /// [`toml_edit::Document`](https://docs.rs/toml_edit/latest/toml_edit/struct.Document.html).
     pub fn pretty(dst: &'d mut String) -> Self {
-        let mut ser = Serializer::new(dst);
-        ser.settings.multiline_array = true;
-        ser
+        Self::with_settings(dst, Settings::pretty())
+    }
+
+    /// Creates a new serializer formatting the document with the given [`Settings`]
+    pub fn with_settings(dst: &'d mut String, settings: Settings) -> Self {
+        Self {
+            dst,
+            settings: crate::fmt::DocumentFormatter {
+                settings,
+                comments: None,
+            },
+        }
+    }
+
+    /// Write [`Comments`] before the keys and tables they are attached to
+    pub fn comments(mut self, comments: &'d Comments) -> Self {
+        self.settings.comments = Some(comments);
+        self
+    }
+}
+
+/// Formatting policy for [`Serializer`]
+///
+/// ```
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Config {
+///     ports: Vec<u16>,
+///     owner: Owner,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Owner {
+///     name: String,
+/// }
+///
+/// let config = Config {
+///     ports: vec![8080, 8081, 8082],
+///     owner: Owner { name: "Tom".to_owned() },
+/// };
+///
+/// let settings = toml::ser::Settings::new()
+///     .array_wrap_len(2)
+///     .indent(2)
+///     .inline_table_width(40);
+/// let mut toml = String::new();
+/// config.serialize(toml::Serializer::with_settings(&mut toml, settings)).unwrap();
+/// assert_eq!(toml, "ports = [\n  8080,\n  8081,\n  8082,\n]\nowner = { name = \"Tom\" }\n");
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+#[cfg(feature = "display")]
+pub struct Settings {
+    pub(crate) array_wrap_len: Option<usize>,
+    pub(crate) array_wrap_width: Option<usize>,
+    pub(crate) indent: usize,
+    pub(crate) inline_table_width: Option<usize>,
+    pub(crate) literal_strings: Option<bool>,
+    pub(crate) dotted_keys: bool,
+}
+
+#[cfg(feature = "display")]
+impl Settings {
+    /// Compact policy, as used by [`Serializer::new`]
+    pub fn new() -> Self {
+        Self {
+            array_wrap_len: None,
+            array_wrap_width: None,
+            indent: 4,
+            inline_table_width: None,
+            literal_strings: None,
+            dotted_keys: false,
+        }
+    }
+
+    /// Pretty policy, as used by [`Serializer::pretty`]
+    ///
+    /// Arrays with more than one element are written one element per line.
+    pub fn pretty() -> Self {
+        Self::new().array_wrap_len(1)
+    }
+
+    /// Write one element per line when an array has more than `len` elements
+    pub fn array_wrap_len(mut self, len: usize) -> Self {
+        self.array_wrap_len = Some(len);
+        self
+    }
+
+    /// Write one element per line when an array doesn't fit in `width` characters
+    pub fn array_wrap_width(mut self, width: usize) -> Self {
+        self.array_wrap_width = Some(width);
+        self
+    }
+
+    /// Number of spaces to indent the elements of a wrapped array, default 4
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Write a nested table inline, as `key = { .. }`, when the line fits in `width` characters
+    pub fn inline_table_width(mut self, width: usize) -> Self {
+        self.inline_table_width = Some(width);
+        self
+    }
+
+    /// Prefer literal strings (`'..'`) over basic strings (`".."`)
+    ///
+    /// Strings that can't be written as literal are still written as basic strings.
+    /// By default, the style is chosen by the content of each string.
+    pub fn literal_strings(mut self, yes: bool) -> Self {
+        self.literal_strings = Some(yes);
+        self
+    }
+
+    /// Write a table with a single key as dotted key, as `a.b = 1`, instead of `[a]` header
+    pub fn dotted_keys(mut self, yes: bool) -> Self {
+        self.dotted_keys = yes;
+        self
+    }
+}
+
+#[cfg(feature = "display")]
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Comments to write before keys and tables, such as the documentation of a config struct
+///
+/// ```
+/// use serde::Serialize;
+///
+/// #[derive(Serialize, Default)]
+/// struct Config {
+///     name: String,
+///     server: Server,
+/// }
+///
+/// #[derive(Serialize, Default)]
+/// struct Server {
+///     port: u16,
+/// }
+///
+/// let comments = toml::ser::Comments::new()
+///     .comment("name", "Name of the application")
+///     .comment("server", "Server settings")
+///     .comment("server.port", "Port to listen on,\n0 for any free port");
+/// let mut toml = String::new();
+/// Config::default()
+///     .serialize(toml::Serializer::pretty(&mut toml).comments(&comments))
+///     .unwrap();
+/// assert_eq!(
+///     toml,
+///     "\
+/// ## Name of the application
+/// name = \"\"
+///
+/// ## Server settings
+/// [server]
+/// ## Port to listen on,
+/// ## 0 for any free port
+/// port = 0
+/// "
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+#[cfg(feature = "display")]
+pub struct Comments {
+    pub(crate) comments: Vec<(Vec<toml_edit::Key>, String)>,
+}
+
+#[cfg(feature = "display")]
+impl Comments {
+    /// Creates an empty set of comments
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach `comment` to the key at `path`, each line is written as `# line`
+    ///
+    /// The path is a dotted key, as `server.port`, and it goes through the first table of an
+    /// array of tables.  A path that isn't a valid dotted key is taken as a single key.
+    /// Keys not present in the serialized document are ignored.
+    pub fn comment(mut self, path: &str, comment: &str) -> Self {
+        let path = toml_edit::Key::parse(path).unwrap_or_else(|_| vec![toml_edit::Key::new(path)]);
+        self.comments.push((path, comment.to_owned()));
+        self
     }
 }
 
@@ -765,7 +1038,7 @@ mod internal {
     pub struct SerializeDocumentArray<'d> {
         inner: InnerSerializeDocumentSeq,
         dst: &'d mut String,
-        settings: DocumentFormatter,
+        settings: DocumentFormatter<'d>,
     }
 
     impl<'d> SerializeDocumentArray<'d> {
@@ -849,7 +1122,7 @@ mod internal {
     pub struct SerializeDocumentTable<'d> {
         inner: InnerSerializeDocumentTable,
         dst: &'d mut String,
-        settings: DocumentFormatter,
+        settings: DocumentFormatter<'d>,
     }
 
     impl<'d> SerializeDocumentTable<'d> {
@@ -907,11 +1180,9 @@ mod internal {
 
     pub(crate) fn write_document(
         dst: &mut String,
-        mut settings: DocumentFormatter,
+        mut settings: DocumentFormatter<'_>,
         value: Result<toml_edit::Value, crate::edit::ser::Error>,
     ) -> Result<(), Error> {
-        use std::fmt::Write;
-
         let value = value.map_err(Error::wrap)?;
         let mut table = match toml_edit::Item::Value(value).into_table() {
             Ok(i) => i,
@@ -923,8 +1194,16 @@ mod internal {
         use toml_edit::visit_mut::VisitMut as _;
         settings.visit_table_mut(&mut table);
 
+        if let Some(comments) = settings.comments {
+            for (path, comment) in &comments.comments {
+                crate::fmt::write_comment(&mut table, path, comment);
+            }
+        }
+
         let doc: toml_edit::Document = table.into();
-        write!(dst, "{}", doc).unwrap();
+        let doc = doc.to_string();
+        // A commented table leads with a blank line even when nothing is before it
+        dst.push_str(doc.strip_prefix('\n').unwrap_or(&doc));
 
         Ok(())
     }
@@ -0,0 +1,975 @@
+//! Serialize a document table by table, without building it in memory.
+
+use toml_edit::visit_mut::VisitMut as _;
+
+use super::{Error, Settings};
+use crate::fmt::DocumentFormatter;
+
+type InnerSerializeValueTable =
+    <toml_edit::ser::ValueSerializer as serde::Serializer>::SerializeMap;
+
+/// Serialization for TOML documents, writing to any [`std::fmt::Write`] as it goes.
+///
+/// Unlike [`Serializer`][super::Serializer], the document isn't built in memory: the table of
+/// the last field of a table, each element of an array of tables and each element of an array
+/// are written as soon as they are reached, so memory use is bounded by the nesting depth rather
+/// than the document size when the values of a table come before its tables, like in a lockfile.
+/// To write to a [`std::io::Write`], see [`to_writer`][super::to_writer].
+///
+/// The output is the same as with [`Serializer`][super::Serializer], but some parts of the
+/// document are still held in memory:
+/// - The tables of a field other than the last, until the end of their table, as values may
+///   still follow them.  [`to_writer`][super::to_writer] first finds the tables that values do
+///   follow, and only holds back those.
+/// - Arrays of values with [`Settings::array_wrap_width`], or with [`Settings::array_wrap_len`]
+///   and an unknown length, as wrapping depends on the whole array.
+///
+/// Tables are always written with a header, so [`Settings::inline_table_width`] and
+/// [`Settings::dotted_keys`] have no effect, and an array starting with a table is written as an
+/// array of tables, so a value further in it fails.  When serialization fails, the document up to
+/// the error is already written; [`to_writer`][super::to_writer] checks the value first instead.
+///
+/// ```
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Lock {
+///     version: u32,
+///     package: Vec<Package>,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Package {
+///     name: String,
+/// }
+///
+/// let lock = Lock {
+///     version: 3,
+///     package: vec![Package { name: "a".to_owned() }, Package { name: "b".to_owned() }],
+/// };
+/// let mut toml = String::new();
+/// lock.serialize(&mut toml::ser::StreamSerializer::new(&mut toml)).unwrap();
+/// assert_eq!(toml, "version = 3\n\n[[package]]\nname = \"a\"\n\n[[package]]\nname = \"b\"\n");
+/// ```
+pub struct StreamSerializer<W> {
+    dst: W,
+    settings: Settings,
+    started: bool,
+    discard: bool,
+    plan: Plan,
+}
+
+impl<W: std::fmt::Write> StreamSerializer<W> {
+    /// Creates a new serializer which will emit TOML into the writer provided.
+    pub fn new(dst: W) -> Self {
+        Self::with_settings(dst, Settings::new())
+    }
+
+    /// Apply a default "pretty" policy to the document, see [`Settings::pretty`]
+    pub fn pretty(dst: W) -> Self {
+        Self::with_settings(dst, Settings::pretty())
+    }
+
+    /// Creates a new serializer formatting the document with the given [`Settings`]
+    pub fn with_settings(dst: W, settings: Settings) -> Self {
+        Self {
+            dst,
+            settings,
+            started: false,
+            discard: false,
+            plan: Plan::default(),
+        }
+    }
+
+    /// Serialize without writing anything, to check that serialization succeeds and to find
+    /// which tables have values after them
+    pub(crate) fn discard(mut self) -> Self {
+        self.discard = true;
+        self
+    }
+
+    /// Which tables have values after them, found while serializing with [`Self::discard`]
+    pub(crate) fn into_plan(self) -> Plan {
+        Plan {
+            tables: 0,
+            late: self.plan.late,
+            known: true,
+        }
+    }
+
+    /// Hold back only the tables that values come after, as found by a first pass
+    pub(crate) fn with_plan(mut self, plan: Plan) -> Self {
+        self.plan = plan;
+        self
+    }
+
+    /// Unwrap the writer from the serializer
+    pub fn into_inner(self) -> W {
+        self.dst
+    }
+}
+
+/// Adapt [`std::io::Write`] to [`std::fmt::Write`], keeping the error to report.
+pub(crate) struct IoWriter<W> {
+    pub(crate) dst: W,
+    pub(crate) error: Option<std::io::Error>,
+}
+
+impl<W: std::io::Write> std::fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.dst.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            std::fmt::Error
+        })
+    }
+}
+
+/// Tables that values come after in their table, which have to be held back
+#[derive(Default)]
+pub(crate) struct Plan {
+    /// Number of tables reached so far, to identify them
+    tables: usize,
+    /// For each table with values after some of its tables, the position of its last value
+    late: std::collections::BTreeMap<usize, usize>,
+    /// Whether `late` is complete, from a first pass over the same value
+    known: bool,
+}
+
+/// Where to write, shared by all the nested tables
+struct Output<'a> {
+    dst: &'a mut dyn std::fmt::Write,
+    settings: Settings,
+    started: &'a mut bool,
+    discard: bool,
+    plan: &'a mut Plan,
+}
+
+impl<'a> Output<'a> {
+    fn reborrow(&mut self) -> Output<'_> {
+        Output {
+            dst: &mut *self.dst,
+            settings: self.settings,
+            started: &mut *self.started,
+            discard: self.discard,
+            plan: &mut *self.plan,
+        }
+    }
+
+    fn write(&mut self, args: std::fmt::Arguments<'_>) -> Result<(), Error> {
+        if self.discard {
+            return Ok(());
+        }
+        *self.started = true;
+        self.dst
+            .write_fmt(args)
+            .map_err(|_| Error::new("failed to write the document"))
+    }
+
+    fn write_held(&mut self, held: &mut Held) -> Result<(), Error> {
+        if held.text.is_empty() {
+            return Ok(());
+        }
+        let blank = if *self.started { "\n" } else { "" };
+        self.write(format_args!("{}{}", blank, held.text))?;
+        *held = Held::default();
+        Ok(())
+    }
+}
+
+/// Tables held back while values may still follow them in their table
+#[derive(Default)]
+struct Held {
+    text: String,
+    started: bool,
+}
+
+impl<'a, W: std::fmt::Write> serde::ser::Serializer for &'a mut StreamSerializer<W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = serde::ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = serde::ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = serde::ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = serde::ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = SerializeStreamTable<'a>;
+    type SerializeStruct = SerializeStreamTable<'a>;
+    type SerializeStructVariant = serde::ser::Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_none())
+    }
+
+    fn serialize_some<T>(self, v: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        v.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        v: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        v.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        Err(Error::unsupported_type(Some(name)))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(Error::unsupported_type(None))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(Error::unsupported_type(Some(name)))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        let output = Output {
+            dst: &mut self.dst,
+            settings: self.settings,
+            started: &mut self.started,
+            discard: self.discard,
+            plan: &mut self.plan,
+        };
+        SerializeStreamTable::new(output, Vec::new(), false, len)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(Error::unsupported_type(Some(name)))
+    }
+}
+
+/// A table being written, only its path and the tables of its fields are kept
+#[doc(hidden)]
+pub struct SerializeStreamTable<'a> {
+    output: Output<'a>,
+    id: usize,
+    path: Vec<toml_edit::Key>,
+    array: bool,
+    header: bool,
+    fields: usize,
+    held: Held,
+    tables: bool,
+    streamed: bool,
+    remaining: Option<usize>,
+    key: Option<String>,
+}
+
+impl<'a> SerializeStreamTable<'a> {
+    fn new(
+        output: Output<'a>,
+        path: Vec<toml_edit::Key>,
+        array: bool,
+        len: Option<usize>,
+    ) -> Result<Self, Error> {
+        let id = output.plan.tables;
+        output.plan.tables += 1;
+        let mut table = Self {
+            output,
+            id,
+            path,
+            array,
+            header: false,
+            fields: 0,
+            held: Held::default(),
+            tables: false,
+            streamed: false,
+            remaining: len,
+            key: None,
+        };
+        // Each element of an array of tables needs its header, even when empty
+        if array {
+            table.write_header()?;
+        }
+        Ok(table)
+    }
+
+    fn write_header(&mut self) -> Result<(), Error> {
+        if self.header || self.path.is_empty() {
+            return Ok(());
+        }
+        self.header = true;
+        let mut header = String::new();
+        for key in &self.path {
+            if !header.is_empty() {
+                header.push('.');
+            }
+            header.push_str(&key.display_repr());
+        }
+        let blank = if *self.output.started { "\n" } else { "" };
+        if self.array {
+            self.output.write(format_args!("{}[[{}]]\n", blank, header))
+        } else {
+            self.output.write(format_args!("{}[{}]\n", blank, header))
+        }
+    }
+
+    /// Where the tables of a field go: held back, unless no value follows them
+    fn tables_output(&mut self, last: bool) -> Result<Output<'_>, Error> {
+        self.tables = true;
+        let plan = &self.output.plan;
+        let hold = if plan.known {
+            plan.late
+                .get(&self.id)
+                .map_or(false, |&late| late > self.fields)
+        } else {
+            !last
+        };
+        if hold {
+            Ok(Output {
+                dst: &mut self.held.text,
+                settings: self.output.settings,
+                started: &mut self.held.started,
+                discard: self.output.discard,
+                plan: &mut *self.output.plan,
+            })
+        } else {
+            self.streamed = true;
+            self.output.write_held(&mut self.held)?;
+            Ok(self.output.reborrow())
+        }
+    }
+
+    fn skip(&mut self) -> bool {
+        let last = self.remaining == Some(1);
+        self.remaining = self.remaining.map(|n| n.saturating_sub(1));
+        last
+    }
+
+    fn write_field<T>(&mut self, key: &str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        let last = self.skip();
+        self.fields += 1;
+        let probe = Probe {
+            table: self,
+            key,
+            last,
+            element: false,
+        };
+        match value.serialize(probe) {
+            Ok(Probed::Value(value)) => self.write_value(key, value),
+            Ok(Probed::Written) => Ok(()),
+            Err(e) if e == Error::unsupported_none() => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Start the line of a value, up to its `=`
+    fn start_value(&mut self, key: &str) -> Result<(), Error> {
+        // Only when the length given by `serialize_struct` or `serialize_map` is wrong, or the
+        // value serializes differently than in the first pass
+        if self.streamed {
+            return Err(Error::new(format!(
+                "value `{}` must be serialized before tables",
+                key
+            )));
+        }
+        if self.tables && self.output.discard {
+            self.output.plan.late.insert(self.id, self.fields);
+        }
+        self.write_header()?;
+        let key = toml_edit::Key::new(key);
+        self.output.write(format_args!("{} = ", key.display_repr()))
+    }
+
+    fn write_value(&mut self, key: &str, mut value: toml_edit::Value) -> Result<(), Error> {
+        self.start_value(key)?;
+        let mut formatter = DocumentFormatter {
+            settings: self.output.settings,
+            comments: None,
+        };
+        formatter.visit_value_mut(&mut value);
+        self.output.write(format_args!("{}\n", value))
+    }
+
+    fn end_table(mut self) -> Result<(), Error> {
+        // Empty tables could be semantically meaningful, so make sure they are written
+        if !self.tables {
+            self.write_header()?;
+        }
+        self.output.write_held(&mut self.held)
+    }
+}
+
+impl<'a> serde::ser::SerializeMap for SerializeStreamTable<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, input: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        let key = input
+            .serialize(toml_edit::ser::ValueSerializer::new())
+            .map_err(Error::wrap)?;
+        match key {
+            toml_edit::Value::String(key) => {
+                self.key = Some(key.into_value());
+                Ok(())
+            }
+            _ => Err(Error::key_not_string()),
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        let key = self.key.take().expect("always called after serialize_key");
+        self.write_field(&key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.end_table()
+    }
+}
+
+impl<'a> serde::ser::SerializeStruct for SerializeStreamTable<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        self.write_field(key, value)
+    }
+
+    fn skip_field(&mut self, _key: &'static str) -> Result<(), Self::Error> {
+        self.skip();
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.end_table()
+    }
+}
+
+/// What a field turned out to be
+#[doc(hidden)]
+pub enum Probed {
+    /// A value to write in its table
+    Value(toml_edit::Value),
+    /// Tables, or an array of values written element by element
+    Written,
+}
+
+/// Write the value as table if it is one, otherwise return it to be written in its table
+struct Probe<'p, 'a> {
+    table: &'p mut SerializeStreamTable<'a>,
+    key: &'p str,
+    last: bool,
+    element: bool,
+}
+
+macro_rules! probe_value {
+    ($($method:ident($ty:ty),)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Self::Ok, Self::Error> {
+                toml_edit::ser::ValueSerializer::new()
+                    .$method(v)
+                    .map(Probed::Value)
+                    .map_err(Error::wrap)
+            }
+        )*
+    };
+}
+
+impl<'p, 'a> serde::ser::Serializer for Probe<'p, 'a> {
+    type Ok = Probed;
+    type Error = Error;
+    type SerializeSeq = ProbeArray<'p, 'a>;
+    type SerializeTuple = ProbeArray<'p, 'a>;
+    type SerializeTupleStruct = ProbeArray<'p, 'a>;
+    type SerializeTupleVariant = ProbeArray<'p, 'a>;
+    type SerializeMap = ProbeTable<'p>;
+    type SerializeStruct = ProbeTable<'p>;
+    type SerializeStructVariant = serde::ser::Impossible<Self::Ok, Self::Error>;
+
+    probe_value! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_none())
+    }
+
+    fn serialize_some<T>(self, v: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        v.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(Some("unit")))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Err(Error::unsupported_type(Some(name)))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        v: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        v.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        Err(Error::unsupported_type(Some(name)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        let settings = self.table.output.settings;
+        // Wrapping can only be decided up front from the length
+        let stream = !self.element
+            && settings.array_wrap_width.is_none()
+            && (settings.array_wrap_len.is_none() || len.is_some());
+        Ok(ProbeArray {
+            // Nested arrays can't hold arrays of tables
+            first: !self.element,
+            probe: self,
+            values: toml_edit::Array::new(),
+            tables: false,
+            stream,
+            wrapped: len
+                .zip(settings.array_wrap_len)
+                .map_or(false, |(len, wrap_len)| len > wrap_len),
+            written: 0,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        let mut path = self.table.path.clone();
+        path.push(toml_edit::Key::new(self.key));
+        let output = self.table.tables_output(self.last)?;
+        let table = SerializeStreamTable::new(output, path, self.element, len)?;
+        Ok(ProbeTable::Table(table))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        if name == toml_datetime::__unstable::NAME {
+            let inner = toml_edit::ser::ValueSerializer::new()
+                .serialize_struct(name, len)
+                .map_err(Error::wrap)?;
+            Ok(ProbeTable::Value(inner))
+        } else {
+            self.serialize_map(Some(len))
+        }
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(Error::unsupported_type(Some(name)))
+    }
+}
+
+/// Array of tables or of values written element by element, or array of values to return
+#[doc(hidden)]
+pub struct ProbeArray<'p, 'a> {
+    probe: Probe<'p, 'a>,
+    values: toml_edit::Array,
+    first: bool,
+    tables: bool,
+    stream: bool,
+    wrapped: bool,
+    written: usize,
+}
+
+impl<'p, 'a> ProbeArray<'p, 'a> {
+    fn element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        if !self.first && !self.tables {
+            let value = value
+                .serialize(toml_edit::ser::ValueSerializer::new())
+                .map_err(Error::wrap)?;
+            return self.push(value);
+        }
+        self.first = false;
+        let probe = Probe {
+            table: &mut *self.probe.table,
+            key: self.probe.key,
+            last: self.probe.last,
+            element: true,
+        };
+        match value.serialize(probe)? {
+            Probed::Written => {
+                self.tables = true;
+                Ok(())
+            }
+            Probed::Value(_) if self.tables => Err(Error::new(
+                "values can't be mixed with tables in an array of tables",
+            )),
+            Probed::Value(value) => self.push(value),
+        }
+    }
+
+    fn push(&mut self, mut value: toml_edit::Value) -> Result<(), Error> {
+        if !self.stream {
+            self.values.push_formatted(value);
+            return Ok(());
+        }
+        let table = &mut *self.probe.table;
+        if self.written == 0 {
+            table.start_value(self.probe.key)?;
+            table.output.write(format_args!("["))?;
+        }
+        let mut formatter = DocumentFormatter {
+            settings: table.output.settings,
+            comments: None,
+        };
+        formatter.visit_value_mut(&mut value);
+        if self.wrapped {
+            let indent = " ".repeat(table.output.settings.indent);
+            table.output.write(format_args!("\n{}{},", indent, value))?;
+        } else if self.written == 0 {
+            table.output.write(format_args!("{}", value))?;
+        } else {
+            table.output.write(format_args!(", {}", value))?;
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    fn end_array(self) -> Result<Probed, Error> {
+        if self.tables {
+            Ok(Probed::Written)
+        } else if self.written == 0 {
+            Ok(Probed::Value(toml_edit::Value::Array(self.values)))
+        } else if self.wrapped {
+            self.probe.table.output.write(format_args!("\n]\n"))?;
+            Ok(Probed::Written)
+        } else {
+            self.probe.table.output.write(format_args!("]\n"))?;
+            Ok(Probed::Written)
+        }
+    }
+}
+
+impl<'p, 'a> serde::ser::SerializeSeq for ProbeArray<'p, 'a> {
+    type Ok = Probed;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.end_array()
+    }
+}
+
+impl<'p, 'a> serde::ser::SerializeTuple for ProbeArray<'p, 'a> {
+    type Ok = Probed;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.end_array()
+    }
+}
+
+impl<'p, 'a> serde::ser::SerializeTupleStruct for ProbeArray<'p, 'a> {
+    type Ok = Probed;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.end_array()
+    }
+}
+
+impl<'p, 'a> serde::ser::SerializeTupleVariant for ProbeArray<'p, 'a> {
+    type Ok = Probed;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.end_array()
+    }
+}
+
+/// Table written as it goes, or a datetime to return
+#[doc(hidden)]
+pub enum ProbeTable<'a> {
+    Table(SerializeStreamTable<'a>),
+    Value(InnerSerializeValueTable),
+}
+
+impl<'a> serde::ser::SerializeMap for ProbeTable<'a> {
+    type Ok = Probed;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, input: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        match self {
+            ProbeTable::Table(table) => table.serialize_key(input),
+            ProbeTable::Value(inner) => inner.serialize_key(input).map_err(Error::wrap),
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        match self {
+            ProbeTable::Table(table) => table.serialize_value(value),
+            ProbeTable::Value(inner) => inner.serialize_value(value).map_err(Error::wrap),
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        match self {
+            ProbeTable::Table(table) => table.end_table().map(|_| Probed::Written),
+            ProbeTable::Value(inner) => inner.end().map(Probed::Value).map_err(Error::wrap),
+        }
+    }
+}
+
+impl<'a> serde::ser::SerializeStruct for ProbeTable<'a> {
+    type Ok = Probed;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::ser::Serialize,
+    {
+        match self {
+            ProbeTable::Table(table) => table.write_field(key, value),
+            ProbeTable::Value(inner) => {
+                serde::ser::SerializeStruct::serialize_field(inner, key, value).map_err(Error::wrap)
+            }
+        }
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        match self {
+            ProbeTable::Table(table) => serde::ser::SerializeStruct::skip_field(table, key),
+            ProbeTable::Value(inner) => {
+                serde::ser::SerializeStruct::skip_field(inner, key).map_err(Error::wrap)
+            }
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        match self {
+            ProbeTable::Table(table) => table.end_table().map(|_| Probed::Written),
+            ProbeTable::Value(inner) => serde::ser::SerializeStruct::end(inner)
+                .map(Probed::Value)
+                .map_err(Error::wrap),
+        }
+    }
+}
@@ -620,9 +620,26 @@ impl<'de> de::Deserializer<'de> for Value {
         visitor.visit_newtype_struct(self)
     }
 
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, crate::de::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if name == datetime::NAME && fields == [datetime::FIELD] {
+            if let Value::Datetime(date) = self {
+                return visitor.visit_map(DatetimeDeserializer { date: Some(date) });
+            }
+        }
+        self.deserialize_any(visitor)
+    }
+
     serde::forward_to_deserialize_any! {
         bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string unit seq
-        bytes byte_buf map unit_struct tuple_struct struct
+        bytes byte_buf map unit_struct tuple_struct
         tuple ignored_any identifier
     }
 }
@@ -712,6 +729,36 @@ impl<'de> de::MapAccess<'de> for MapDeserializer {
     }
 }
 
+struct DatetimeDeserializer {
+    date: Option<Datetime>,
+}
+
+impl<'de> de::MapAccess<'de> for DatetimeDeserializer {
+    type Error = crate::de::Error;
+
+    fn next_key_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, crate::de::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if self.date.is_some() {
+            seed.deserialize(Value::String(datetime::FIELD.to_owned()))
+                .map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value, crate::de::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.date.take() {
+            Some(date) => seed.deserialize(Value::String(date.to_string())),
+            None => Err(de::Error::custom("value is missing")),
+        }
+    }
+}
+
 impl<'de> de::IntoDeserializer<'de, crate::de::Error> for Value {
     type Deserializer = Self;
 
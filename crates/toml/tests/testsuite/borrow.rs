@@ -0,0 +1,76 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Package<'a> {
+    name: &'a str,
+    #[serde(borrow)]
+    description: Cow<'a, str>,
+    authors: Vec<&'a str>,
+    #[serde(borrow, default)]
+    dependencies: BTreeMap<&'a str, Dependency<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Dependency<'a> {
+    version: &'a str,
+    #[serde(borrow, default)]
+    features: Vec<&'a str>,
+}
+
+#[test]
+fn borrowed_strings_and_keys() {
+    let input = r#"
+name = "toml"
+description = 'A "native" parser'
+authors = ["Alex", """
+Multi""", '''
+Line''']
+
+[dependencies]
+serde = { version = "1.0", features = ['derive'] }
+"toml_edit" = { version = "0.19" }
+"#;
+    let package: Package<'_> = toml::from_str(input).unwrap();
+    assert_eq!(package.name, "toml");
+    assert_eq!(package.description, r#"A "native" parser"#);
+    assert!(matches!(package.description, Cow::Borrowed(_)));
+    assert_eq!(package.authors, ["Alex", "Multi", "Line"]);
+    assert_eq!(
+        package.dependencies.keys().copied().collect::<Vec<_>>(),
+        ["serde", "toml_edit"]
+    );
+    assert_eq!(package.dependencies["serde"].version, "1.0");
+    assert_eq!(package.dependencies["serde"].features, ["derive"]);
+    assert_eq!(package.dependencies["toml_edit"].version, "0.19");
+
+    let start = input.as_ptr() as usize;
+    let end = start + input.len();
+    let within = |s: &str| (start..end).contains(&(s.as_ptr() as usize));
+    assert!(within(package.name));
+    assert!(within(&package.description));
+    assert!(package.dependencies.keys().all(|key| within(key)));
+}
+
+#[test]
+fn escaped_string_is_not_borrowed() {
+    let err =
+        toml::from_str::<Package<'_>>("name = \"a\\u0062\"\ndescription = ''\nauthors = []\n")
+            .unwrap_err();
+    snapbox::assert_eq(
+        r#"TOML parse error at line 1, column 8
+  |
+1 | name = "a\u0062"
+  |        ^^^^^^^^^
+invalid type: string "ab", expected a borrowed string
+"#,
+        err.to_string(),
+    );
+
+    let package: Package<'_> =
+        toml::from_slice(b"name = 'a'\ndescription = \"line\\n\"\nauthors = []\n").unwrap();
+    assert_eq!(package.description, "line\n");
+    assert!(matches!(package.description, Cow::Owned(_)));
+}
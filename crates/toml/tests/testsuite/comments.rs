@@ -0,0 +1,160 @@
+use serde::Serialize;
+use snapbox::assert_eq;
+
+#[derive(Serialize)]
+struct Config {
+    name: String,
+    tags: Vec<String>,
+    server: Server,
+    backends: Vec<Backend>,
+}
+
+#[derive(Serialize)]
+struct Server {
+    port: u16,
+    limits: Limits,
+    tls: Tls,
+}
+
+#[derive(Serialize)]
+struct Limits {
+    conn: u32,
+}
+
+#[derive(Serialize)]
+struct Tls {
+    cert: String,
+}
+
+#[derive(Serialize)]
+struct Backend {
+    host: String,
+}
+
+fn config() -> Config {
+    Config {
+        name: "app".to_owned(),
+        tags: vec!["a".to_owned(), "b".to_owned()],
+        server: Server {
+            port: 80,
+            limits: Limits { conn: 10 },
+            tls: Tls {
+                cert: "a.pem".to_owned(),
+            },
+        },
+        backends: vec![
+            Backend {
+                host: "a".to_owned(),
+            },
+            Backend {
+                host: "b".to_owned(),
+            },
+        ],
+    }
+}
+
+fn serialize(settings: toml::ser::Settings, comments: &toml::ser::Comments) -> String {
+    let mut result = String::new();
+    config()
+        .serialize(toml::Serializer::with_settings(&mut result, settings).comments(comments))
+        .unwrap();
+    result
+}
+
+#[test]
+fn comment_keys_and_tables() {
+    let comments = toml::ser::Comments::new()
+        .comment("tags", "Labels\n\nfor search")
+        .comment("server", "Server settings")
+        .comment("server.limits.conn", "Max connections")
+        .comment("server.tls", "TLS")
+        .comment("backends", "Backends to proxy")
+        .comment("backends.host", "Host name")
+        .comment("nokey", "Ignored")
+        .comment("server.port.nokey", "Ignored");
+    assert_eq(
+        "\
+name = \"app\"
+# Labels
+#
+# for search
+tags = [
+    \"a\",
+    \"b\",
+]
+
+# Server settings
+[server]
+port = 80
+
+[server.limits]
+# Max connections
+conn = 10
+
+# TLS
+[server.tls]
+cert = \"a.pem\"
+
+# Backends to proxy
+[[backends]]
+# Host name
+host = \"a\"
+
+[[backends]]
+host = \"b\"
+",
+        serialize(toml::ser::Settings::pretty(), &comments),
+    );
+}
+
+#[test]
+fn comment_dotted_and_inline() {
+    let comments = toml::ser::Comments::new()
+        .comment("server.limits.conn", "Max connections")
+        .comment("server.tls.cert", "Certificate");
+    let settings = toml::ser::Settings::new()
+        .dotted_keys(true)
+        .inline_table_width(30);
+    assert_eq(
+        "\
+name = \"app\"
+tags = [\"a\", \"b\"]
+
+[server]
+port = 80
+# Max connections
+limits = { conn = 10 }
+# Certificate
+tls = { cert = \"a.pem\" }
+
+[[backends]]
+host = \"a\"
+
+[[backends]]
+host = \"b\"
+",
+        serialize(settings, &comments),
+    );
+
+    let comments = toml::ser::Comments::new().comment("server.limits.conn", "Max connections");
+    let settings = toml::ser::Settings::new().dotted_keys(true);
+    assert_eq(
+        "\
+name = \"app\"
+tags = [\"a\", \"b\"]
+
+[server]
+port = 80
+# Max connections
+limits.conn = 10
+tls.cert = \"a.pem\"
+
+[[backends]]
+host = \"a\"
+
+[[backends]]
+host = \"b\"
+",
+        serialize(settings, &comments),
+    );
+}
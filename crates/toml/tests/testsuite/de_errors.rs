@@ -458,3 +458,286 @@ duplicate key `a` in table `t2`
 "
     );
 }
+
+macro_rules! bad_all {
+    ($toml:expr, $ty:ty, $msgs:expr) => {
+        match toml::de::from_str_all::<$ty>($toml) {
+            Ok(s) => panic!("parsed to: {:#?}", s),
+            Err(errors) => {
+                let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                snapbox::assert_eq($msgs, errors.join("---\n"));
+            }
+        }
+    };
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Server {
+    name: String,
+    port: u16,
+    #[serde(default)]
+    tags: Vec<String>,
+    limits: Limits,
+    mode: Option<Mode>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Limits {
+    conn: u32,
+    ratio: f64,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+enum Mode {
+    Fast,
+    Slow,
+}
+
+#[test]
+fn collect_all_errors() {
+    bad_all!(
+        r#"
+name = 1
+port = "http"
+tags = ["a", 2, """
+multi"""]
+limits = { conn = -1, ratio = "x" }
+"#,
+        Server,
+        r#"TOML parse error at line 2, column 8
+  |
+2 | name = 1
+  |        ^
+invalid type: integer `1`, expected a string
+---
+TOML parse error at line 3, column 8
+  |
+3 | port = "http"
+  |        ^^^^^^
+invalid type: string "http", expected u16
+---
+TOML parse error at line 4, column 14
+  |
+4 | tags = ["a", 2, """
+  |              ^
+invalid type: integer `2`, expected a string
+---
+TOML parse error at line 6, column 19
+  |
+6 | limits = { conn = -1, ratio = "x" }
+  |                   ^^
+invalid value: integer `-1`, expected u32
+---
+TOML parse error at line 6, column 31
+  |
+6 | limits = { conn = -1, ratio = "x" }
+  |                               ^^^
+invalid type: string "x", expected f64
+"#
+    );
+}
+
+#[test]
+fn collect_errors_stop() {
+    // missing field is not at a value
+    bad_all!(
+        "name = 1\nport = 'x'\n",
+        Server,
+        r#"TOML parse error at line 1, column 8
+  |
+1 | name = 1
+  |        ^
+invalid type: integer `1`, expected a string
+---
+TOML parse error at line 2, column 8
+  |
+2 | port = 'x'
+  |        ^^^
+invalid type: string "x", expected u16
+---
+TOML parse error at line 1, column 1
+  |
+1 | name = 1
+  | ^^^^^^^^
+missing field `limits`
+"#
+    );
+
+    // an unknown variant is not replaced
+    bad_all!(
+        "name = 1\nport = 80\nmode = 'Quick'\nlimits = { conn = 'x', ratio = 1.0 }\n",
+        Server,
+        r#"TOML parse error at line 1, column 8
+  |
+1 | name = 1
+  |        ^
+invalid type: integer `1`, expected a string
+---
+TOML parse error at line 3, column 8
+  |
+3 | mode = 'Quick'
+  |        ^^^^^^^
+unknown variant `Quick`, expected `Fast` or `Slow`
+"#
+    );
+
+    let server = toml::de::from_str_all::<Server>(
+        "name = 'a'\nport = 80\nlimits = { conn = 1, ratio = 1.0 }\n",
+    )
+    .unwrap();
+    assert_eq!(server.port, 80);
+    let errors = toml::de::from_str_all::<Server>("name = 'a'\nport = 80\n[limits]\nconn = 'x'\n")
+        .unwrap_err();
+    assert_eq!(errors[0].keys(), ["limits", "conn"]);
+    assert_eq!(errors[0].span(), Some(37..40));
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(try_from = "RawRange")]
+struct Range {
+    min: i64,
+    max: i64,
+}
+
+#[derive(Deserialize)]
+struct RawRange {
+    min: i64,
+    max: i64,
+}
+
+impl TryFrom<RawRange> for Range {
+    type Error = String;
+
+    fn try_from(raw: RawRange) -> Result<Self, Self::Error> {
+        if raw.min <= raw.max {
+            Ok(Range {
+                min: raw.min,
+                max: raw.max,
+            })
+        } else {
+            Err(format!("{} is greater than {}", raw.min, raw.max))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Job {
+    name: String,
+    range: Range,
+    limits: Option<Limits>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Jobs {
+    job: Vec<Job>,
+}
+
+#[test]
+fn collect_errors_validated() {
+    // the validation of the replaced `max` is not reported
+    bad_all!(
+        "name = 'a'\nrange = { min = 3, max = 'x' }\n",
+        Job,
+        r#"TOML parse error at line 2, column 26
+  |
+2 | range = { min = 3, max = 'x' }
+  |                          ^^^
+invalid type: string "x", expected i64
+"#
+    );
+
+    // the validation of a table without replaced values is
+    bad_all!(
+        "name = 1\nrange = { min = 3, max = 1 }\n",
+        Job,
+        r#"TOML parse error at line 1, column 8
+  |
+1 | name = 1
+  |        ^
+invalid type: integer `1`, expected a string
+---
+TOML parse error at line 2, column 9
+  |
+2 | range = { min = 3, max = 1 }
+  |         ^^^^^^^^^^^^^^^^^^^^
+3 is greater than 1
+"#
+    );
+}
+
+#[test]
+fn collect_errors_nested() {
+    let errors = toml::de::from_str_all::<Jobs>(
+        r#"
+[[job]]
+name = 1
+range = { min = 1, max = 2 }
+[job.limits]
+conn = "x"
+ratio = 0.5
+
+[[job]]
+name = "b"
+range = { min = 1, max = 2.5 }
+"#,
+    )
+    .unwrap_err();
+    let errors = errors
+        .iter()
+        .map(|e| (e.keys().join("."), e.message().to_owned()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        [
+            (
+                "job.name".to_owned(),
+                "invalid type: integer `1`, expected a string".to_owned()
+            ),
+            (
+                "job.limits.conn".to_owned(),
+                "invalid type: string \"x\", expected u32".to_owned()
+            ),
+            (
+                "job.range.max".to_owned(),
+                "invalid type: floating point `2.5`, expected i64".to_owned()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn from_slice_encoding() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Config {
+        name: String,
+    }
+
+    let config = Config {
+        name: "app".to_owned(),
+    };
+    assert_eq!(toml::from_slice::<Config>(b"name = 'app'"), Ok(config));
+    let bom =
+        toml::from_slice::<Config>(b"\xEF\xBB\xBFname = 'app'\n[t]\na = 1\na = 2").unwrap_err();
+    assert_eq!(bom.span(), Some(26..27));
+
+    let err = toml::from_slice::<Config>(b"\n\nname = 'a\xC3pp'\n").unwrap_err();
+    assert_eq!(err.span(), Some(11..12));
+    snapbox::assert_eq(
+        "\
+TOML parse error at line 3, column 10
+  |
+3 | name = 'a\u{FFFD}pp'
+  |          ^
+invalid UTF-8
+",
+        err.to_string(),
+    );
+
+    let err = toml::from_slice::<Config>(b"\xFF\xFEn\0a\0").unwrap_err();
+    assert_eq!(err.span(), Some(0..2));
+    assert_eq!(err.message(), "UTF-16 is not supported, TOML must be UTF-8");
+    let err = toml::from_slice::<Config>(b"\0n\0a").unwrap_err();
+    assert_eq!(err.span(), Some(0..1));
+    let err = toml::from_reader::<_, Config>(&b"n\0a\0"[..]).unwrap_err();
+    assert_eq!(err.span(), Some(1..2));
+}
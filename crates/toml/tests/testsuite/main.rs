@@ -1,6 +1,8 @@
 #![recursion_limit = "256"]
 #![cfg(all(feature = "parse", feature = "display"))]
 
+mod borrow;
+mod comments;
 mod de_errors;
 mod display;
 mod display_tricky;
@@ -12,4 +14,6 @@ mod pretty;
 mod serde;
 mod spanned;
 mod spanned_impls;
+mod stream;
 mod tables_last;
+mod unknown_keys;
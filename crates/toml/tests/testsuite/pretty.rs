@@ -182,3 +182,128 @@ debug = true
     let pretty = toml::to_string_pretty(&pkg).unwrap();
     assert_eq(raw, pretty);
 }
+
+fn serialize_with(toml: &str, settings: toml::ser::Settings) -> String {
+    let value: toml::Value = toml::from_str(toml).unwrap();
+    let mut result = String::with_capacity(128);
+    value
+        .serialize(toml::Serializer::with_settings(&mut result, settings))
+        .unwrap();
+    result
+}
+
+#[test]
+fn settings_array_wrap() {
+    let toml = "\
+long = [1, 2, 3, 4]
+short = [1, 2]
+words = [\"alpha\", \"beta\"]
+";
+    let settings = toml::ser::Settings::new().array_wrap_len(3).indent(2);
+    assert_eq(
+        "\
+long = [
+  1,
+  2,
+  3,
+  4,
+]
+short = [1, 2]
+words = [\"alpha\", \"beta\"]
+",
+        serialize_with(toml, settings),
+    );
+
+    let settings = toml::ser::Settings::new().array_wrap_width(16);
+    assert_eq(
+        "\
+long = [1, 2, 3, 4]
+short = [1, 2]
+words = [
+    \"alpha\",
+    \"beta\",
+]
+",
+        serialize_with(toml, settings),
+    );
+}
+
+#[test]
+fn settings_inline_table() {
+    let toml = "\
+[owner]
+name = \"Tom\"
+
+[server]
+host = \"example.com\"
+port = 8080
+
+[server.tls]
+cert = \"a.pem\"
+";
+    let settings = toml::ser::Settings::new().inline_table_width(30);
+    assert_eq(
+        "\
+owner = { name = \"Tom\" }
+
+[server]
+host = \"example.com\"
+port = 8080
+tls = { cert = \"a.pem\" }
+",
+        serialize_with(toml, settings),
+    );
+}
+
+#[test]
+fn settings_literal_strings() {
+    let toml = r#"
+path = 'C:\dir'
+quote = "it's"
+text = "one\ntwo"
+"#;
+    let settings = toml::ser::Settings::new().literal_strings(true);
+    assert_eq(
+        r#"path = 'C:\dir'
+quote = "it's"
+text = '''
+one
+two'''
+"#,
+        serialize_with(toml, settings),
+    );
+
+    let settings = toml::ser::Settings::new().literal_strings(false);
+    assert_eq(
+        r#"path = "C:\\dir"
+quote = "it's"
+text = """
+one
+two"""
+"#,
+        serialize_with(toml, settings),
+    );
+}
+
+#[test]
+fn settings_dotted_keys() {
+    let toml = "\
+[a.b]
+c = 1
+
+[d]
+e = 1
+f = 2
+";
+    let settings = toml::ser::Settings::new().dotted_keys(true);
+    assert_eq(
+        "\
+a.b.c = 1
+
+[d]
+e = 1
+f = 2
+",
+        serialize_with(toml, settings),
+    );
+}
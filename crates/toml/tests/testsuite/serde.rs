@@ -1072,3 +1072,19 @@ fn datetime_offset_issue_496() {
     let output = toml.to_string();
     snapbox::assert_eq(original, output);
 }
+
+#[test]
+fn datetime_from_value() {
+    use toml::value::Datetime;
+
+    #[derive(Deserialize)]
+    struct Struct {
+        date: Datetime,
+    }
+
+    let value = "date = 2022-01-01T10:11:12Z".parse::<Value>().unwrap();
+    let date: Datetime = value["date"].clone().try_into().unwrap();
+    assert_eq!(date.to_string(), "2022-01-01T10:11:12Z");
+    let s: Struct = value.try_into().unwrap();
+    assert_eq!(s.date.to_string(), "2022-01-01T10:11:12Z");
+}
@@ -0,0 +1,388 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use snapbox::assert_eq;
+
+#[derive(Serialize)]
+struct Lock {
+    version: u32,
+    updated: toml::value::Datetime,
+    empty: Vec<u32>,
+    meta: BTreeMap<String, String>,
+    package: Vec<Package>,
+}
+
+#[derive(Serialize)]
+struct Package {
+    name: String,
+    checksum: Option<String>,
+    dependencies: Vec<String>,
+    targets: Vec<Target>,
+    source: Source,
+}
+
+#[derive(Serialize)]
+struct Source {
+    kind: Kind,
+    paths: Vec<Vec<u32>>,
+}
+
+#[derive(Serialize)]
+enum Kind {
+    Git,
+}
+
+#[derive(Serialize)]
+struct Target {
+    name: String,
+}
+
+fn lock() -> Lock {
+    Lock {
+        version: 3,
+        updated: "1979-05-27T07:32:00Z".parse().unwrap(),
+        empty: vec![],
+        meta: BTreeMap::new(),
+        package: vec![
+            Package {
+                name: "a".to_owned(),
+                checksum: Some("abc".to_owned()),
+                dependencies: vec!["b".to_owned(), "c".to_owned()],
+                targets: vec![
+                    Target {
+                        name: "x".to_owned(),
+                    },
+                    Target {
+                        name: "y".to_owned(),
+                    },
+                ],
+                source: Source {
+                    kind: Kind::Git,
+                    paths: vec![vec![1], vec![]],
+                },
+            },
+            Package {
+                name: "b".to_owned(),
+                checksum: None,
+                dependencies: vec![],
+                targets: vec![],
+                source: Source {
+                    kind: Kind::Git,
+                    paths: vec![],
+                },
+            },
+        ],
+    }
+}
+
+#[test]
+fn same_as_to_string() {
+    let expected = toml::to_string(&lock()).unwrap();
+    let mut output = Vec::new();
+    toml::to_writer(&mut output, &lock()).unwrap();
+    assert_eq(expected, String::from_utf8(output).unwrap());
+
+    let value: toml::Value = toml::from_str(
+        r#"
+[a.b]
+c = { d = [1, { e = 2 }] }
+f = 1
+[[g]]
+[[g]]
+h.i = 3
+"#,
+    )
+    .unwrap();
+    let expected = toml::to_string(&value).unwrap();
+    let mut output = Vec::new();
+    toml::to_writer(&mut output, &value).unwrap();
+    assert_eq(expected, String::from_utf8(output).unwrap());
+
+    let expected = toml::to_string_pretty(&lock()).unwrap();
+    let mut output = Vec::new();
+    toml::to_writer_pretty(&mut output, &lock()).unwrap();
+    assert_eq(expected, String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn stream_to_fmt_write() {
+    let mut output = String::new();
+    let settings = toml::ser::Settings::new().array_wrap_len(1).indent(2);
+    let mut serializer = toml::ser::StreamSerializer::with_settings(&mut output, settings);
+    lock().serialize(&mut serializer).unwrap();
+    assert_eq(
+        r#"version = 3
+updated = 1979-05-27T07:32:00Z
+empty = []
+
+[meta]
+
+[[package]]
+name = "a"
+checksum = "abc"
+dependencies = [
+  "b",
+  "c",
+]
+
+[[package.targets]]
+name = "x"
+
+[[package.targets]]
+name = "y"
+
+[package.source]
+kind = "Git"
+paths = [
+  [1],
+  [],
+]
+
+[[package]]
+name = "b"
+dependencies = []
+targets = []
+
+[package.source]
+kind = "Git"
+paths = []
+"#,
+        output,
+    );
+}
+
+fn assert_same_as_to_string<T: Serialize>(value: &T) {
+    let expected = toml::to_string(value).unwrap();
+    let mut output = Vec::new();
+    toml::to_writer(&mut output, value).unwrap();
+    assert_eq(expected, String::from_utf8(output).unwrap());
+
+    let expected = toml::to_string_pretty(value).unwrap();
+    let mut output = Vec::new();
+    toml::to_writer_pretty(&mut output, value).unwrap();
+    assert_eq(expected, String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn value_after_table() {
+    #[derive(Serialize)]
+    struct Config {
+        server: BTreeMap<String, u32>,
+        name: String,
+    }
+
+    let config = Config {
+        server: BTreeMap::new(),
+        name: "app".to_owned(),
+    };
+    assert_same_as_to_string(&config);
+
+    let mut output = Vec::new();
+    toml::to_writer(&mut output, &config).unwrap();
+    assert_eq(
+        r#"name = "app"
+
+[server]
+"#,
+        String::from_utf8(output).unwrap(),
+    );
+}
+
+#[test]
+fn value_after_nested_tables() {
+    #[derive(Serialize)]
+    struct Pkg {
+        source: Source,
+        targets: Vec<Target>,
+        name: String,
+        meta: BTreeMap<String, Source>,
+        ids: Vec<u32>,
+    }
+
+    let mut meta = BTreeMap::new();
+    meta.insert(
+        "m".to_owned(),
+        Source {
+            kind: Kind::Git,
+            paths: vec![vec![2]],
+        },
+    );
+    let mut pkg = Pkg {
+        source: Source {
+            kind: Kind::Git,
+            paths: vec![],
+        },
+        targets: vec![],
+        name: "a".to_owned(),
+        meta,
+        ids: vec![1, 2],
+    };
+    assert_same_as_to_string(&pkg);
+
+    pkg.targets = vec![
+        Target {
+            name: "x".to_owned(),
+        },
+        Target {
+            name: "y".to_owned(),
+        },
+    ];
+    assert_same_as_to_string(&pkg);
+    assert_same_as_to_string(&vec![("pkg", &pkg)].into_iter().collect::<BTreeMap<_, _>>());
+}
+
+#[test]
+fn tables_streamed_before_later_tables() {
+    use serde::ser::SerializeStruct as _;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl std::io::Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Records how much was written when it is serialized
+    struct Pkg {
+        name: &'static str,
+        output: Shared,
+        written: Cell<usize>,
+    }
+
+    impl Serialize for Pkg {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.written.set(self.output.0.borrow().len());
+            let mut table = serializer.serialize_struct("Pkg", 1)?;
+            table.serialize_field("name", self.name)?;
+            table.end()
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Lock<'a> {
+        version: u32,
+        package: &'a [Pkg],
+        metadata: BTreeMap<String, String>,
+    }
+
+    let output = Shared::default();
+    let package: Vec<Pkg> = ["a", "b", "c"]
+        .iter()
+        .map(|name| Pkg {
+            name,
+            output: output.clone(),
+            written: Cell::new(0),
+        })
+        .collect();
+    let mut metadata = BTreeMap::new();
+    metadata.insert("k".to_owned(), "v".to_owned());
+    let lock = Lock {
+        version: 3,
+        package: &package,
+        metadata,
+    };
+    toml::to_writer(output.clone(), &lock).unwrap();
+
+    let written = String::from_utf8(output.0.borrow().clone()).unwrap();
+    // The earlier packages are already written when the last one is serialized
+    assert_eq!(
+        package[2].written.get(),
+        written.rfind("\n[[package]]").unwrap()
+    );
+    assert_eq(toml::to_string(&lock).unwrap(), written);
+}
+
+#[test]
+fn table_then_value_in_array() {
+    let value: toml::Value = toml::from_str(
+        r#"
+a = [{ b = 1 }, 2]
+c = 3
+"#,
+    )
+    .unwrap();
+    assert_same_as_to_string(&value);
+}
+
+#[test]
+fn wrapped_arrays() {
+    let values = vec![vec![1, 2, 3], vec![]];
+    for settings in [
+        toml::ser::Settings::new().array_wrap_len(2),
+        toml::ser::Settings::new().array_wrap_width(8).indent(1),
+    ] {
+        let mut expected = String::new();
+        let serializer = toml::ser::Serializer::with_settings(&mut expected, settings);
+        lock().serialize(serializer).unwrap();
+        let mut output = String::new();
+        let mut serializer = toml::ser::StreamSerializer::with_settings(&mut output, settings);
+        lock().serialize(&mut serializer).unwrap();
+        assert_eq(expected, output);
+
+        let mut expected = String::new();
+        let serializer = toml::ser::Serializer::with_settings(&mut expected, settings);
+        let value = vec![("values", &values)]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        value.serialize(serializer).unwrap();
+        let mut output = String::new();
+        let mut serializer = toml::ser::StreamSerializer::with_settings(&mut output, settings);
+        value.serialize(&mut serializer).unwrap();
+        assert_eq(expected, output);
+    }
+}
+
+#[test]
+fn no_output_on_error() {
+    #[derive(Serialize)]
+    struct Config {
+        server: BTreeMap<String, u32>,
+        name: String,
+        ports: Vec<u16>,
+        unit: (),
+    }
+
+    let config = Config {
+        server: BTreeMap::new(),
+        name: "app".to_owned(),
+        ports: vec![80, 443],
+        unit: (),
+    };
+    let mut output = Vec::new();
+    let err = toml::to_writer(&mut output, &config).unwrap_err();
+    assert_eq(
+        toml::to_string(&config).unwrap_err().to_string(),
+        err.to_string(),
+    );
+    assert!(output.is_empty());
+}
+
+#[test]
+fn io_error() {
+    struct Full;
+
+    impl std::io::Write for Full {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(std::io::ErrorKind::Other, "disk full"))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let err = toml::to_writer(Full, &lock()).unwrap_err();
+    assert_eq("disk full", err.to_string());
+
+    let err = toml::to_writer(Vec::new(), &1).unwrap_err();
+    assert_eq("unsupported rust type", err.to_string());
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use toml::Spanned;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Config {
+    name: String,
+    server: Server,
+    #[serde(default)]
+    backends: Vec<Backend>,
+    mode: Option<Mode>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Server {
+    port: Spanned<u16>,
+    #[serde(default)]
+    labels: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Backend {
+    host: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+enum Mode {
+    Tcp(Tcp),
+    Udp,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Tcp {
+    timeout: u32,
+}
+
+fn unknown_keys(input: &str) -> Vec<(String, String)> {
+    let mut unknown = Vec::new();
+    Config::deserialize(toml::Deserializer::new(input).unknown_keys(&mut unknown)).unwrap();
+    unknown
+        .iter()
+        .map(|key| (key.path(), input[key.span().unwrap()].to_owned()))
+        .collect()
+}
+
+#[test]
+fn no_unknown_keys() {
+    let input = r#"
+name = "app"
+[server]
+port = 80
+labels = { env = "prod" }
+[[backends]]
+host = "a"
+"#;
+    assert_eq!(unknown_keys(input), vec![]);
+}
+
+#[test]
+fn nested_unknown_keys() {
+    let input = r#"
+name = "app"
+version = 2
+[server]
+port = 80
+prot = 80
+extra = { a = 1, b = [1, 2] }
+[[backends]]
+host = "a"
+[[backends]]
+host = "b"
+weight = 3
+[owner]
+name = "x"
+"#;
+    assert_eq!(
+        unknown_keys(input),
+        vec![
+            ("version".to_owned(), "version".to_owned()),
+            ("server.prot".to_owned(), "prot".to_owned()),
+            ("server.extra".to_owned(), "extra".to_owned()),
+            ("backends.1.weight".to_owned(), "weight".to_owned()),
+            ("owner".to_owned(), "owner".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn unknown_keys_in_enum_and_quoted() {
+    let input = r#"
+name = "app"
+"a.b" = 1
+[server]
+port = 80
+[mode.Tcp]
+timeout = 10
+retry = 3
+"#;
+    assert_eq!(
+        unknown_keys(input),
+        vec![
+            (r#""a.b""#.to_owned(), r#""a.b""#.to_owned()),
+            ("mode.Tcp.retry".to_owned(), "retry".to_owned()),
+        ]
+    );
+
+    let mut unknown = Vec::new();
+    let config = Config::deserialize(
+        toml::Deserializer::new("name = 'app'\nmode = 'Udp'\n[server]\nport = 80\n")
+            .unknown_keys(&mut unknown),
+    )
+    .unwrap();
+    assert_eq!(config.mode, Some(Mode::Udp));
+    assert_eq!(config.server.port.span(), 42..44);
+    assert!(unknown.is_empty());
+}
+
+#[test]
+fn unknown_keys_with_error() {
+    let mut unknown = Vec::new();
+    let err = Config::deserialize(
+        toml::Deserializer::new("name = 'app'\nvalue = 1\n[server]\nport = 'x'\n")
+            .unknown_keys(&mut unknown),
+    )
+    .unwrap_err();
+    assert_eq!(err.span(), Some(39..42));
+    assert_eq!(unknown[0].keys(), ["value"]);
+}
+
+#[test]
+fn unknown_keys_with_spanned_and_borrowed_keys() {
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Deserialize)]
+    struct Spans {
+        ports: BTreeMap<Spanned<String>, Spanned<u16>>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Borrowed<'a> {
+        #[serde(borrow)]
+        labels: BTreeMap<&'a str, &'a str>,
+        #[serde(borrow)]
+        server: BTreeMap<&'a str, Spanned<&'a str>>,
+    }
+
+    let input = "extra = 1\n[ports]\nhttp = 80\n'ssh' = 22\n";
+    let mut unknown = Vec::new();
+    let spans =
+        Spans::deserialize(toml::Deserializer::new(input).unknown_keys(&mut unknown)).unwrap();
+    let ports = spans
+        .ports
+        .iter()
+        .map(|(key, port)| (&input[key.span()], key.get_ref().as_str(), *port.get_ref()))
+        .collect::<Vec<_>>();
+    assert_eq!(ports, [("http", "http", 80), ("'ssh'", "ssh", 22)]);
+    assert_eq!(unknown.len(), 1);
+    assert_eq!(unknown[0].path(), "extra");
+    assert_eq!(unknown[0].span(), Some(0..5));
+
+    let input = "labels = { env = 'prod' }\n[server]\nhost = 'a'\n";
+    let mut unknown = Vec::new();
+    let borrowed =
+        Borrowed::deserialize(toml::Deserializer::new(input).unknown_keys(&mut unknown)).unwrap();
+    assert_eq!(borrowed.labels["env"], "prod");
+    assert_eq!(borrowed.server["host"].get_ref(), &"a");
+    assert_eq!(&input[borrowed.server["host"].span()], "'a'");
+    assert!(unknown.is_empty());
+}
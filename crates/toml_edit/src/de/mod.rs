@@ -52,6 +52,11 @@ impl Error {
         self.inner.span()
     }
 
+    /// The keys of the tables down to where the error occurred
+    pub fn keys(&self) -> &[String] {
+        self.inner.keys()
+    }
+
     pub(crate) fn set_span(&mut self, span: Option<std::ops::Range<usize>>) {
         self.inner.set_span(span);
     }
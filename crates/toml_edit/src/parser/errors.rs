@@ -65,6 +65,11 @@ impl TomlError {
         self.span.clone()
     }
 
+    #[cfg(feature = "serde")]
+    pub(crate) fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
     #[cfg(feature = "serde")]
     pub(crate) fn set_span(&mut self, span: Option<std::ops::Range<usize>>) {
         self.span = span;
@@ -14,11 +14,11 @@ fn main()
     println!("read by path:");
 
     let root = v.path();
-    let ip = root / "ip" | "";
+    let ip = root.clone() / "ip" | "";
     println!("/ip = {ip}");
 
-    let host = root / "host";
-    let ip = host / "ip" | "";
+    let host = root.clone() / "host";
+    let ip = host.clone() / "ip" | "";
     println!("/host/ip = {ip}");
     let port = host / "port" | 0;
     println!("/host/port = {port}");
@@ -33,8 +33,8 @@ fn main()
     let desc = v.pathto("service.1.desc") | "";
     println!("/service/1/desc = {desc}");
 
-    let int = root / "misc" / "int" | 0;
-    let float = root / "misc" / "float" | 0.0;
+    let int = root.clone() / "misc" / "int" | 0;
+    let float = root.clone() / "misc" / "float" | 0.0;
     let tf = root / "misc" / "bool" | false;
     println!("/misc/int = {int}");
     println!("/misc/float = {float}");
@@ -0,0 +1,316 @@
+//! Command line tool to query and edit toml file for shell scripts.
+//! The file is edited in place, keeping the comments and format.
+//!
+//! Exit code: 0 success, 1 usage or file error, 2 path not found,
+//! 3 type mismatch.
+
+use std::fmt;
+use toml_edit::{Datetime, Document, Item, Value};
+use toml_ops::{EditOperator, PathItem, TomlPath};
+
+const USAGE: &str = "\
+Usage: tomlq <command> <file> [path] [value] [--type <type>]
+
+Commands:
+    get  <file> <path>            print the value of node, string is unquoted
+    set  <file> <path> <value>    set value of node, or add key to table
+    del  <file> <path>            remove node from table or array
+    push <file> <path> <value>    push value to the end of array
+    keys <file> [path]            print keys of table or indexes of array
+
+Options:
+    --type <type>   value type: str, int, float, bool, datetime or toml,
+                    default the type of existing node, toml literal for new one
+
+Path is dot separated as `host.port` or `service.0.name`, or json pointer
+as `/host/port`.
+
+Exit code: 0 success, 1 usage or file error, 2 path not found, 3 type mismatch.";
+
+/// Failure of command, map to exit code.
+enum Failure {
+    Usage(String),
+    File(String),
+    Missing(String),
+    Mismatch(String),
+}
+
+impl Failure {
+    fn code(&self) -> i32 {
+        match self {
+            Failure::Usage(_) | Failure::File(_) => 1,
+            Failure::Missing(_) => 2,
+            Failure::Mismatch(_) => 3,
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Usage(msg) | Failure::File(msg) | Failure::Missing(msg) | Failure::Mismatch(msg) => {
+                write!(f, "{msg}")
+            }
+        }
+    }
+}
+
+/// Value type given by `--type` option.
+#[derive(Copy, Clone, PartialEq)]
+enum Kind {
+    Str,
+    Int,
+    Float,
+    Bool,
+    Datetime,
+    Toml,
+}
+
+impl Kind {
+    fn parse(name: &str) -> Result<Self, Failure> {
+        match name {
+            "str" | "string" => Ok(Kind::Str),
+            "int" | "integer" => Ok(Kind::Int),
+            "float" => Ok(Kind::Float),
+            "bool" | "boolean" => Ok(Kind::Bool),
+            "datetime" => Ok(Kind::Datetime),
+            "toml" => Ok(Kind::Toml),
+            _ => Err(Failure::Usage(format!("unknown type `{name}`"))),
+        }
+    }
+
+    /// The type of existing node, complex value is in toml literal.
+    fn of(v: &Item) -> Option<Self> {
+        match v.as_value()? {
+            Value::String(_) => Some(Kind::Str),
+            Value::Integer(_) => Some(Kind::Int),
+            Value::Float(_) => Some(Kind::Float),
+            Value::Boolean(_) => Some(Kind::Bool),
+            Value::Datetime(_) => Some(Kind::Datetime),
+            Value::Array(_) | Value::InlineTable(_) => Some(Kind::Toml),
+        }
+    }
+
+    /// Convert text to value of this type.
+    fn value(self, text: &str) -> Option<Value> {
+        match self {
+            Kind::Str => Some(Value::from(text)),
+            Kind::Int => text.trim().parse::<i64>().ok().map(Value::from),
+            Kind::Float => text.trim().parse::<f64>().ok().map(Value::from),
+            Kind::Bool => text.trim().parse::<bool>().ok().map(Value::from),
+            Kind::Datetime => text.trim().parse::<Datetime>().ok().map(Value::from),
+            Kind::Toml => {
+                let mut val = text.parse::<Value>().ok()?;
+                val.decor_mut().clear();
+                Some(val)
+            }
+        }
+    }
+}
+
+/// Convert text to value by the type option, or by the type of existing node.
+/// Without both, try toml literal first and fallback to string.
+fn make_value(text: &str, kind: Option<Kind>, old: Option<&Item>) -> Result<Value, Failure> {
+    let mismatch = |kind: &str| Failure::Mismatch(format!("`{text}` is not valid {kind}"));
+    if let Some(kind) = kind {
+        return kind.value(text).ok_or_else(|| mismatch("value of given type"));
+    }
+    match old {
+        Some(old) => {
+            let kind = Kind::of(old).ok_or_else(|| mismatch(old.type_name()))?;
+            let val = kind.value(text).filter(|val| val.type_name() == old.type_name());
+            val.ok_or_else(|| mismatch(old.type_name()))
+        }
+        None => Ok(Kind::Toml.value(text).unwrap_or_else(|| Value::from(text))),
+    }
+}
+
+/// Print the node, string without quote, table in toml text.
+fn print_item(v: &Item) {
+    match v {
+        Item::Value(Value::String(s)) => println!("{}", s.value()),
+        Item::Value(val) => println!("{}", val.clone().decorated("", "")),
+        Item::Table(table) => print!("{}", Document::from(table.clone())),
+        Item::ArrayOfTables(_) => {
+            if let Ok(val) = v.clone().into_value() {
+                println!("{}", val.decorated("", ""));
+            }
+        }
+        Item::None => {}
+    }
+}
+
+/// Command line arguments, with options removed.
+struct Args {
+    command: String,
+    file: String,
+    rest: Vec<String>,
+    kind: Option<Kind>,
+}
+
+impl Args {
+    fn parse(args: Vec<String>) -> Result<Self, Failure> {
+        let mut kind = None;
+        let mut positional = Vec::new();
+        let mut iter = args.into_iter();
+        while let Some(arg) = iter.next() {
+            if arg == "--type" {
+                let name = iter.next().ok_or_else(|| Failure::Usage("missing value of --type".to_string()))?;
+                kind = Some(Kind::parse(&name)?);
+            }
+            else if let Some(name) = arg.strip_prefix("--type=") {
+                kind = Some(Kind::parse(name)?);
+            }
+            else {
+                positional.push(arg);
+            }
+        }
+        if positional.len() < 2 {
+            return Err(Failure::Usage(USAGE.to_string()));
+        }
+        let rest = positional.split_off(2);
+        let file = positional.pop().unwrap();
+        let command = positional.pop().unwrap();
+        Ok(Self { command, file, rest, kind })
+    }
+
+    /// Get the required positional argument after file.
+    fn arg(&self, index: usize, name: &str) -> Result<&str, Failure> {
+        match self.rest.get(index) {
+            Some(arg) => Ok(arg),
+            None => Err(Failure::Usage(format!("missing argument <{name}> for {}", self.command))),
+        }
+    }
+
+    /// Parse the path argument, empty for root.
+    fn path(&self, index: usize) -> Result<TomlPath, Failure> {
+        let path = self.rest.get(index).map(String::as_str).unwrap_or_default();
+        let parsed = if path.is_empty() || path.starts_with('/') {
+            TomlPath::from_pointer(path)
+        }
+        else {
+            TomlPath::parse(path)
+        };
+        parsed.map_err(|e| Failure::Usage(format!("invalid path `{path}`: {e}")))
+    }
+}
+
+fn load(file: &str) -> Result<Document, Failure> {
+    let text = std::fs::read_to_string(file).map_err(|e| Failure::File(format!("{file}: {e}")))?;
+    text.parse::<Document>().map_err(|e| Failure::File(format!("{file}: {e}")))
+}
+
+/// Write to a temporary file in the same directory then rename it over the
+/// original, so an interrupted script never leave a truncated file.
+fn save(file: &str, doc: &Document) -> Result<(), Failure> {
+    let fail = |e: std::io::Error| Failure::File(format!("{file}: {e}"));
+    let path = std::path::Path::new(file);
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let temp = path.with_file_name(format!(".{name}.tomlq-{}", std::process::id()));
+    let write = || -> std::io::Result<()> {
+        std::fs::write(&temp, doc.to_string())?;
+        std::fs::set_permissions(&temp, std::fs::metadata(path)?.permissions())?;
+        std::fs::rename(&temp, path)
+    };
+    write().map_err(|e| {
+        let _ = std::fs::remove_file(&temp);
+        fail(e)
+    })
+}
+
+fn missing(path: &TomlPath) -> Failure {
+    Failure::Missing(format!("path `{path}` not found"))
+}
+
+fn run(args: Args) -> Result<(), Failure> {
+    let mut doc = load(&args.file)?;
+    match args.command.as_str() {
+        "get" => {
+            args.arg(0, "path")?;
+            let path = args.path(0)?;
+            let node = *(doc.path() / &path);
+            print_item(node.ok_or_else(|| missing(&path))?);
+        }
+        "keys" => {
+            let path = args.path(0)?;
+            let node = (doc.path() / &path).ok_or_else(|| missing(&path))?;
+            if let Some(table) = node.as_table_like() {
+                for (key, _) in table.iter() {
+                    println!("{key}");
+                }
+            }
+            else if let Some(len) = node.as_array().map(|a| a.len()).or(node.as_array_of_tables().map(|a| a.len())) {
+                for index in 0..len {
+                    println!("{index}");
+                }
+            }
+            else {
+                return Err(Failure::Mismatch(format!("`{path}` is {}, not table or array", node.type_name())));
+            }
+        }
+        "set" => {
+            let path = args.path(0)?;
+            let text = args.arg(1, "value")?;
+            let mut ptr = doc.path_mut() / &path;
+            if let Some(old) = ptr.as_deref() {
+                let val = make_value(text, args.kind, Some(old))?;
+                ptr <<= val;
+            }
+            else {
+                let (parent, key) = match (path.parent(), path.last()) {
+                    (Some(parent), Some(PathItem::Key(key))) => (parent, key.clone()),
+                    _ => return Err(missing(&path)),
+                };
+                let val = make_value(text, args.kind, None)?;
+                let table = doc.path_mut() / &parent;
+                match table.as_deref() {
+                    Some(v) if v.is_table_like() => { let _ = table << (key, val); }
+                    Some(v) => return Err(Failure::Mismatch(format!("`{parent}` is {}, not table", v.type_name()))),
+                    None => return Err(missing(&parent)),
+                }
+            }
+            save(&args.file, &doc)?;
+        }
+        "del" => {
+            let path = args.path(0)?;
+            let parent = path.parent().ok_or_else(|| Failure::Usage("cannot delete root".to_string()))?;
+            let ptr = doc.path_mut() / &parent;
+            let removed = match path.last() {
+                Some(PathItem::Key(key)) => ptr - key.as_str(),
+                Some(PathItem::Index(index)) => ptr - *index,
+                None => None,
+            };
+            removed.ok_or_else(|| missing(&path))?;
+            save(&args.file, &doc)?;
+        }
+        "push" => {
+            let path = args.path(0)?;
+            let text = args.arg(1, "value")?;
+            let ptr = doc.path_mut() / &path;
+            let node = ptr.as_deref().ok_or_else(|| missing(&path))?;
+            if !node.is_array() && !node.is_array_of_tables() {
+                return Err(Failure::Mismatch(format!("`{path}` is {}, not array", node.type_name())));
+            }
+            let first = node.get(0);
+            let val = make_value(text, args.kind, first)?;
+            if !(ptr << (val,)) {
+                return Err(Failure::Mismatch(format!("`{text}` cannot push to array of tables")));
+            }
+            save(&args.file, &doc)?;
+        }
+        _ => return Err(Failure::Usage(format!("unknown command `{}`\n\n{USAGE}", args.command))),
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{USAGE}");
+        return;
+    }
+    if let Err(failure) = Args::parse(args).and_then(run) {
+        eprintln!("tomlq: {failure}");
+        std::process::exit(failure.code());
+    }
+}
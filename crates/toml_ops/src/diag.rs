@@ -0,0 +1,490 @@
+use toml::Value;
+use toml::value::{Index, Table, Datetime};
+use std::fmt;
+use std::ops::{Div, BitOr, Shl, ShlAssign, Not, Deref, DerefMut};
+
+use crate::path::{TomlPath, PathItem, PathError};
+use crate::operator::{PathBuilder, TomlPtr, TomlInteger};
+
+/// The reason why diagnostic pointer become invalid.
+/// The `path` field is where the failed node is, from the initial node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PtrError {
+    /// Syntax error in path string.
+    Syntax(PathError),
+    /// Key is not found in table.
+    MissingKey { path: TomlPath, key: String },
+    /// Index is out of range of array.
+    OutOfRange { path: TomlPath, index: usize, len: usize },
+    /// Node cannot be indexed by the step, as scalar, or string key on array.
+    NotIndexable { path: TomlPath, step: PathItem, found: &'static str },
+    /// Put value by `<<` to node of different type.
+    TypeMismatch { path: TomlPath, expected: &'static str, found: &'static str },
+}
+
+/// Display the path in error message, empty path is the initial node.
+pub(crate) struct At<'a>(pub(crate) &'a TomlPath);
+
+impl fmt::Display for At<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            f.write_str("root")
+        }
+        else {
+            write!(f, "`{}`", self.0)
+        }
+    }
+}
+
+impl fmt::Display for PtrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PtrError::Syntax(err) => err.fmt(f),
+            PtrError::MissingKey { path, key } => {
+                write!(f, "missing key `{key}` in table at {}", At(path))
+            }
+            PtrError::OutOfRange { path, index, len } => {
+                write!(f, "index {index} out of range for array of length {len} at {}", At(path))
+            }
+            PtrError::NotIndexable { path, step, found } => {
+                let step = match step {
+                    PathItem::Key(key) => format!("key `{key}`"),
+                    PathItem::Index(index) => format!("index {index}"),
+                };
+                write!(f, "cannot get {step} from {found} at {}", At(path))
+            }
+            PtrError::TypeMismatch { path, expected, found } => {
+                write!(f, "cannot put {expected} to {found} at {}", At(path))
+            }
+        }
+    }
+}
+
+impl std::error::Error for PtrError {}
+
+/// Walk path step by step, append each successful step to `walked`,
+/// and stop at the first failed step.
+fn walk<'tr>(v: &'tr Value, path: &TomlPath, walked: &mut TomlPath) -> Result<&'tr Value, PtrError> {
+    let mut target = v;
+    for p in path.items() {
+        target = match (p, target) {
+            (PathItem::Key(key), Value::Table(table)) => match table.get(key) {
+                Some(child) => child,
+                None => return Err(PtrError::MissingKey { path: walked.clone(), key: key.clone() }),
+            },
+            (_, Value::Array(array)) if p.as_index().is_some() => {
+                let index = p.as_index().unwrap();
+                match array.get(index) {
+                    Some(child) => child,
+                    None => return Err(PtrError::OutOfRange { path: walked.clone(), index, len: array.len() }),
+                }
+            }
+            _ => {
+                let found = target.type_str();
+                return Err(PtrError::NotIndexable { path: walked.clone(), step: p.clone(), found });
+            }
+        };
+        walked.push(p.clone());
+    }
+    Ok(target)
+}
+
+/// Resolve the path in operator `/`, literal key is tried first as normal pointer.
+fn resolve<B>(v: &Value, p: B) -> Result<TomlPath, PtrError> where B: PathBuilder + Index + Copy {
+    if v.get(p).is_some() {
+        return Ok(TomlPath::from(vec![p.path_item()]));
+    }
+    p.parse_path().map_err(PtrError::Syntax)
+}
+
+/// Diagnostic version of `TomlPtr`, also record the path walked so far,
+/// and the first failure reason when it become invalid.
+/// eg: `(v.path_diag() / "a" / "b").error()`.
+#[derive(Clone)]
+pub struct DiagPtr<'tr> {
+    valop: Option<&'tr Value>,
+    walked: TomlPath,
+    error: Option<PtrError>,
+}
+
+impl<'tr> DiagPtr<'tr> {
+    /// As constructor, to build diagnostic pointer from a `toml::Value` node.
+    pub fn from(v: &'tr Value) -> Self {
+        Self { valop: Some(v), walked: TomlPath::new(), error: None }
+    }
+
+    /// The path walked so far from initial node, excluding the failed step.
+    pub fn location(&self) -> &TomlPath {
+        &self.walked
+    }
+
+    /// The first failure reason, `None` if the pointer is still valid.
+    pub fn error(&self) -> Option<&PtrError> {
+        self.error.as_ref()
+    }
+
+    /// Convert to result, that is `Err` if the pointer is invalid.
+    pub fn result(self) -> Result<&'tr Value, PtrError> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(self.valop.unwrap()),
+        }
+    }
+
+    /// Resolve path to sub node.
+    fn path<B>(mut self, p: B) -> Self where B: PathBuilder + Index + Copy {
+        if let Some(v) = self.valop {
+            match resolve(v, p) {
+                Ok(path) => return self.path_to(&path),
+                Err(err) => self.fail(err),
+            }
+        }
+        self
+    }
+
+    /// Resolve parsed path to sub node.
+    fn path_to(mut self, path: &TomlPath) -> Self {
+        if let Some(v) = self.valop {
+            match walk(v, path, &mut self.walked) {
+                Ok(target) => self.valop = Some(target),
+                Err(err) => self.fail(err),
+            }
+        }
+        self
+    }
+
+    fn fail(&mut self, err: PtrError) {
+        self.valop = None;
+        self.error = Some(err);
+    }
+}
+
+/// Overload `!` operator to test the pointer is invalid.
+impl<'tr> Not for DiagPtr<'tr> {
+    type Output = bool;
+    fn not(self) -> Self::Output {
+        self.is_none()
+    }
+}
+
+/// Overload `*` deref operator to treate pointer as `Option<&toml::Value>`.
+impl<'tr> Deref for DiagPtr<'tr> {
+    type Target = Option<&'tr Value>;
+    fn deref(&self) -> &Self::Target {
+        &self.valop
+    }
+}
+
+/// Path operator `/`, the same as `TomlPtr` but record the failure.
+impl<'tr, Rhs> Div<Rhs> for DiagPtr<'tr>
+where Rhs: PathBuilder + Index + Copy
+{
+    type Output = Self;
+    fn div(self, rhs: Rhs) -> Self::Output {
+        self.path(rhs)
+    }
+}
+
+/// Path operator `/` with parsed path.
+impl<'tr> Div<&TomlPath> for DiagPtr<'tr> {
+    type Output = Self;
+    fn div(self, rhs: &TomlPath) -> Self::Output {
+        self.path_to(rhs)
+    }
+}
+
+/// Pipe operator `|` to get value or `rhs` as default, the same as `TomlPtr`.
+impl<'tr, T> BitOr<T> for DiagPtr<'tr> where TomlPtr<'tr>: BitOr<T> {
+    type Output = <TomlPtr<'tr> as BitOr<T>>::Output;
+    fn bitor(self, rhs: T) -> Self::Output {
+        TomlPtr::new(self.valop) | rhs
+    }
+}
+
+/// Diagnostic version of `TomlPtrMut`, also record type mismatch of `<<`.
+pub struct DiagPtrMut<'tr> {
+    valop: Option<&'tr mut Value>,
+    walked: TomlPath,
+    error: Option<PtrError>,
+}
+
+impl<'tr> DiagPtrMut<'tr> {
+    /// As constructor, to build diagnostic pointer from a `toml::Value` node.
+    pub fn from(v: &'tr mut Value) -> Self {
+        Self { valop: Some(v), walked: TomlPath::new(), error: None }
+    }
+
+    /// The path walked so far from initial node, excluding the failed step.
+    pub fn location(&self) -> &TomlPath {
+        &self.walked
+    }
+
+    /// The first failure reason, `None` if the pointer is still valid.
+    pub fn error(&self) -> Option<&PtrError> {
+        self.error.as_ref()
+    }
+
+    /// Convert to result, that is `Err` if the pointer is invalid.
+    pub fn result(self) -> Result<&'tr mut Value, PtrError> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(self.valop.unwrap()),
+        }
+    }
+
+    /// Resolve path to sub node.
+    fn path<B>(mut self, p: B) -> Self where B: PathBuilder + Index + Copy {
+        let resolved = match self.valop {
+            Some(ref v) => resolve(v, p),
+            None => return self,
+        };
+        match resolved {
+            Ok(path) => self.path_to(&path),
+            Err(err) => { self.fail(err); self }
+        }
+    }
+
+    /// Resolve parsed path to sub node.
+    /// Note: walk immutable first to find the failed step, then get_mut.
+    fn path_to(mut self, path: &TomlPath) -> Self {
+        let walked = match self.valop {
+            Some(ref v) => walk(v, path, &mut self.walked).map(|_| ()),
+            None => return self,
+        };
+        match walked {
+            Ok(_) => self.valop = self.valop.take().and_then(|v| path.apply_mut(v)),
+            Err(err) => self.fail(err),
+        }
+        self
+    }
+
+    fn fail(&mut self, err: PtrError) {
+        self.valop = None;
+        self.error = Some(err);
+    }
+
+    /// Assign any supported value to toml unconditionally.
+    pub fn assign<T>(&mut self, rhs: T) where Value: From<T> {
+        if let Some(ref mut v) = self.valop {
+            **v = Value::from(rhs);
+        }
+    }
+
+    /// Put value to leaf node of the same type, record error when type mismatch.
+    fn put<T>(mut self, rhs: T) -> Self where Value: From<T> {
+        let val = Value::from(rhs);
+        let found = match self.valop.as_deref_mut() {
+            Some(v) if v.same_type(&val) => { *v = val; return self; }
+            Some(v) => v.type_str(),
+            None => return self,
+        };
+        let err = PtrError::TypeMismatch { path: self.walked.clone(), expected: val.type_str(), found };
+        self.fail(err);
+        self
+    }
+
+    /// Implment for table << (key, val) pair.
+    fn push_table<K: ToString, T>(mut self, key: K, val: T) -> Self where Value: From<T> {
+        let found = match self.valop.as_deref_mut() {
+            Some(Value::Table(table)) => {
+                table.insert(key.to_string(), Value::from(val));
+                return self;
+            }
+            Some(v) => v.type_str(),
+            None => return self,
+        };
+        let err = PtrError::TypeMismatch { path: self.walked.clone(), expected: "table", found };
+        self.fail(err);
+        self
+    }
+
+    /// Fail if the node is not the expected type, eg: "table" or "array".
+    fn expect_type(mut self, expected: &'static str) -> Self {
+        let found = match self.valop.as_deref() {
+            Some(v) if v.type_str() != expected => v.type_str(),
+            _ => return self,
+        };
+        let err = PtrError::TypeMismatch { path: self.walked.clone(), expected, found };
+        self.fail(err);
+        self
+    }
+
+    /// Implment for array << (val, ).
+    fn push_array<T>(mut self, val: T) -> Self where Value: From<T> {
+        let found = match self.valop.as_deref_mut() {
+            Some(Value::Array(array)) => {
+                array.push(Value::from(val));
+                return self;
+            }
+            Some(v) => v.type_str(),
+            None => return self,
+        };
+        let err = PtrError::TypeMismatch { path: self.walked.clone(), expected: "array", found };
+        self.fail(err);
+        self
+    }
+}
+
+/// Overload `!` operator to test the pointer is invalid.
+impl<'tr> Not for DiagPtrMut<'tr> {
+    type Output = bool;
+    fn not(self) -> Self::Output {
+        self.is_none()
+    }
+}
+
+/// Overload `*` deref operator to treate pointer as `Option<&mut toml::Value>`.
+impl<'tr> Deref for DiagPtrMut<'tr> {
+    type Target = Option<&'tr mut Value>;
+    fn deref(&self) -> &Self::Target {
+        &self.valop
+    }
+}
+
+/// Overload `*` deref operator to treate pointer as `Option<&mut toml::Value>`.
+impl<'tr> DerefMut for DiagPtrMut<'tr> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.valop
+    }
+}
+
+/// Path operator `/`, the same as `TomlPtrMut` but record the failure.
+impl<'tr, Rhs> Div<Rhs> for DiagPtrMut<'tr>
+where Rhs: PathBuilder + Index + Copy
+{
+    type Output = Self;
+    fn div(self, rhs: Rhs) -> Self::Output {
+        self.path(rhs)
+    }
+}
+
+/// Path operator `/` with parsed path.
+impl<'tr> Div<&TomlPath> for DiagPtrMut<'tr> {
+    type Output = Self;
+    fn div(self, rhs: &TomlPath) -> Self::Output {
+        self.path_to(rhs)
+    }
+}
+
+/// Pipe operator `|` to get value or `rhs` as default, the same as `TomlPtr`.
+impl<'tr, T> BitOr<T> for DiagPtrMut<'tr> where TomlPtr<'tr>: BitOr<T> {
+    type Output = <TomlPtr<'tr> as BitOr<T>>::Output;
+    fn bitor(self, rhs: T) -> Self::Output {
+        TomlPtr::new(self.valop.map(|v| &*v)) | rhs
+    }
+}
+
+/// Operator `<<` to put a string into toml leaf node.
+impl<'tr> Shl<&str> for DiagPtrMut<'tr> {
+    type Output = Self;
+    fn shl(self, rhs: &str) -> Self::Output {
+        self.put(rhs)
+    }
+}
+
+/// Operator `<<` to put and move a string into toml leaf node.
+impl<'tr> Shl<String> for DiagPtrMut<'tr> {
+    type Output = Self;
+    fn shl(self, rhs: String) -> Self::Output {
+        self.put(rhs)
+    }
+}
+
+/// Operator `<<` to put a integer value into toml leaf node, any `TomlInteger`.
+impl<'tr, T: TomlInteger> Shl<T> for DiagPtrMut<'tr> {
+    type Output = Self;
+    fn shl(self, rhs: T) -> Self::Output {
+        self.put(rhs.to_i64())
+    }
+}
+
+/// Operator `<<` to put a float value into toml leaf node.
+impl<'tr> Shl<f64> for DiagPtrMut<'tr> {
+    type Output = Self;
+    fn shl(self, rhs: f64) -> Self::Output {
+        self.put(rhs)
+    }
+}
+
+/// Operator `<<` to put a bool value into toml leaf node.
+impl<'tr> Shl<bool> for DiagPtrMut<'tr> {
+    type Output = Self;
+    fn shl(self, rhs: bool) -> Self::Output {
+        self.put(rhs)
+    }
+}
+
+/// Operator `<<` to put a datetime value into toml leaf node.
+impl<'tr> Shl<Datetime> for DiagPtrMut<'tr> {
+    type Output = Self;
+    fn shl(self, rhs: Datetime) -> Self::Output {
+        self.put(rhs)
+    }
+}
+
+/// Operator `<<` to push all entries of table into toml table.
+impl<'tr> Shl<Table> for DiagPtrMut<'tr> {
+    type Output = Self;
+    fn shl(mut self, rhs: Table) -> Self::Output {
+        self = self.expect_type("table");
+        for (key, val) in rhs {
+            self = self.push_table(key, val);
+        }
+        self
+    }
+}
+
+/// Operator `<<` to push all items of vector into toml array.
+impl<'tr> Shl<Vec<Value>> for DiagPtrMut<'tr> {
+    type Output = Self;
+    fn shl(mut self, rhs: Vec<Value>) -> Self::Output {
+        self = self.expect_type("array");
+        for item in rhs {
+            self = self.push_array(item);
+        }
+        self
+    }
+}
+
+/// Operator `<<` to push key-value pair (tuple) into toml table.
+impl<'tr, K: ToString, T> Shl<(K, T)> for DiagPtrMut<'tr> where Value: From<T> {
+    type Output = Self;
+    fn shl(self, rhs: (K, T)) -> Self::Output {
+        self.push_table(rhs.0, rhs.1)
+    }
+}
+
+/// Operator `<<` to push one value tuple into toml array.
+impl<'tr, T> Shl<(T,)> for DiagPtrMut<'tr> where Value: From<T> {
+    type Output = Self;
+    fn shl(self, rhs: (T,)) -> Self::Output {
+        self.push_array(rhs.0)
+    }
+}
+
+/// Operator `<<` to push one item to toml array.
+impl<'tr, T: Copy> Shl<[T;1]> for DiagPtrMut<'tr> where Value: From<T> {
+    type Output = Self;
+    fn shl(self, rhs: [T;1]) -> Self::Output {
+        self.push_array(rhs[0])
+    }
+}
+
+/// Operator `<<` to push a slice to toml array.
+impl<'tr, T: Copy> Shl<&[T]> for DiagPtrMut<'tr> where Value: From<T> {
+    type Output = Self;
+    fn shl(mut self, rhs: &[T]) -> Self::Output {
+        self = self.expect_type("array");
+        for item in rhs {
+            self = self.push_array(*item);
+        }
+        self
+    }
+}
+
+/// Operator `<<=` re-assign to an node unconditionally.
+impl<'tr, T> ShlAssign<T> for DiagPtrMut<'tr> where Value: From<T> {
+    fn shl_assign(&mut self, rhs: T) {
+        self.assign(rhs);
+    }
+}
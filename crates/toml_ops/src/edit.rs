@@ -0,0 +1,647 @@
+use toml_edit::{Document, Item, Value};
+use std::ops::{Div, BitOr, Shl, ShlAssign, Sub, Not, Deref, DerefMut};
+use crate::operator::PathBuilder;
+use crate::path::{TomlPath, PathItem};
+
+/// Index one step into `toml_edit::Item`, like `toml::value::Index` for `toml::Value`.
+/// Note that `Item::get_mut()` would insert missing key, so use `TableLike` instead.
+pub(crate) trait ItemIndex {
+    fn index_item<'tr>(&self, v: &'tr Item) -> Option<&'tr Item>;
+    fn index_item_mut<'tr>(&self, v: &'tr mut Item) -> Option<&'tr mut Item>;
+}
+
+/// String key index into table or inline table.
+impl ItemIndex for &str {
+    fn index_item<'tr>(&self, v: &'tr Item) -> Option<&'tr Item> {
+        v.as_table_like().and_then(|t| t.get(self))
+    }
+    fn index_item_mut<'tr>(&self, v: &'tr mut Item) -> Option<&'tr mut Item> {
+        v.as_table_like_mut().and_then(|t| t.get_mut(self))
+    }
+}
+
+/// Integer index into array or array of tables.
+impl ItemIndex for usize {
+    fn index_item<'tr>(&self, v: &'tr Item) -> Option<&'tr Item> {
+        v.get(*self)
+    }
+    fn index_item_mut<'tr>(&self, v: &'tr mut Item) -> Option<&'tr mut Item> {
+        v.get_mut(*self)
+    }
+}
+
+/// Is array or array of tables, where key segment should be index.
+fn is_array_like(v: &Item) -> bool {
+    v.is_array() || v.is_array_of_tables()
+}
+
+impl TomlPath
+{
+    /// Resolve path readonly for readonly `toml_edit::Item`.
+    /// Key is used as index only when the node is array.
+    pub(crate) fn apply_item<'tr>(&self, v: &'tr Item) -> Option<&'tr Item> {
+        let mut target = v;
+        for p in self.items() {
+            target = match p {
+                PathItem::Key(_) if is_array_like(target) => p.as_index()?.index_item(target)?,
+                PathItem::Key(key) => key.as_str().index_item(target)?,
+                PathItem::Index(index) => index.index_item(target)?,
+            };
+        }
+        Some(target)
+    }
+
+    /// Resolve path readonly for mutable `toml_edit::Item`.
+    pub(crate) fn apply_item_mut<'tr>(&self, v: &'tr mut Item) -> Option<&'tr mut Item> {
+        let mut target = v;
+        for p in self.items() {
+            target = match p {
+                PathItem::Key(_) if is_array_like(target) => p.as_index()?.index_item_mut(target)?,
+                PathItem::Key(key) => key.as_str().index_item_mut(target)?,
+                PathItem::Index(index) => index.index_item_mut(target)?,
+            };
+        }
+        Some(target)
+    }
+
+    /// Remove the node from it's parent table or array, return the removed node.
+    pub(crate) fn remove_item(&self, v: &mut Item) -> Option<Item> {
+        let last = self.last()?;
+        let parent = self.parent()?.apply_item_mut(v)?;
+        if let Some(array) = parent.as_array_mut() {
+            let index = last.as_index().filter(|index| *index < array.len())?;
+            let removed = array.remove(index);
+            if index == 0 {
+                if let Some(first) = array.get_mut(0) {
+                    first.decor_mut().set_prefix("");
+                }
+            }
+            return Some(Item::Value(removed));
+        }
+        if let Some(array) = parent.as_array_of_tables_mut() {
+            let index = last.as_index()?;
+            let table = array.get(index)?.clone();
+            array.remove(index);
+            return Some(Item::Table(table));
+        }
+        match last {
+            PathItem::Key(key) => parent.as_table_like_mut()?.remove(key),
+            PathItem::Index(_) => None,
+        }
+    }
+}
+
+/// Provide format-preserving toml pointer to supported operator overload.
+/// The same as `PathOperator` but backed by `toml_edit`.
+pub trait EditOperator
+{
+    /// Construct immutable toml pointer to some initial node.
+    fn path<'tr>(&'tr self) -> EditPtr<'tr>;
+
+    /// Construct immutable toml pointer and move it follwoing sub path.
+    fn pathto<'tr>(&'tr self, p: &str) -> EditPtr<'tr>;
+
+    /// Construct mutable toml pointer to some initial node.
+    fn path_mut<'tr>(&'tr mut self) -> EditPtrMut<'tr>;
+
+    /// Construct mutable toml pointer and move it follwoing sub path.
+    fn pathto_mut<'tr>(&'tr mut self, p: &str) -> EditPtrMut<'tr>;
+
+    /// Construct immutable toml pointer by RFC 6901 JSON Pointer.
+    fn pointer<'tr>(&'tr self, p: &str) -> EditPtr<'tr>;
+
+    /// Construct mutable toml pointer by RFC 6901 JSON Pointer.
+    fn pointer_mut<'tr>(&'tr mut self, p: &str) -> EditPtrMut<'tr>;
+}
+
+/// Create toml pointer directely from `toml_edit::Item`.
+impl EditOperator for Item
+{
+    fn path<'tr>(&'tr self) -> EditPtr<'tr> {
+        EditPtr::from(self)
+    }
+    fn pathto<'tr>(&'tr self, p: &str) -> EditPtr<'tr> {
+        let valop = p.build_path().and_then(|path| path.apply_item(self));
+        EditPtr { valop }
+    }
+
+    fn path_mut<'tr>(&'tr mut self) -> EditPtrMut<'tr> {
+        EditPtrMut::from(self)
+    }
+    fn pathto_mut<'tr>(&'tr mut self, p: &str) -> EditPtrMut<'tr> {
+        let valop = p.build_path().and_then(|path| path.apply_item_mut(self));
+        EditPtrMut { valop }
+    }
+
+    fn pointer<'tr>(&'tr self, p: &str) -> EditPtr<'tr> {
+        let valop = TomlPath::from_pointer(p).ok().and_then(|path| path.apply_item(self));
+        EditPtr { valop }
+    }
+    fn pointer_mut<'tr>(&'tr mut self, p: &str) -> EditPtrMut<'tr> {
+        let valop = TomlPath::from_pointer(p).ok().and_then(|path| path.apply_item_mut(self));
+        EditPtrMut { valop }
+    }
+}
+
+/// Create toml pointer from the root table of `toml_edit::Document`.
+impl EditOperator for Document
+{
+    fn path<'tr>(&'tr self) -> EditPtr<'tr> {
+        self.as_item().path()
+    }
+    fn pathto<'tr>(&'tr self, p: &str) -> EditPtr<'tr> {
+        self.as_item().pathto(p)
+    }
+
+    fn path_mut<'tr>(&'tr mut self) -> EditPtrMut<'tr> {
+        self.as_item_mut().path_mut()
+    }
+    fn pathto_mut<'tr>(&'tr mut self, p: &str) -> EditPtrMut<'tr> {
+        self.as_item_mut().pathto_mut(p)
+    }
+
+    fn pointer<'tr>(&'tr self, p: &str) -> EditPtr<'tr> {
+        self.as_item().pointer(p)
+    }
+    fn pointer_mut<'tr>(&'tr mut self, p: &str) -> EditPtrMut<'tr> {
+        self.as_item_mut().pointer_mut(p)
+    }
+}
+
+/// Wrapper pointer to `toml_edit::Item` for operator overload.
+/// Must refer to an existed toml document, `Option::None` to refer non-exist node.
+#[derive(Copy, Clone)]
+pub struct EditPtr<'tr> {
+    valop: Option<&'tr Item>,
+}
+
+impl<'tr> EditPtr<'tr> {
+    /// Common constructor
+    pub fn new(valop: Option<&'tr Item>) -> Self {
+        Self { valop }
+    }
+
+    /// As constructor, to build path operand object from a `toml_edit::Item` node.
+    pub fn from(v: &'tr Item) -> Self {
+        Self::new(Some(v))
+    }
+
+    /// Construct new null pointer.
+    fn none() -> Self {
+        Self::new(None)
+    }
+
+    /// Resolve path to sub node.
+    fn path<B>(&self, p: B) -> Self where B: PathBuilder + ItemIndex + Copy {
+        if self.is_none() {
+            return Self::none();
+        }
+
+        let v = self.unwrap();
+        let mut target = p.index_item(v);
+        if target.is_none() {
+            target = p.build_path().and_then(|path| path.apply_item(v));
+        }
+
+        Self::new(target)
+    }
+
+    /// Resolve parsed path to sub node.
+    fn path_to(&self, path: &TomlPath) -> Self {
+        Self::new(self.valop.and_then(|v| path.apply_item(v)))
+    }
+}
+
+/// Overload `!` operator to test the pointer is invalid.
+impl<'tr> Not for EditPtr<'tr> {
+    type Output = bool;
+    fn not(self) -> Self::Output {
+        self.is_none()
+    }
+}
+
+/// Overload `*` deref operator to treate pointer as `Option<&toml_edit::Item>`.
+impl<'tr> Deref for EditPtr<'tr>
+{
+    type Target = Option<&'tr Item>;
+    fn deref(&self) -> &Self::Target {
+        &self.valop
+    }
+}
+
+/// Path operator `/`, visit sub-node by string key for table or index for array.
+/// Can chained as `editptr / "path" / "to" / "node"` or `editptr / "path/to/node"`.
+impl<'tr, Rhs> Div<Rhs> for EditPtr<'tr>
+where Rhs: PathBuilder + ItemIndex + Copy
+{
+    type Output = Self;
+    fn div(self, rhs: Rhs) -> Self::Output {
+        self.path(rhs)
+    }
+}
+
+/// Path operator `/` with parsed path.
+impl<'tr> Div<&TomlPath> for EditPtr<'tr>
+{
+    type Output = Self;
+    fn div(self, rhs: &TomlPath) -> Self::Output {
+        self.path_to(rhs)
+    }
+}
+
+/// Pipe operator `|` with `String`, to get value from string node,
+/// or return `rhs` as default value if pointer is invalid or type mistach.
+impl<'tr> BitOr<String> for EditPtr<'tr>
+{
+    type Output = String;
+    fn bitor(self, rhs: String) -> Self::Output {
+        match self.valop.and_then(Item::as_str) {
+            Some(s) => s.to_string(),
+            None => rhs,
+        }
+    }
+}
+
+/// Pipe operator `|` with string literal, to get string value or `rhs` as default.
+impl<'tr> BitOr<&'static str> for EditPtr<'tr>
+{
+    type Output = &'tr str;
+    fn bitor(self, rhs: &'static str) -> Self::Output {
+        match self.valop {
+            Some(v) => v.as_str().unwrap_or(rhs),
+            None => rhs,
+        }
+    }
+}
+
+/// Pipe operator to get integer value or `rhs` as default.
+impl<'tr> BitOr<i64> for EditPtr<'tr>
+{
+    type Output = i64;
+    fn bitor(self, rhs: i64) -> Self::Output {
+        match self.valop {
+            Some(v) => v.as_integer().unwrap_or(rhs),
+            None => rhs,
+        }
+    }
+}
+
+/// Pipe operator to get float value or `rhs` as default.
+impl<'tr> BitOr<f64> for EditPtr<'tr>
+{
+    type Output = f64;
+    fn bitor(self, rhs: f64) -> Self::Output {
+        match self.valop {
+            Some(v) => v.as_float().unwrap_or(rhs),
+            None => rhs,
+        }
+    }
+}
+
+/// Pipe operator to get bool value or `rhs` as default.
+impl<'tr> BitOr<bool> for EditPtr<'tr>
+{
+    type Output = bool;
+    fn bitor(self, rhs: bool) -> Self::Output {
+        match self.valop {
+            Some(v) => v.as_bool().unwrap_or(rhs),
+            None => rhs,
+        }
+    }
+}
+
+/// Mutable version of pointer wrapper of `toml_edit::Item` for operator overload.
+/// Must refer to existed toml document, `Option::None` to refer non-exist node.
+/// Modification through it keep the comments and whitespace around the node.
+pub struct EditPtrMut<'tr> {
+    valop: Option<&'tr mut Item>,
+}
+
+impl<'tr> EditPtrMut<'tr> {
+    /// Common constructor
+    pub fn new(valop: Option<&'tr mut Item>) -> Self {
+        Self { valop }
+    }
+
+    /// As constructor, to build path operand object from a `toml_edit::Item` node.
+    pub fn from(v: &'tr mut Item) -> Self {
+        Self::new(Some(v))
+    }
+
+    /// Construct new null pointer.
+    fn none() -> Self {
+        Self::new(None)
+    }
+
+    /// Cast to immutable toml pointer.
+    fn immut(&mut self) -> EditPtr<'tr> {
+        match self.take() {
+            Some(v) => EditPtr::from(v),
+            None => EditPtr::none(),
+        }
+    }
+
+    /// Resolve path to sub node.
+    fn path<B>(&mut self, p: B) -> Self where B: PathBuilder + ItemIndex + Copy {
+        if self.is_none() {
+            return Self::none();
+        }
+
+        let v = self.take().unwrap();
+
+        // Note: the same as `TomlPtrMut`, test path is valid with immutable
+        // version first to avoid mutable borrow twice.
+        let mut target = None;
+        if p.index_item(v).is_some() {
+            target = p.index_item_mut(v);
+        }
+        else if let Some(path) = p.build_path() {
+            target = path.apply_item_mut(v);
+        }
+
+        Self::new(target)
+    }
+
+    /// Resolve parsed path to sub node.
+    fn path_to(&mut self, path: &TomlPath) -> Self {
+        Self::new(self.take().and_then(|v| path.apply_item_mut(v)))
+    }
+
+    /// Assign any supported value to toml, keep the decor if it is value before.
+    /// But canno overload operator=, will choose <<= instead.
+    pub fn assign<T>(&mut self, rhs: T) where Value: From<T> {
+        if let Some(ref mut v) = self.valop {
+            Self::replace_val(v, rhs);
+        }
+    }
+
+    /// Replace the value in node with the prefix and suffix decor untouched.
+    fn replace_val<T>(v: &mut Item, rhs: T) where Value: From<T> {
+        let mut val = Value::from(rhs);
+        if let Some(old) = v.as_value() {
+            *val.decor_mut() = old.decor().clone();
+        }
+        *v = Item::Value(val);
+    }
+
+    /// Put a value to toml and return pointer to it.
+    fn put_val<T>(v: &'tr mut Item, rhs: T) -> Self where Value: From<T> {
+        Self::replace_val(v, rhs);
+        Self::from(v)
+    }
+
+    /// Put value to string toml node pointer, would invalidate it when type mismatch.
+    /// Implement for << String and << &str.
+    fn put_string(&mut self, rhs: String) -> Self {
+        match self.take() {
+            Some(v) if v.is_str() => Self::put_val(v, rhs),
+            _ => Self::none()
+        }
+    }
+
+    /// Implement for << i64.
+    fn put_integer(&mut self, rhs: i64) -> Self {
+        match self.take() {
+            Some(v) if v.is_integer() => Self::put_val(v, rhs),
+            _ => Self::none()
+        }
+    }
+
+    /// Implement for << f64.
+    fn put_float(&mut self, rhs: f64) -> Self {
+        match self.take() {
+            Some(v) if v.is_float() => Self::put_val(v, rhs),
+            _ => Self::none()
+        }
+    }
+
+    /// Implement for << bool.
+    fn put_bool(&mut self, rhs: bool) -> Self {
+        match self.take() {
+            Some(v) if v.is_bool() => Self::put_val(v, rhs),
+            _ => Self::none()
+        }
+    }
+
+    /// Implment for table << (key, val) pair, either standard or inline table.
+    fn push_table<K: ToString, T>(&mut self, key: K, val: T) -> Self where Value: From<T> {
+        match self.take() {
+            Some(v) if v.is_table_like() => {
+                let table = v.as_table_like_mut().unwrap();
+                let key = key.to_string();
+                match table.get_mut(&key) {
+                    Some(old) => Self::replace_val(old, val),
+                    None => { table.insert(&key, Item::Value(Value::from(val))); }
+                }
+                Self::from(v)
+            }
+            _ => Self::none()
+        }
+    }
+
+    /// Implment for array << (val, ) << [item] .
+    /// Array of tables only accept inline table as new item.
+    fn push_array<T>(&mut self, val: T) -> Self where Value: From<T> {
+        match self.take() {
+            Some(v) if v.is_array() => {
+                v.as_array_mut().unwrap().push(Value::from(val));
+                Self::from(v)
+            }
+            Some(v) if v.is_array_of_tables() => {
+                match Value::from(val) {
+                    Value::InlineTable(table) => {
+                        v.as_array_of_tables_mut().unwrap().push(table.into_table());
+                        Self::from(v)
+                    }
+                    _ => Self::none()
+                }
+            }
+            _ => Self::none()
+        }
+    }
+}
+
+/// Overload `!` operator to test the pointer is invalid.
+impl<'tr> Not for EditPtrMut<'tr> {
+    type Output = bool;
+    fn not(self) -> Self::Output {
+        self.is_none()
+    }
+}
+
+/// Overload `*` deref operator to treate pointer as `Option<&mut toml_edit::Item>`.
+impl<'tr> Deref for EditPtrMut<'tr> {
+    type Target = Option<&'tr mut Item>;
+    fn deref(&self) -> &Self::Target {
+        &self.valop
+    }
+}
+
+/// Overload `*` deref operator to treate pointer as `Option<&mut toml_edit::Item>`.
+impl<'tr> DerefMut for EditPtrMut<'tr> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.valop
+    }
+}
+
+/// Path operator `/`, visit sub-node by string key for table or index for array.
+impl<'tr, Rhs> Div<Rhs> for EditPtrMut<'tr>
+where Rhs: PathBuilder + ItemIndex + Copy
+{
+    type Output = Self;
+    fn div(mut self, rhs: Rhs) -> Self::Output {
+        self.path(rhs)
+    }
+}
+
+/// Path operator `/` with parsed path.
+impl<'tr> Div<&TomlPath> for EditPtrMut<'tr>
+{
+    type Output = Self;
+    fn div(mut self, rhs: &TomlPath) -> Self::Output {
+        self.path_to(rhs)
+    }
+}
+
+/// Pipe operator `|` with `String`, to get value from string node,
+/// or return `rhs` as default value if pointer is invalid or type mistach.
+impl<'tr> BitOr<String> for EditPtrMut<'tr>
+{
+    type Output = String;
+    fn bitor(mut self, rhs: String) -> Self::Output {
+        self.immut().bitor(rhs)
+    }
+}
+
+/// Pipe operator `|` with string literal, to get string value or `rhs` as default.
+impl<'tr> BitOr<&'static str> for EditPtrMut<'tr> {
+    type Output = &'tr str;
+    fn bitor(mut self, rhs: &'static str) -> Self::Output {
+        self.immut().bitor(rhs)
+    }
+}
+
+/// Pipe operator to get integer value or `rhs` as default.
+impl<'tr> BitOr<i64> for EditPtrMut<'tr> {
+    type Output = i64;
+    fn bitor(mut self, rhs: i64) -> Self::Output {
+        self.immut().bitor(rhs)
+    }
+}
+
+/// Pipe operator to get float value or `rhs` as default.
+impl<'tr> BitOr<f64> for EditPtrMut<'tr> {
+    type Output = f64;
+    fn bitor(mut self, rhs: f64) -> Self::Output {
+        self.immut().bitor(rhs)
+    }
+}
+
+/// Pipe operator to get bool value or `rhs` as default.
+impl<'tr> BitOr<bool> for EditPtrMut<'tr> {
+    type Output = bool;
+    fn bitor(mut self, rhs: bool) -> Self::Output {
+        self.immut().bitor(rhs)
+    }
+}
+
+/// Operator `<<` to put a string into toml leaf node, keep the decor.
+/// While the data type mismatch the node, set self pointer to `None`.
+impl<'tr> Shl<&str> for EditPtrMut<'tr> {
+    type Output = Self;
+    fn shl(mut self, rhs: &str) -> Self::Output {
+        self.put_string(rhs.to_string())
+    }
+}
+
+/// Operator `<<` to put and move a string into toml leaf node.
+impl<'tr> Shl<String> for EditPtrMut<'tr> {
+    type Output = Self;
+    fn shl(mut self, rhs: String) -> Self::Output {
+        self.put_string(rhs)
+    }
+}
+
+/// Operator `<<` to put a integer value into toml leaf node.
+impl<'tr> Shl<i64> for EditPtrMut<'tr> {
+    type Output = Self;
+    fn shl(mut self, rhs: i64) -> Self::Output {
+        self.put_integer(rhs)
+    }
+}
+
+/// Operator `<<` to put a float value into toml leaf node.
+impl<'tr> Shl<f64> for EditPtrMut<'tr> {
+    type Output = Self;
+    fn shl(mut self, rhs: f64) -> Self::Output {
+        self.put_float(rhs)
+    }
+}
+
+/// Operator `<<` to put a bool value into toml leaf node.
+impl<'tr> Shl<bool> for EditPtrMut<'tr> {
+    type Output = Self;
+    fn shl(mut self, rhs: bool) -> Self::Output {
+        self.put_bool(rhs)
+    }
+}
+
+/// Operator `<<` to push key-value pair (tuple) into toml table.
+/// Overwrite the value of existed key but keep it's decor.
+impl<'tr, K: ToString, T> Shl<(K, T)> for EditPtrMut<'tr> where Value: From<T> {
+    type Output = Self;
+    fn shl(mut self, rhs: (K, T)) -> Self::Output {
+        self.push_table(rhs.0, rhs.1)
+    }
+}
+
+/// Operator `<<` to push one value tuple into toml array.
+impl<'tr, T> Shl<(T,)> for EditPtrMut<'tr> where Value: From<T> {
+    type Output = Self;
+    fn shl(mut self, rhs: (T,)) -> Self::Output {
+        self.push_array(rhs.0)
+    }
+}
+
+/// Operator `<<` to push one item to toml array.
+impl<'tr, T: Copy> Shl<[T;1]> for EditPtrMut<'tr> where Value: From<T> {
+    type Output = Self;
+    fn shl(mut self, rhs: [T;1]) -> Self::Output {
+        self.push_array(rhs[0])
+    }
+}
+
+/// Operator `<<` to push a slice to toml array.
+impl<'tr, T: Copy> Shl<&[T]> for EditPtrMut<'tr> where Value: From<T> {
+    type Output = Self;
+    fn shl(mut self, rhs: &[T]) -> Self::Output {
+        for item in rhs {
+            self = self.push_array(*item);
+        }
+        self
+    }
+}
+
+/// Operator `-` to remove sub node from table or array, return the removed node.
+/// The decor of other nodes is kept.
+impl<'tr, Rhs> Sub<Rhs> for EditPtrMut<'tr>
+where Rhs: PathBuilder + ItemIndex + Copy
+{
+    type Output = Option<Item>;
+    fn sub(mut self, rhs: Rhs) -> Self::Output {
+        let v = self.valop.as_deref_mut()?;
+        let path = match rhs.index_item(v) {
+            Some(_) => TomlPath::from(vec![rhs.path_item()]),
+            None => rhs.build_path()?,
+        };
+        path.remove_item(v)
+    }
+}
+
+/// Operator `<<=` re-assign to an node unconditionally, may change it data type.
+/// The decor of old value is kept.
+impl<'tr, T> ShlAssign<T> for EditPtrMut<'tr> where Value: From<T> {
+    fn shl_assign(&mut self, rhs: T) {
+        self.assign(rhs);
+    }
+}
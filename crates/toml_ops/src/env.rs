@@ -0,0 +1,180 @@
+use toml::Value;
+use toml::value::Datetime;
+use std::fmt;
+
+use crate::path::TomlPath;
+
+/// Error when overlay environment variable onto toml.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EnvError {
+    /// The variable map to no existing node in strict mode, or cannot create it.
+    UnknownKey { var: String, path: TomlPath },
+    /// The value cannot convert to the type of existing node.
+    Coerce { var: String, path: TomlPath, expected: &'static str },
+}
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvError::UnknownKey { var, path } => {
+                write!(f, "environment variable {var}: unknown key `{path}`")
+            }
+            EnvError::Coerce { var, path, expected } => {
+                write!(f, "environment variable {var}: expect {expected} for `{path}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EnvError {}
+
+/// Overlay environment variables onto toml, eg: `APP_HOST__PORT=9090` with
+/// prefix `APP_` and separator `__` override `host.port`. The prefix should be
+/// followed by the separator, so prefix `APP` with separator `_` also work,
+/// but ignore unrelated variable like `APPDATA`.
+///
+/// The key is matched case-insensitively, and `-` in key can be written as `_`.
+/// Numeric segment is index of array. The value is coerced to the type of the
+/// existing node, and array is split from comma-separated string.
+/// Unknown key is created as string, or rejected in strict mode.
+#[derive(Debug, Clone)]
+pub struct EnvOverlay {
+    prefix: String,
+    separator: String,
+    strict: bool,
+}
+
+impl EnvOverlay {
+    /// Construct with variable prefix, and the default separator `__`.
+    pub fn new(prefix: &str) -> Self {
+        Self { prefix: prefix.to_string(), separator: "__".to_string(), strict: false }
+    }
+
+    /// Set the separator between nested keys.
+    pub fn separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Reject variable that map to no existing node.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Overlay the variables of current process environment.
+    pub fn apply(&self, v: &mut Value) -> Result<usize, EnvError> {
+        self.apply_from(v, std::env::vars())
+    }
+
+    /// Overlay the variables from given map, return the number of applied.
+    /// It is atomic, the toml is left untouched if any variable fail.
+    pub fn apply_from<I, K, S>(&self, v: &mut Value, vars: I) -> Result<usize, EnvError>
+    where I: IntoIterator<Item = (K, S)>, K: AsRef<str>, S: AsRef<str>
+    {
+        let mut vars: Vec<(String, String)> = vars.into_iter()
+            .filter(|(name, _)| self.strip_prefix(name.as_ref()).is_some())
+            .map(|(name, val)| (name.as_ref().to_string(), val.as_ref().to_string()))
+            .collect();
+        vars.sort();
+
+        let mut doc = v.clone();
+        let mut count = 0;
+        for (name, val) in &vars {
+            let rest = self.strip_prefix(name).unwrap_or_default();
+            let segments: Vec<&str> = rest.split(self.separator.as_str()).collect();
+            if segments.iter().any(|seg| seg.is_empty()) {
+                if self.strict {
+                    return Err(EnvError::UnknownKey { var: name.clone(), path: TomlPath::new() });
+                }
+                continue;
+            }
+            self.apply_var(&mut doc, name, &segments, val)?;
+            count += 1;
+        }
+        *v = doc;
+        Ok(count)
+    }
+
+    /// The key part of variable name, `None` if not under the prefix.
+    /// The prefix should end at a separator or punctuation like `APP_`,
+    /// or be followed by the separator, so `APPDATA` is not under `APP`.
+    fn strip_prefix<'a>(&self, name: &'a str) -> Option<&'a str> {
+        let rest = name.strip_prefix(self.prefix.as_str())?;
+        if let Some(rest) = rest.strip_prefix(self.separator.as_str()) {
+            return Some(rest);
+        }
+        let ended = self.prefix.is_empty()
+            || self.prefix.ends_with(self.separator.as_str())
+            || self.prefix.ends_with(|c: char| !c.is_alphanumeric());
+        if ended { Some(rest) } else { None }
+    }
+
+    fn apply_var(&self, doc: &mut Value, name: &str, segments: &[&str], val: &str) -> Result<(), EnvError> {
+        let (path, found) = resolve(doc, segments);
+        let unknown = || EnvError::UnknownKey { var: name.to_string(), path: path.clone() };
+        if found {
+            let node = path.apply_mut(doc).ok_or_else(unknown)?;
+            *node = coerce(node, val).ok_or_else(|| {
+                EnvError::Coerce { var: name.to_string(), path: path.clone(), expected: node.type_str() }
+            })?;
+            return Ok(());
+        }
+        if self.strict {
+            return Err(unknown());
+        }
+        match path.apply_create(doc, false) {
+            Some((node, true)) => { *node = Value::from(val); Ok(()) }
+            _ => Err(unknown()),
+        }
+    }
+}
+
+/// Match segments to existing keys, the missing key is lower case.
+fn resolve(v: &Value, segments: &[&str]) -> (TomlPath, bool) {
+    let mut path = TomlPath::new();
+    let mut node = Some(v);
+    for seg in segments {
+        node = match node {
+            Some(Value::Table(table)) => {
+                let key = table.keys().find(|key| key.to_uppercase().replace('-', "_") == seg.to_uppercase());
+                match key {
+                    Some(key) => { path.push_key(key); table.get(key) }
+                    None => { path.push_key(seg.to_lowercase()); None }
+                }
+            }
+            Some(Value::Array(array)) => match seg.parse::<usize>() {
+                Ok(index) => { path.push_index(index); array.get(index) }
+                Err(_) => { path.push_key(seg.to_lowercase()); None }
+            },
+            _ => { path.push_key(seg.to_lowercase()); None }
+        };
+    }
+    (path, node.is_some())
+}
+
+/// Convert string to the type of node.
+fn coerce(node: &Value, val: &str) -> Option<Value> {
+    let coerced = match node {
+        Value::String(_) => Value::from(val),
+        _ if val.trim() != val => return coerce(node, val.trim()),
+        Value::Integer(_) => Value::from(val.parse::<i64>().ok()?),
+        Value::Float(_) => Value::from(val.parse::<f64>().ok()?),
+        Value::Boolean(_) => match val.to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Value::from(true),
+            "false" | "no" | "off" | "0" => Value::from(false),
+            _ => return None,
+        },
+        Value::Datetime(_) => Value::from(val.parse::<Datetime>().ok()?),
+        Value::Array(array) => {
+            let item = array.first().cloned().unwrap_or_else(|| Value::from(""));
+            let mut items = Vec::new();
+            for part in val.split(',').filter(|part| !part.trim().is_empty()) {
+                items.push(coerce(&item, part.trim())?);
+            }
+            Value::Array(items)
+        }
+        Value::Table(_) => return None,
+    };
+    Some(coerced)
+}
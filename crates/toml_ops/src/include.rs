@@ -0,0 +1,294 @@
+use toml::Value;
+use toml::value::Table;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::path::{TomlPath, PathItem};
+use crate::merge::Merge;
+
+/// Origin of nodes, map path to index of source file, the nearest ancestor
+/// in map tell where the node came from, always has entry for root.
+type Origins = BTreeMap<TomlPath, usize>;
+
+/// Error when load toml file with include directive.
+#[derive(Debug)]
+pub enum IncludeError {
+    /// Fail to read file.
+    Io { file: PathBuf, error: std::io::Error },
+    /// Fail to parse file, the error is boxed as it is large.
+    Parse { file: PathBuf, error: Box<toml::de::Error> },
+    /// The include value is not string or array of string, or bad glob.
+    Invalid { file: PathBuf, path: TomlPath, reason: String },
+    /// Include cycle, the files from the first to the one include it again.
+    Cycle { files: Vec<PathBuf> },
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IncludeError::Io { file, error } => {
+                write!(f, "failed to read {}: {error}", file.display())
+            }
+            IncludeError::Parse { file, error } => {
+                write!(f, "failed to parse {}: {error}", file.display())
+            }
+            IncludeError::Invalid { file, path, reason } => {
+                write!(f, "invalid include at `{path}` in {}: {reason}", file.display())
+            }
+            IncludeError::Cycle { files } => {
+                let files: Vec<String> = files.iter().map(|file| file.display().to_string()).collect();
+                write!(f, "include cycle: {} -> {}", files.join(" -> "), files[0])
+            }
+        }
+    }
+}
+
+impl std::error::Error for IncludeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IncludeError::Io { error, .. } => Some(error),
+            IncludeError::Parse { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Load toml file that include other files by key, eg: `include = ["a.toml", "b/*.toml"]`.
+///
+/// The include key can be in any table, the files are relative to the file
+/// where the key is, and glob pattern is expanded in sorted order.
+/// The included files are merged in order into the table at the location of
+/// include key, then the table's own content is merged over them.
+/// Include recursively is allowed, but cycle is reported as error.
+#[derive(Debug, Clone)]
+pub struct Loader {
+    key: String,
+    merge: Merge,
+}
+
+impl Default for Loader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Loader {
+    /// Construct loader for `include` key, with default merge.
+    pub fn new() -> Self {
+        Self { key: "include".to_string(), merge: Merge::default() }
+    }
+
+    /// Set the key name of include directive.
+    pub fn key(mut self, key: &str) -> Self {
+        self.key = key.to_string();
+        self
+    }
+
+    /// Set the merge options, such as array strategy.
+    pub fn merge(mut self, merge: Merge) -> Self {
+        self.merge = merge;
+        self
+    }
+
+    /// Load the toml file and all the included files.
+    pub fn load<P: AsRef<Path>>(&self, file: P) -> Result<Loaded, IncludeError> {
+        let mut state = LoadState { loader: self, files: Vec::new(), stack: Vec::new() };
+        let (value, origins) = state.load_file(file.as_ref())?;
+        Ok(Loaded { value, files: state.files, origins })
+    }
+}
+
+/// The result of `Loader`, the merged value and the source file of each node.
+#[derive(Debug, Clone)]
+pub struct Loaded {
+    value: Value,
+    files: Vec<PathBuf>,
+    origins: Origins,
+}
+
+impl Loaded {
+    /// The merged value.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Take the merged value.
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+
+    /// All the loaded files, the first is the main file.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// The file where the node came from, or where it's nearest ancestor came
+    /// from. Table merged from many files is regard as from the including file.
+    pub fn source(&self, path: &TomlPath) -> Option<&Path> {
+        let mut path = path.clone();
+        loop {
+            if let Some(index) = self.origins.get(&path) {
+                return Some(&self.files[*index]);
+            }
+            path.pop()?;
+        }
+    }
+}
+
+struct LoadState<'a> {
+    loader: &'a Loader,
+    files: Vec<PathBuf>,
+    stack: Vec<PathBuf>,
+}
+
+impl<'a> LoadState<'a> {
+    fn load_file(&mut self, file: &Path) -> Result<(Value, Origins), IncludeError> {
+        let io_error = |error| IncludeError::Io { file: file.to_path_buf(), error };
+        let file = file.canonicalize().map_err(io_error)?;
+        if let Some(pos) = self.stack.iter().position(|f| *f == file) {
+            return Err(IncludeError::Cycle { files: self.stack[pos..].to_vec() });
+        }
+        let text = std::fs::read_to_string(&file).map_err(io_error)?;
+        let value: Value = toml::from_str(&text).map_err(|error| {
+            IncludeError::Parse { file: file.clone(), error: Box::new(error) }
+        })?;
+
+        let index = match self.files.iter().position(|f| *f == file) {
+            Some(index) => index,
+            None => { self.files.push(file.clone()); self.files.len() - 1 }
+        };
+        self.stack.push(file);
+        let result = self.expand(&value, &mut TomlPath::new(), index);
+        self.stack.pop();
+        result
+    }
+
+    /// Expand include key in the node at path recursively.
+    fn expand(&mut self, v: &Value, path: &mut TomlPath, index: usize) -> Result<(Value, Origins), IncludeError> {
+        let mut origins = Origins::new();
+        origins.insert(TomlPath::new(), index);
+        match v {
+            Value::Table(table) => {
+                let mut own = Table::new();
+                for (key, val) in table.iter().filter(|(key, _)| **key != self.loader.key) {
+                    path.push_key(key);
+                    let (child, child_origins) = self.expand(val, path, index)?;
+                    path.pop();
+                    own.insert(key.clone(), child);
+                    shift_origins(&mut origins, child_origins, PathItem::Key(key.clone()), index);
+                }
+                let own = Value::Table(own);
+                let include = match table.get(&self.loader.key) {
+                    Some(include) => include,
+                    None => return Ok((own, origins)),
+                };
+
+                let mut base = Value::Table(Table::new());
+                let mut base_origins = Origins::new();
+                base_origins.insert(TomlPath::new(), index);
+                for file in self.include_files(include, path)? {
+                    let (val, val_origins) = self.load_file(&file)?;
+                    self.graft(&mut base, &mut base_origins, &val, &val_origins);
+                }
+                self.graft(&mut base, &mut base_origins, &own, &origins);
+                Ok((base, base_origins))
+            }
+            Value::Array(array) => {
+                let mut out = Vec::with_capacity(array.len());
+                for (i, val) in array.iter().enumerate() {
+                    path.push_index(i);
+                    let (child, child_origins) = self.expand(val, path, index)?;
+                    path.pop();
+                    out.push(child);
+                    shift_origins(&mut origins, child_origins, PathItem::Index(i), index);
+                }
+                Ok((Value::Array(out), origins))
+            }
+            _ => Ok((v.clone(), origins)),
+        }
+    }
+
+    /// Merge overlay into base, and copy the origins of assigned nodes.
+    fn graft(&self, base: &mut Value, base_origins: &mut Origins, overlay: &Value, origins: &Origins) {
+        self.loader.merge.merge(base, overlay, &mut TomlPath::new(), &mut |path, deleted| {
+            let below: Vec<TomlPath> = base_origins.range(path.clone()..)
+                .take_while(|(p, _)| p.starts_with(path))
+                .map(|(p, _)| p.clone())
+                .collect();
+            for p in below {
+                base_origins.remove(&p);
+            }
+            if deleted {
+                return;
+            }
+            let mut ancestor = path.clone();
+            let index = loop {
+                if let Some(index) = origins.get(&ancestor) {
+                    break *index;
+                }
+                ancestor.pop();
+            };
+            base_origins.insert(path.clone(), index);
+            for (p, index) in origins.range(path.clone()..).take_while(|(p, _)| p.starts_with(path)) {
+                base_origins.insert(p.clone(), *index);
+            }
+        });
+    }
+
+    /// Resolve the include value to file list, relative to current file.
+    fn include_files(&self, include: &Value, path: &TomlPath) -> Result<Vec<PathBuf>, IncludeError> {
+        let current = self.stack.last().cloned().unwrap_or_default();
+        let invalid = |reason: String| {
+            let mut path = path.clone();
+            path.push_key(&self.loader.key);
+            IncludeError::Invalid { file: current.clone(), path, reason }
+        };
+        let patterns: Vec<&str> = match include {
+            Value::String(s) => vec![s.as_str()],
+            Value::Array(array) => array.iter()
+                .map(|item| item.as_str().ok_or_else(|| invalid("expect string of file".to_string())))
+                .collect::<Result<_, _>>()?,
+            _ => return Err(invalid("expect string or array of string".to_string())),
+        };
+
+        let dir = current.parent().unwrap_or_else(|| Path::new(""));
+        let mut files = Vec::new();
+        for pattern in patterns {
+            let full = dir.join(pattern);
+            if !pattern.contains(['*', '?', '[']) {
+                files.push(full);
+                continue;
+            }
+            // the directory may contain glob special chars itself
+            let full = match dir.to_str() {
+                Some(d) if !d.is_empty() && !Path::new(pattern).is_absolute() => {
+                    format!("{}/{pattern}", glob::Pattern::escape(d))
+                }
+                _ => full.to_string_lossy().to_string(),
+            };
+            let mut matched: Vec<PathBuf> = glob::glob(&full)
+                .map_err(|e| invalid(format!("bad pattern `{pattern}`: {e}")))?
+                .filter_map(Result::ok)
+                .collect();
+            matched.sort();
+            files.extend(matched);
+        }
+        Ok(files)
+    }
+}
+
+/// Move the origins of child node under the parent, skip the redundant root.
+fn shift_origins(origins: &mut Origins, child: Origins, item: PathItem, index: usize) {
+    for (path, i) in child {
+        if path.is_empty() && i == index {
+            continue;
+        }
+        let mut full = TomlPath::new();
+        full.push(item.clone());
+        for item in path.items() {
+            full.push(item.clone());
+        }
+        origins.insert(full, i);
+    }
+}
@@ -0,0 +1,264 @@
+use toml::Value;
+use toml::value::Table;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::path::TomlPath;
+
+/// Error when expand `${...}` reference, `path` is where the string is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpError {
+    /// Unclosed `${`, or the reference is not valid path.
+    Syntax { path: TomlPath, reason: String },
+    /// The referred node is not found.
+    Missing { path: TomlPath, reference: String },
+    /// Table or array referred inside a longer string.
+    NotScalar { path: TomlPath, reference: String },
+    /// Environment variable not set and no default, or lookup not enabled.
+    Env { path: TomlPath, name: String },
+    /// Reference cycle, the nodes from the first to the one refer back.
+    Cycle { cycle: Vec<TomlPath> },
+}
+
+impl fmt::Display for InterpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterpError::Syntax { path, reason } => {
+                write!(f, "invalid reference at `{path}`: {reason}")
+            }
+            InterpError::Missing { path, reference } => {
+                write!(f, "reference `{reference}` at `{path}` not found")
+            }
+            InterpError::NotScalar { path, reference } => {
+                write!(f, "reference `{reference}` at `{path}` cannot embed in string")
+            }
+            InterpError::Env { path, name } => {
+                write!(f, "environment variable `{name}` at `{path}` not available")
+            }
+            InterpError::Cycle { cycle } => {
+                let cycle: Vec<String> = cycle.iter().map(|path| format!("`{path}`")).collect();
+                write!(f, "reference cycle: {} -> {}", cycle.join(" -> "), cycle[0])
+            }
+        }
+    }
+}
+
+impl std::error::Error for InterpError {}
+
+/// Expand `${path}` references in string values, eg: `"${host.ip}:${host.port}"`.
+///
+/// * the path is in crate path syntax, always from the root.
+/// * string that is exactly one reference keep the type of referred node,
+///   which can be any value, eg: `port = "${host.port}"` is integer.
+/// * other reference is rendered as string, table or array is not allowed.
+/// * `$${` is escaped as literal `${`, lone `$` is kept as it is.
+/// * `${env:NAME}` and `${env:NAME:-default}` lookup environment variable,
+///   only when enabled by `env()` or `env_vars()`.
+#[derive(Debug, Clone, Default)]
+pub struct Interpolate {
+    env: EnvSource,
+}
+
+#[derive(Debug, Clone, Default)]
+enum EnvSource {
+    #[default]
+    Disabled,
+    Process,
+    Vars(HashMap<String, String>),
+}
+
+impl Interpolate {
+    /// Construct resolver without environment lookup.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable lookup variable of current process environment.
+    pub fn env(mut self, enable: bool) -> Self {
+        self.env = if enable { EnvSource::Process } else { EnvSource::Disabled };
+        self
+    }
+
+    /// Enable lookup variable from given map instead of process environment.
+    pub fn env_vars<I, K, S>(mut self, vars: I) -> Self
+    where I: IntoIterator<Item = (K, S)>, K: ToString, S: ToString
+    {
+        let vars = vars.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        self.env = EnvSource::Vars(vars);
+        self
+    }
+
+    /// Return new value with all references expanded.
+    pub fn resolve(&self, v: &Value) -> Result<Value, InterpError> {
+        let mut state = State { root: v, interp: self, done: HashMap::new(), stack: Vec::new() };
+        state.expand(&mut TomlPath::new(), v)
+    }
+
+    fn lookup_env(&self, name: &str) -> Option<String> {
+        match &self.env {
+            EnvSource::Disabled => None,
+            EnvSource::Process => std::env::var(name).ok(),
+            EnvSource::Vars(vars) => vars.get(name).cloned(),
+        }
+    }
+}
+
+/// Part of string split by reference.
+enum Piece {
+    Text(String),
+    Ref(String),
+}
+
+/// Split string to text and reference, `None` if `${` is unclosed.
+fn split_pieces(s: &str) -> Option<Vec<Piece>> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut rest = s;
+    while let Some(pos) = rest.find('$') {
+        text.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        if rest.starts_with("$${") {
+            text.push_str("${");
+            rest = &rest[3..];
+        }
+        else if let Some(inner) = rest.strip_prefix("${") {
+            let end = inner.find('}')?;
+            if !text.is_empty() {
+                pieces.push(Piece::Text(std::mem::take(&mut text)));
+            }
+            pieces.push(Piece::Ref(inner[..end].to_string()));
+            rest = &inner[end + 1..];
+        }
+        else {
+            text.push('$');
+            rest = &rest[1..];
+        }
+    }
+    text.push_str(rest);
+    if !text.is_empty() || pieces.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+    Some(pieces)
+}
+
+struct State<'a> {
+    root: &'a Value,
+    interp: &'a Interpolate,
+    done: HashMap<TomlPath, Value>,
+    stack: Vec<TomlPath>,
+}
+
+impl<'a> State<'a> {
+    /// Expand node at path recursively.
+    fn expand(&mut self, path: &mut TomlPath, v: &'a Value) -> Result<Value, InterpError> {
+        match v {
+            Value::String(s) => self.expand_str(path, s),
+            Value::Table(table) => {
+                let mut out = Table::new();
+                for (key, val) in table {
+                    path.push_key(key);
+                    out.insert(key.clone(), self.expand(path, val)?);
+                    path.pop();
+                }
+                Ok(Value::Table(out))
+            }
+            Value::Array(array) => {
+                let mut out = Vec::with_capacity(array.len());
+                for (index, val) in array.iter().enumerate() {
+                    path.push_index(index);
+                    out.push(self.expand(path, val)?);
+                    path.pop();
+                }
+                Ok(Value::Array(out))
+            }
+            _ => Ok(v.clone()),
+        }
+    }
+
+    fn expand_str(&mut self, path: &TomlPath, s: &str) -> Result<Value, InterpError> {
+        if let Some(done) = self.done.get(path) {
+            return Ok(done.clone());
+        }
+        if let Some(pos) = self.stack.iter().position(|p| p == path) {
+            return Err(InterpError::Cycle { cycle: self.stack[pos..].to_vec() });
+        }
+        let pieces = split_pieces(s).ok_or_else(|| {
+            InterpError::Syntax { path: path.clone(), reason: "unclosed `${`".to_string() }
+        })?;
+
+        self.stack.push(path.clone());
+        let result = self.join(path, &pieces);
+        self.stack.pop();
+
+        let value = result?;
+        self.done.insert(path.clone(), value.clone());
+        Ok(value)
+    }
+
+    /// Join the pieces, or keep the type of single reference.
+    fn join(&mut self, path: &TomlPath, pieces: &[Piece]) -> Result<Value, InterpError> {
+        if let [Piece::Ref(reference)] = pieces {
+            return self.refer(path, reference);
+        }
+        let mut out = String::new();
+        for piece in pieces {
+            match piece {
+                Piece::Text(text) => out.push_str(text),
+                Piece::Ref(reference) => match self.refer(path, reference)? {
+                    Value::String(s) => out.push_str(&s),
+                    Value::Integer(i) => out.push_str(&i.to_string()),
+                    Value::Float(f) => out.push_str(&f.to_string()),
+                    Value::Boolean(b) => out.push_str(&b.to_string()),
+                    Value::Datetime(dt) => out.push_str(&dt.to_string()),
+                    _ => {
+                        return Err(InterpError::NotScalar { path: path.clone(), reference: reference.clone() });
+                    }
+                },
+            }
+        }
+        Ok(Value::String(out))
+    }
+
+    /// Resolve one reference in the string at path.
+    fn refer(&mut self, path: &TomlPath, reference: &str) -> Result<Value, InterpError> {
+        if let Some(env) = reference.strip_prefix("env:") {
+            let (name, default) = match env.split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (env, None),
+            };
+            return self.interp.lookup_env(name)
+                .or_else(|| default.map(str::to_string))
+                .map(Value::String)
+                .ok_or_else(|| InterpError::Env { path: path.clone(), name: name.to_string() });
+        }
+
+        let target = TomlPath::parse(reference).map_err(|e| {
+            InterpError::Syntax { path: path.clone(), reason: e.to_string() }
+        })?;
+        match canonical(self.root, &target) {
+            Some((mut target, node)) => self.expand(&mut target, node),
+            None => Err(InterpError::Missing { path: path.clone(), reference: reference.to_string() }),
+        }
+    }
+}
+
+/// Resolve path and normalize numeric key into array as index,
+/// so the same node always has the same path.
+fn canonical<'a>(root: &'a Value, path: &TomlPath) -> Option<(TomlPath, &'a Value)> {
+    let mut canonical = TomlPath::new();
+    let mut node = root;
+    for item in path.items() {
+        node = match node {
+            Value::Array(array) => {
+                let index = item.as_index()?;
+                canonical.push_index(index);
+                array.get(index)?
+            }
+            _ => {
+                canonical.push(item.clone());
+                TomlPath::from(vec![item.clone()]).apply(node)?
+            }
+        };
+    }
+    Some((canonical, node))
+}
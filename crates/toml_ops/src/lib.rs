@@ -43,13 +43,81 @@
 //! assert_eq!(proto, "default");
 //!
 //! let invalid = v.path() / "host" / "no-key";
-//! assert_eq!(!invalid, true);
 //! assert_eq!(invalid.is_none(), true);
+//! assert_eq!(!invalid, true);
+//! ```
+//!
+//! # Format Preserving
+//! The same operators are also available for `toml_edit::Document` by trait
+//! `EditOperator`, where modification keep the comments and whitespace.
+//! ```rust
+//! use toml_ops::EditOperator;
+//! let tv = r#"
+//! [host]
+//! port = 8080 # the listen port
+//! "#;
+//! let mut doc: toml_edit::Document = tv.parse().unwrap();
+//!
+//! let _ = doc.path_mut() / "host" / "port" << 8989;
+//! assert_eq!(doc.path() / "host" / "port" | 0, 8989);
+//! assert_eq!(doc.to_string(), tv.replace("8080", "8989"));
 //! ```
 //!
 
+mod path;
+pub use path::TomlPath;
+pub use path::PathItem;
+pub use path::PathError;
+
 mod operator;
 pub use operator::PathOperator;
 pub use operator::TomlPtr;
 pub use operator::TomlPtrMut;
+pub use operator::Walk;
+pub use operator::TomlInteger;
+
+mod merge;
+pub use merge::Merge;
+pub use merge::ArrayMerge;
+pub use merge::Layers;
+
+mod env;
+pub use env::EnvOverlay;
+pub use env::EnvError;
+
+mod interp;
+pub use interp::Interpolate;
+pub use interp::InterpError;
+
+mod include;
+pub use include::Loader;
+pub use include::Loaded;
+pub use include::IncludeError;
+
+mod schema;
+pub use schema::Schema;
+pub use schema::SchemaError;
+pub use schema::Violation;
+
+mod patch;
+pub use patch::Patch;
+pub use patch::PatchOp;
+pub use patch::PatchError;
+
+mod typed;
+pub use typed::Typed;
+
+mod diag;
+pub use diag::DiagPtr;
+pub use diag::DiagPtrMut;
+pub use diag::PtrError;
+
+mod edit;
+pub use edit::EditOperator;
+pub use edit::EditPtr;
+pub use edit::EditPtrMut;
+
+mod query;
+pub use query::Query;
+pub use query::QueryIter;
 
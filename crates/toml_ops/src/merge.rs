@@ -0,0 +1,194 @@
+use toml::Value;
+use toml::value::Table;
+use std::collections::BTreeMap;
+
+use crate::path::TomlPath;
+
+/// Strategy to merge array in overlay with array in base.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ArrayMerge {
+    /// Overlay array replace the base one, as RFC 7396 merge-patch.
+    #[default]
+    Replace,
+    /// Append overlay items to the base array.
+    Append,
+    /// Append overlay items that not already in base array.
+    Union,
+    /// Merge tables with the same value of the key, append the others.
+    /// eg: `UnionBy("name".into())` for `[[servers]]` identified by name.
+    UnionBy(String),
+}
+
+/// Deep merge overlay into base, following RFC 7396 merge-patch semantics:
+/// tables are merged recursively, other value in overlay replace the base.
+///
+/// Since toml has no `null`, the deletion is expressed by a marker value
+/// set by `delete_marker()`, the key with such value in overlay is removed
+/// from base. No marker by default.
+#[derive(Debug, Clone, Default)]
+pub struct Merge {
+    array: ArrayMerge,
+    delete: Option<Value>,
+}
+
+impl Merge {
+    /// Default merge, replace array and no delete marker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the strategy to merge array.
+    pub fn array(mut self, strategy: ArrayMerge) -> Self {
+        self.array = strategy;
+        self
+    }
+
+    /// Set the value that mark the key to be deleted, eg: `"~"`.
+    pub fn delete_marker<T>(mut self, marker: T) -> Self where Value: From<T> {
+        self.delete = Some(Value::from(marker));
+        self
+    }
+
+    /// Merge overlay into base in place.
+    pub fn apply(&self, base: &mut Value, overlay: &Value) {
+        self.merge(base, overlay, &mut TomlPath::new(), &mut |_, _| {});
+    }
+
+    fn is_delete(&self, v: &Value) -> bool {
+        self.delete.as_ref() == Some(v)
+    }
+
+    /// Recursive merge, call `record(path, deleted)` on each node that is
+    /// assigned from overlay or deleted.
+    pub(crate) fn merge<F>(&self, base: &mut Value, overlay: &Value, path: &mut TomlPath, record: &mut F)
+    where F: FnMut(&TomlPath, bool)
+    {
+        match (base, overlay) {
+            (base, Value::Table(over)) => {
+                if !base.is_table() {
+                    *base = Value::Table(Table::new());
+                    record(path, true);
+                }
+                let table = base.as_table_mut().unwrap();
+                for (key, val) in over {
+                    path.push_key(key);
+                    if self.is_delete(val) {
+                        if table.remove(key).is_some() {
+                            record(path, true);
+                        }
+                    }
+                    else if val.is_table() || table.contains_key(key) {
+                        let node = table.entry(key).or_insert_with(|| Value::Table(Table::new()));
+                        self.merge(node, val, path, record);
+                    }
+                    else {
+                        table.insert(key.clone(), val.clone());
+                        record(path, false);
+                    }
+                    path.pop();
+                }
+            }
+            (Value::Array(array), Value::Array(over)) if self.array != ArrayMerge::Replace => {
+                for val in over {
+                    let found = match &self.array {
+                        ArrayMerge::Union => array.iter().position(|item| item == val),
+                        ArrayMerge::UnionBy(key) => match val.get(key.as_str()) {
+                            Some(id) => array.iter().position(|item| item.get(key.as_str()) == Some(id)),
+                            None => None,
+                        },
+                        _ => None,
+                    };
+                    match found {
+                        Some(index) if self.array != ArrayMerge::Union => {
+                            path.push_index(index);
+                            self.merge(&mut array[index], val, path, record);
+                            path.pop();
+                        }
+                        Some(_) => {}
+                        None => {
+                            path.push_index(array.len());
+                            array.push(val.clone());
+                            record(path, false);
+                            path.pop();
+                        }
+                    }
+                }
+            }
+            (base, overlay) => {
+                *base = overlay.clone();
+                record(path, false);
+            }
+        }
+    }
+}
+
+/// Layered config, such as defaults, site file, user file, then environment.
+/// Each layer is merged over the previous, and remember the layer name where
+/// each final value came from.
+#[derive(Debug, Clone)]
+pub struct Layers {
+    merge: Merge,
+    value: Value,
+    names: Vec<String>,
+    origins: BTreeMap<TomlPath, usize>,
+}
+
+impl Default for Layers {
+    fn default() -> Self {
+        Self::new(Merge::default())
+    }
+}
+
+impl Layers {
+    /// Start from empty table, merge layers with the options.
+    pub fn new(merge: Merge) -> Self {
+        Self { merge, value: Value::Table(Table::new()), names: Vec::new(), origins: BTreeMap::new() }
+    }
+
+    /// Merge another layer over the previous.
+    pub fn push<S: ToString>(&mut self, name: S, layer: &Value) -> &mut Self {
+        let index = self.names.len();
+        self.names.push(name.to_string());
+        let origins = &mut self.origins;
+        self.merge.merge(&mut self.value, layer, &mut TomlPath::new(), &mut |path, deleted| {
+            let below: Vec<TomlPath> = origins.range(path.clone()..)
+                .take_while(|(p, _)| p.starts_with(path))
+                .map(|(p, _)| p.clone())
+                .collect();
+            for p in below {
+                origins.remove(&p);
+            }
+            if !deleted {
+                origins.insert(path.clone(), index);
+            }
+        });
+        self
+    }
+
+    /// The merged value.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Take the merged value.
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+
+    /// Name of the layer that the node came from, or where it's nearest
+    /// ancestor came from as a whole. `None` for table merged from many layers.
+    pub fn origin(&self, path: &TomlPath) -> Option<&str> {
+        let mut path = path.clone();
+        loop {
+            if let Some(index) = self.origins.get(&path) {
+                return Some(&self.names[*index]);
+            }
+            path.pop()?;
+        }
+    }
+
+    /// All the nodes that assigned as a whole from some layer, with the layer name.
+    pub fn origins(&self) -> impl Iterator<Item = (&TomlPath, &str)> {
+        self.origins.iter().map(|(path, index)| (path, self.names[*index].as_str()))
+    }
+}
@@ -1,79 +1,180 @@
 use toml::Value;
-use toml::value::Index;
-use std::ops::{Div, BitOr, Shl, ShlAssign, Not, Deref, DerefMut};
+use toml::value::{Index, Table, Datetime};
+use std::ops::{Div, BitOr, Shl, ShlAssign, Sub, AddAssign, SubAssign, Not, Deref, DerefMut};
+use std::cmp::Ordering;
 
-/// Path segment break on slash(/) or dot(.).
-/// eg: `table.subtable.key` or `table/subtable/key` or `array/index/key`
-struct PathSegment
-{
-    paths: Vec<String>,
-}
+use crate::path::{TomlPath, PathItem, PathError};
+use crate::diag::{DiagPtr, DiagPtrMut};
+use crate::merge::Merge;
+use crate::query::compare_value;
 
-impl PathSegment
+impl TomlPath
 {
     /// Resolve path readonly for readonly `toml::Value`.
-    fn apply<'tr>(&self, v: &'tr Value) -> Option<&'tr Value> {
-        let mut target = Some(v);
-        for p in &self.paths {
-            if target.is_none() {
-                return None;
-            }
-            if p.is_empty() {
-                continue;
+    /// Key is used as index only when the node is array.
+    pub(crate) fn apply<'tr>(&self, v: &'tr Value) -> Option<&'tr Value> {
+        let mut target = v;
+        for p in self.items() {
+            target = match (p, target) {
+                (PathItem::Key(key), Value::Table(table)) => table.get(key)?,
+                (_, Value::Array(array)) => array.get(p.as_index()?)?,
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+
+    /// Resolve path readonly for mutable `toml::Value`.
+    /// Numeric key of table is no longer mistaken as array index.
+    pub(crate) fn apply_mut<'tr>(&self, v: &'tr mut Value) -> Option<&'tr mut Value> {
+        let mut target = v;
+        for p in self.items() {
+            target = match (p, target) {
+                (PathItem::Key(key), Value::Table(table)) => table.get_mut(key)?,
+                (_, Value::Array(array)) => array.get_mut(p.as_index()?)?,
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+
+    /// Resolve path for mutable `toml::Value`, and create missing node on the way.
+    /// Key step create table, index step create array, and index equal to the
+    /// length append to the array, but existing scalar is never overwritten.
+    /// The missing node is created as empty table placeholder, and flag `fresh`
+    /// tell whether the node is such placeholder, which can turn to array.
+    pub(crate) fn apply_create<'tr>(&self, v: &'tr mut Value, mut fresh: bool) -> Option<(&'tr mut Value, bool)> {
+        if !self.can_create(v, fresh) {
+            return None;
+        }
+        let mut target = v;
+        for p in self.items() {
+            if fresh && matches!(p, PathItem::Index(_)) {
+                *target = Value::Array(Vec::new());
             }
-            match target.unwrap() {
-                Value::Table(table) => { target = table.get(p); },
-                Value::Array(array) => {
-                    if let Ok(index) = p.parse::<usize>() {
-                        target = array.get(index); 
+            let (child, created) = match (p, target) {
+                (PathItem::Key(key), Value::Table(table)) => {
+                    let created = !table.contains_key(key);
+                    (table.entry(key).or_insert_with(|| Value::Table(Table::new())), created)
+                }
+                (_, Value::Array(array)) => {
+                    let index = p.as_index()?;
+                    let created = index == array.len();
+                    if created {
+                        array.push(Value::Table(Table::new()));
                     }
+                    (array.get_mut(index)?, created)
+                }
+                _ => return None,
+            };
+            target = child;
+            fresh = created;
+        }
+        Some((target, fresh))
+    }
+
+    /// Check `apply_create()` would succeed without touching the tree, so that
+    /// a failed walk leave no placeholder behind.
+    fn can_create(&self, v: &Value, fresh: bool) -> bool {
+        // `None` for the node to be created, or the fresh placeholder
+        let mut target = if fresh { None } else { Some(v) };
+        for p in self.items() {
+            target = match (p, target) {
+                (PathItem::Key(key), Some(Value::Table(table))) => table.get(key),
+                (_, Some(Value::Array(array))) => match p.as_index() {
+                    Some(index) if index < array.len() => array.get(index),
+                    Some(index) if index == array.len() => None,
+                    _ => return false,
                 },
-                _ => { return None; }
-            }
+                (PathItem::Key(_), None) | (PathItem::Index(0), None) => None,
+                _ => return false,
+            };
         }
-        return target;
+        true
     }
 
-    /// Resolve path readonly for mutable `toml::Value`.
-    /// Bug: if some table key is all numerical char, would mistake as array index.
-    fn apply_mut<'tr>(&self, v: &'tr mut Value) -> Option<&'tr mut Value> {
-        let mut target = Some(v);
-        for p in &self.paths {
-            if target.is_none() {
-                return None;
+    /// Remove the node from it's parent table or array, return the removed value.
+    /// Later items in array are shifted.
+    pub(crate) fn remove_from(&self, v: &mut Value) -> Option<Value> {
+        let last = self.last()?;
+        match (last, self.parent()?.apply_mut(v)?) {
+            (PathItem::Key(key), Value::Table(table)) => table.remove(key),
+            (_, Value::Array(array)) => {
+                let index = last.as_index()?;
+                if index < array.len() { Some(array.remove(index)) } else { None }
             }
-            if p.is_empty() {
-                continue;
+            _ => None,
+        }
+    }
+
+    /// Insert value to the parent table or array, overwrite existing key in table,
+    /// but insert before the index in array, or append when index equal to length
+    /// or is `-` as in JSON Pointer.
+    /// Give back the value if parent is missing or index is out of range.
+    pub(crate) fn insert_into(&self, v: &mut Value, val: Value) -> Result<(), Value> {
+        let last = match self.last() {
+            Some(last) => last,
+            None => return Err(val),
+        };
+        let parent = match self.parent().and_then(|path| path.apply_mut(v)) {
+            Some(parent) => parent,
+            None => return Err(val),
+        };
+        match (last, parent) {
+            (PathItem::Key(key), Value::Table(table)) => {
+                table.insert(key.clone(), val);
+                Ok(())
             }
-            match p.parse::<usize>() {
-                Ok(index) => { target = target.unwrap().get_mut(index); },
-                Err(_) => { target = target.unwrap().get_mut(p); },
+            (PathItem::Key(key), Value::Array(array)) if key == "-" => {
+                array.push(val);
+                Ok(())
             }
+            (_, Value::Array(array)) => match last.as_index() {
+                Some(index) if index <= array.len() => {
+                    array.insert(index, val);
+                    Ok(())
+                }
+                _ => Err(val),
+            },
+            _ => Err(val),
         }
-        return target;
     }
+
 }
 
-/// Type trait that can build `PathSegment` from.
-trait PathBuilder {
-    fn build_path(&self) -> PathSegment {
-        PathSegment { paths: Vec::new() }
+/// Type trait that can build `TomlPath` from.
+pub(crate) trait PathBuilder {
+    /// Parse to path, report syntax error.
+    fn parse_path(&self) -> Result<TomlPath, PathError>;
+
+    /// Parse to path, `None` if syntax error.
+    fn build_path(&self) -> Option<TomlPath> {
+        self.parse_path().ok()
     }
+
+    /// Take as a single step, when directly index the node.
+    fn path_item(&self) -> PathItem;
 }
 
-/// split string to get path segment vector.
+/// Parse string with path syntax, see `TomlPath`.
 impl PathBuilder for &str {
-    fn build_path(&self) -> PathSegment {
-        let paths = self
-            .split(|c| c == '/' || c == '.')
-            .map(|s| s.to_string())
-            .collect();
-        PathSegment { paths }
+    fn parse_path(&self) -> Result<TomlPath, PathError> {
+        TomlPath::parse(self)
+    }
+    fn path_item(&self) -> PathItem {
+        PathItem::Key(self.to_string())
     }
 }
 
 /// usize index only act path on it's own, but cannot split to more path segment.
-impl PathBuilder for usize {}
+impl PathBuilder for usize {
+    fn parse_path(&self) -> Result<TomlPath, PathError> {
+        Ok(TomlPath::from(vec![self.path_item()]))
+    }
+    fn path_item(&self) -> PathItem {
+        PathItem::Index(*self)
+    }
+}
 
 /// Provide toml pointer to supported operator overload.
 pub trait PathOperator
@@ -89,6 +190,22 @@ pub trait PathOperator
 
     /// Construct mutable toml pointer and move it follwoing sub path.
     fn pathto_mut<'tr>(&'tr mut self, p: &str) -> TomlPtrMut<'tr>;
+
+    /// Construct immutable toml pointer by RFC 6901 JSON Pointer, eg: `/a~1b/0`.
+    fn pointer<'tr>(&'tr self, p: &str) -> TomlPtr<'tr>;
+
+    /// Construct mutable toml pointer by RFC 6901 JSON Pointer.
+    fn pointer_mut<'tr>(&'tr mut self, p: &str) -> TomlPtrMut<'tr>;
+
+    /// Construct mutable toml pointer that create missing node when move
+    /// following path, eg: `v.path_create() / "a" / "b" << ("c", 1)`.
+    fn path_create<'tr>(&'tr mut self) -> TomlPtrMut<'tr>;
+
+    /// Construct immutable diagnostic pointer, which record why path failed.
+    fn path_diag<'tr>(&'tr self) -> DiagPtr<'tr>;
+
+    /// Construct mutable diagnostic pointer, which record why path or put failed.
+    fn path_diag_mut<'tr>(&'tr mut self) -> DiagPtrMut<'tr>;
 }
 
 /// Create toml pointer directely from `toml::Value`.
@@ -98,30 +215,128 @@ impl PathOperator for Value
         TomlPtr::from(self)
     }
     fn pathto<'tr>(&'tr self, p: &str) -> TomlPtr<'tr> {
-        let valop = p.build_path().apply(self);
-        TomlPtr { valop }
+        let ptr = TomlPtr::from(self);
+        match p.build_path() {
+            Some(path) => ptr.path_to(&path),
+            None => ptr.moveto(None, TomlPath::new()),
+        }
     }
 
     fn path_mut<'tr>(&'tr mut self) -> TomlPtrMut<'tr> {
         TomlPtrMut::from(self)
     }
     fn pathto_mut<'tr>(&'tr mut self, p: &str) -> TomlPtrMut<'tr> {
-        let valop = p.build_path().apply_mut(self);
-        TomlPtrMut { valop }
+        let valop = p.build_path().and_then(|path| path.apply_mut(self));
+        TomlPtrMut::new(valop)
+    }
+
+    fn pointer<'tr>(&'tr self, p: &str) -> TomlPtr<'tr> {
+        let ptr = TomlPtr::from(self);
+        match TomlPath::from_pointer(p) {
+            Ok(path) => ptr.path_to(&path),
+            Err(_) => ptr.moveto(None, TomlPath::new()),
+        }
+    }
+    fn pointer_mut<'tr>(&'tr mut self, p: &str) -> TomlPtrMut<'tr> {
+        let valop = TomlPath::from_pointer(p).ok().and_then(|path| path.apply_mut(self));
+        TomlPtrMut::new(valop)
+    }
+
+    fn path_create<'tr>(&'tr mut self) -> TomlPtrMut<'tr> {
+        TomlPtrMut::creating(self)
+    }
+
+    fn path_diag<'tr>(&'tr self) -> DiagPtr<'tr> {
+        DiagPtr::from(self)
+    }
+    fn path_diag_mut<'tr>(&'tr mut self) -> DiagPtrMut<'tr> {
+        DiagPtrMut::from(self)
     }
 }
 
 /// Wrapper pointer to `toml::Value` for operator overload.
 /// Must refer to an existed toml tree, `Option::None` to refer non-exist node.
-#[derive(Copy, Clone)]
+/// Also remember the root node where the pointer start from, and the path
+/// it took from there, so it is not `Copy`, and `clone()` copy the path.
+/// The `/` operators move the path along, so chained steps don't copy it.
+#[derive(Clone)]
 pub struct TomlPtr<'tr> {
     valop: Option<&'tr Value>,
+    root: Option<&'tr Value>,
+    trail: TomlPath,
 }
 
 impl<'tr> TomlPtr<'tr> {
     /// Common constructor
     pub fn new(valop: Option<&'tr Value>) -> Self {
-        Self { valop }
+        Self { valop, root: valop, trail: TomlPath::new() }
+    }
+
+    /// Move to another node in the same tree, at the path from root.
+    fn moveto(&self, valop: Option<&'tr Value>, trail: TomlPath) -> Self {
+        Self { valop, root: self.root, trail }
+    }
+
+    /// The path from root node where the pointer start from to current node,
+    /// `None` if the pointer is invalid.
+    pub fn location(&self) -> Option<TomlPath> {
+        self.valop.map(|_| self.trail.clone())
+    }
+
+    /// Render `location()` as RFC 6901 JSON Pointer, eg: `/host/protocol/0`.
+    pub fn to_pointer(&self) -> Option<String> {
+        self.location().map(|path| path.to_pointer())
+    }
+
+    /// Alias of `location()`, the path from root to current node.
+    pub fn path(&self) -> Option<TomlPath> {
+        self.location()
+    }
+
+    /// Pointer to the parent node in the same tree, invalid if current node
+    /// is the root or invalid itself.
+    pub fn parent(&self) -> Self {
+        let path = match self.location().and_then(|path| path.parent()) {
+            Some(path) => path,
+            None => return self.moveto(None, TomlPath::new()),
+        };
+        let parent = self.root.and_then(|root| path.apply(root));
+        self.moveto(parent, path)
+    }
+
+    /// Keys of table or indexes of array, empty for scalar or invalid pointer.
+    pub fn keys(&self) -> impl Iterator<Item = PathItem> + 'tr {
+        let keys: Vec<PathItem> = match self.valop {
+            Some(Value::Table(table)) => table.keys().map(|key| PathItem::Key(key.clone())).collect(),
+            Some(Value::Array(array)) => (0..array.len()).map(PathItem::Index).collect(),
+            _ => Vec::new(),
+        };
+        keys.into_iter()
+    }
+
+    /// Pointers to the direct children of table or array, in `keys()` order.
+    pub fn children(&self) -> impl Iterator<Item = TomlPtr<'tr>> + 'tr {
+        let ptr = self.clone();
+        let children: Vec<(PathItem, &'tr Value)> = match self.valop {
+            Some(Value::Table(table)) => table.iter().map(|(key, v)| (PathItem::Key(key.clone()), v)).collect(),
+            Some(Value::Array(array)) => array.iter().enumerate().map(|(index, v)| (PathItem::Index(index), v)).collect(),
+            _ => Vec::new(),
+        };
+        children.into_iter().map(move |(item, v)| {
+            let mut trail = ptr.trail.clone();
+            trail.push(item);
+            ptr.moveto(Some(v), trail)
+        })
+    }
+
+    /// Depth-first walk all the descendant nodes in pre-order, not include
+    /// current node itself. Each node is paired with its path from root.
+    pub fn walk(&self) -> Walk<'tr> {
+        let mut stack = Vec::new();
+        if let Some(v) = self.valop {
+            push_children(&mut stack, &self.trail, v);
+        }
+        Walk { ptr: self.clone(), stack }
     }
 
     /// As constructor, to build path operand object from a `toml::Value` node.
@@ -135,21 +350,86 @@ impl<'tr> TomlPtr<'tr> {
     }
 
     /// Resolve path to sub node.
-    fn path<B>(&self, p: B) -> Self where B: PathBuilder + Index + Copy {
+    fn sub_path<B>(mut self, p: B) -> Self where B: PathBuilder + Index + Copy {
         if self.is_none() {
             return Self::none();
         }
 
-        let v = self.unwrap();
-        let mut target = v.get(p);
-        if target.is_none() {
-            let path_segment = p.build_path();
-            if path_segment.paths.len() > 1 {
-                target = path_segment.apply(v);
+        let item = p.path_item();
+        if let Some((target, item)) = step(self.valop, &item) {
+            self.trail.push(item);
+            self.valop = Some(target);
+            return self;
+        }
+        match p.build_path() {
+            Some(path) => self.path_to(&path),
+            None => Self { valop: None, ..self },
+        }
+    }
+
+    /// Resolve parsed path to sub node, and record the steps it took,
+    /// key that index array is recorded as index.
+    fn path_to(mut self, path: &TomlPath) -> Self {
+        for p in path.items() {
+            match step(self.valop, p) {
+                Some((target, item)) => {
+                    self.trail.push(item);
+                    self.valop = Some(target);
+                }
+                None => return Self { valop: None, ..self },
             }
         }
+        self
+    }
+}
 
-        return Self::new(target);
+/// One step from node to its child, with the path item to record.
+fn step<'tr>(v: Option<&'tr Value>, p: &PathItem) -> Option<(&'tr Value, PathItem)> {
+    match (p, v?) {
+        (PathItem::Key(key), Value::Table(table)) => Some((table.get(key)?, p.clone())),
+        (_, Value::Array(array)) => {
+            let index = p.as_index()?;
+            Some((array.get(index)?, PathItem::Index(index)))
+        }
+        _ => None,
+    }
+}
+
+/// Iterator of `TomlPtr::walk()`, yield `(path, pointer)` pairs.
+pub struct Walk<'tr> {
+    ptr: TomlPtr<'tr>,
+    stack: Vec<(TomlPath, &'tr Value)>,
+}
+
+impl<'tr> Iterator for Walk<'tr> {
+    type Item = (TomlPath, TomlPtr<'tr>);
+    fn next(&mut self) -> Option<Self::Item> {
+        let (path, v) = self.stack.pop()?;
+        push_children(&mut self.stack, &path, v);
+        let ptr = self.ptr.moveto(Some(v), path.clone());
+        Some((path, ptr))
+    }
+}
+
+/// Push children of `v` to stack in reverse order, so pop in document order.
+fn push_children<'tr>(stack: &mut Vec<(TomlPath, &'tr Value)>, path: &TomlPath, v: &'tr Value) {
+    let mut child = |item: PathItem, v: &'tr Value| {
+        let mut path = path.clone();
+        path.push(item);
+        stack.push((path, v));
+    };
+    match v {
+        Value::Table(table) => {
+            for (key, v) in table.iter().rev() {
+                child(PathItem::Key(key.clone()), v);
+            }
+        }
+        Value::Array(array) => {
+            for (index, v) in array.iter().enumerate().rev() {
+                child(PathItem::Index(index), v);
+            }
+        }
+        _ => {}
     }
 }
 
@@ -177,7 +457,17 @@ where Rhs: PathBuilder + Index + Copy
 {
     type Output = Self;
     fn div(self, rhs: Rhs) -> Self::Output {
-        self.path(rhs)
+        self.sub_path(rhs)
+    }
+}
+
+/// Path operator `/` with parsed path, which can report syntax error before.
+/// eg: `tomlptr / &TomlPath::parse("servers[0].port")?`.
+impl<'tr> Div<&TomlPath> for TomlPtr<'tr>
+{
+    type Output = Self;
+    fn div(self, rhs: &TomlPath) -> Self::Output {
+        self.path_to(rhs)
     }
 }
 
@@ -246,17 +536,185 @@ impl<'tr> BitOr<bool> for TomlPtr<'tr>
     }
 }
 
+/// Pipe operator to get datetime value or `rhs` as default.
+impl<'tr> BitOr<Datetime> for TomlPtr<'tr>
+{
+    type Output = Datetime;
+    fn bitor(self, rhs: Datetime) -> Self::Output {
+        match self.valop {
+            Some(v) => v.as_datetime().copied().unwrap_or(rhs),
+            None => rhs,
+        }
+    }
+}
+
+/// Pipe operator to get a copy of table or `rhs` as default.
+impl<'tr> BitOr<Table> for TomlPtr<'tr>
+{
+    type Output = Table;
+    fn bitor(self, rhs: Table) -> Self::Output {
+        match self.valop {
+            Some(v) => v.as_table().cloned().unwrap_or(rhs),
+            None => rhs,
+        }
+    }
+}
+
+/// Pipe operator to get a copy of array or `rhs` as default.
+impl<'tr> BitOr<Vec<Value>> for TomlPtr<'tr>
+{
+    type Output = Vec<Value>;
+    fn bitor(self, rhs: Vec<Value>) -> Self::Output {
+        match self.valop {
+            Some(v) => v.as_array().cloned().unwrap_or(rhs),
+            None => rhs,
+        }
+    }
+}
+
+/// Operator `==` to compare the node with primitive value of the same type,
+/// invalid pointer or type mismatch is never equal.
+/// eg: `if v.path() / "host" / "port" == 8080 {...}`
+impl<'tr> PartialEq<i64> for TomlPtr<'tr> {
+    fn eq(&self, other: &i64) -> bool {
+        self.valop.and_then(Value::as_integer) == Some(*other)
+    }
+}
+
+impl<'tr> PartialEq<f64> for TomlPtr<'tr> {
+    fn eq(&self, other: &f64) -> bool {
+        self.valop.and_then(Value::as_float) == Some(*other)
+    }
+}
+
+impl<'tr> PartialEq<bool> for TomlPtr<'tr> {
+    fn eq(&self, other: &bool) -> bool {
+        self.valop.and_then(Value::as_bool) == Some(*other)
+    }
+}
+
+impl<'tr> PartialEq<&str> for TomlPtr<'tr> {
+    fn eq(&self, other: &&str) -> bool {
+        self.valop.and_then(Value::as_str) == Some(*other)
+    }
+}
+
+impl<'tr> PartialEq<String> for TomlPtr<'tr> {
+    fn eq(&self, other: &String) -> bool {
+        self.valop.and_then(Value::as_str) == Some(other.as_str())
+    }
+}
+
+/// Operator `==` between pointers compare the nodes deeply, integer and float
+/// by number, consistent with `partial_cmp()`.
+impl<'tr, 'a> PartialEq<TomlPtr<'a>> for TomlPtr<'tr> {
+    fn eq(&self, other: &TomlPtr<'a>) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+/// Operator `<` `>` to compare the node with primitive value of the same type,
+/// always false if invalid pointer or type mismatch.
+impl<'tr> PartialOrd<i64> for TomlPtr<'tr> {
+    fn partial_cmp(&self, other: &i64) -> Option<Ordering> {
+        self.valop?.as_integer()?.partial_cmp(other)
+    }
+}
+
+impl<'tr> PartialOrd<f64> for TomlPtr<'tr> {
+    fn partial_cmp(&self, other: &f64) -> Option<Ordering> {
+        self.valop?.as_float()?.partial_cmp(other)
+    }
+}
+
+impl<'tr> PartialOrd<&str> for TomlPtr<'tr> {
+    fn partial_cmp(&self, other: &&str) -> Option<Ordering> {
+        self.valop?.as_str()?.partial_cmp(*other)
+    }
+}
+
+impl<'tr> PartialOrd<String> for TomlPtr<'tr> {
+    fn partial_cmp(&self, other: &String) -> Option<Ordering> {
+        self.valop?.as_str()?.partial_cmp(other.as_str())
+    }
+}
+
+/// Operator `<` `>` between pointers to scalar nodes, the same as comparison
+/// in `Query` filter. Equal tables or arrays are only `Equal`, never ordered.
+impl<'tr, 'a> PartialOrd<TomlPtr<'a>> for TomlPtr<'tr> {
+    fn partial_cmp(&self, other: &TomlPtr<'a>) -> Option<Ordering> {
+        compare_value(self.valop?, other.valop?)
+    }
+}
+
+/// Integer types that can be put into integer node by `<<`, as `i64`.
+/// Note that integer literal is then inferred as `i32` by default,
+/// add suffix such as `5_000_000_000i64` for larger one.
+pub trait TomlInteger: Copy {
+    fn to_i64(self) -> i64;
+}
+
+impl TomlInteger for i64 {
+    fn to_i64(self) -> i64 { self }
+}
+
+impl TomlInteger for i32 {
+    fn to_i64(self) -> i64 { i64::from(self) }
+}
+
+impl TomlInteger for i16 {
+    fn to_i64(self) -> i64 { i64::from(self) }
+}
+
+impl TomlInteger for i8 {
+    fn to_i64(self) -> i64 { i64::from(self) }
+}
+
+impl TomlInteger for u32 {
+    fn to_i64(self) -> i64 { i64::from(self) }
+}
+
+impl TomlInteger for u16 {
+    fn to_i64(self) -> i64 { i64::from(self) }
+}
+
+impl TomlInteger for u8 {
+    fn to_i64(self) -> i64 { i64::from(self) }
+}
+
 /// Mutable version of pointer wrapper of `toml::Value` for operator overload.
 /// Must refer to existed toml tree, `Option::None` to refer non-exist node.
 /// Note that mutable reference don't support copy.
+/// In creating mode, missing node is created when move following path.
 pub struct TomlPtrMut<'tr> {
     valop: Option<&'tr mut Value>,
+    create: bool,
+    fresh: bool,
 }
 
 impl<'tr> TomlPtrMut<'tr> {
     /// Common constructor
     pub fn new(valop: Option<&'tr mut Value>) -> Self {
-        Self { valop }
+        Self { valop, create: false, fresh: false }
+    }
+
+    /// Constructor in creating mode, see `PathOperator::path_create()`.
+    pub fn creating(v: &'tr mut Value) -> Self {
+        Self { valop: Some(v), create: true, fresh: false }
+    }
+
+    /// Move to another node, keep in creating mode.
+    fn moveto(&self, valop: Option<&'tr mut Value>) -> Self {
+        Self { valop, create: self.create, fresh: false }
+    }
+
+    /// Move following path and create missing node.
+    fn create_to(&mut self, path: &TomlPath) -> Self {
+        let fresh = self.fresh;
+        match self.take().and_then(|v| path.apply_create(v, fresh)) {
+            Some((v, fresh)) => Self { valop: Some(v), create: true, fresh },
+            None => Self::none(),
+        }
     }
 
     /// As constructor, to build path operand object from a `toml::Value` node.
@@ -283,6 +741,13 @@ impl<'tr> TomlPtrMut<'tr> {
             return Self::none();
         }
 
+        if self.create && self.as_ref().and_then(|v| v.get(p)).is_none() {
+            return match p.build_path() {
+                Some(path) => self.create_to(&path),
+                None => Self::none(),
+            };
+        }
+
         let v = self.take().unwrap();
 
         // Note: use immutable version of get() to determiner path is valid first,
@@ -291,14 +756,89 @@ impl<'tr> TomlPtrMut<'tr> {
         if let Some(_) = v.get(p) {
             target = v.get_mut(p);
         }
-        else {
-            let path_segment = p.build_path();
-            if path_segment.paths.len() > 1 {
-                target = path_segment.apply_mut(v);
+        else if let Some(path) = p.build_path() {
+            target = path.apply_mut(v);
+        }
+
+        return self.moveto(target);
+    }
+
+    /// Resolve parsed path to sub node.
+    fn path_to(&mut self, path: &TomlPath) -> Self {
+        if self.create {
+            return self.create_to(path);
+        }
+        Self::new(self.take().and_then(|v| path.apply_mut(v)))
+    }
+
+    /// Resolve sub path for remove, literal key is tried first as `/` does.
+    fn sub_path<B>(v: &Value, p: B) -> Option<TomlPath> where B: PathBuilder + Index + Copy {
+        if v.get(p).is_some() {
+            return Some(TomlPath::from(vec![p.path_item()]));
+        }
+        p.build_path()
+    }
+
+    /// Remove the node at sub path from it's parent, return the removed value.
+    /// The same as `-` operator but not consume the pointer.
+    pub fn remove(&mut self, path: &str) -> Option<Value> {
+        let v = self.valop.as_deref_mut()?;
+        Self::sub_path(v, path)?.remove_from(v)
+    }
+
+    /// Rename key of the table in place, fail if `to` key already existed.
+    pub fn rename(&mut self, from: &str, to: &str) -> bool {
+        match self.valop.as_deref_mut() {
+            Some(Value::Table(table)) if from == to => table.contains_key(from),
+            Some(Value::Table(table)) if !table.contains_key(to) => {
+                match table.remove(from) {
+                    Some(val) => { table.insert(to.to_string(), val); true }
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Move sub tree from one sub path to another, both relative to the pointer.
+    /// The parent of `to` must exist, and array index follow the rule of insert.
+    /// Nothing changed when fail.
+    pub fn move_path(&mut self, from: &str, to: &str) -> bool {
+        let (from, to) = match (TomlPath::parse(from), TomlPath::parse(to)) {
+            (Ok(from), Ok(to)) => (from, to),
+            _ => return false,
+        };
+        let v = match self.valop.as_deref_mut() {
+            Some(v) => v,
+            None => return false,
+        };
+        let val = match from.remove_from(v) {
+            Some(val) => val,
+            None => return false,
+        };
+        match to.insert_into(v, val) {
+            Ok(_) => true,
+            Err(val) => {
+                let _ = from.insert_into(v, val);
+                false
             }
         }
+    }
 
-        return Self::new(target);
+    /// Copy sub tree from one sub path to another, both relative to the pointer.
+    pub fn copy_path(&mut self, from: &str, to: &str) -> bool {
+        let (from, to) = match (TomlPath::parse(from), TomlPath::parse(to)) {
+            (Ok(from), Ok(to)) => (from, to),
+            _ => return false,
+        };
+        let v = match self.valop.as_deref_mut() {
+            Some(v) => v,
+            None => return false,
+        };
+        match from.apply(v) {
+            Some(val) => to.insert_into(v, val.clone()).is_ok(),
+            None => false,
+        }
     }
 
     /// Assign any supported value to toml.
@@ -316,18 +856,37 @@ impl<'tr> TomlPtrMut<'tr> {
     }
 
     /// Put value to string toml node pointer, would invalidate it when type mismatch.
+    /// But the created placeholder node can be put any type.
     /// Implement for << String and << &str.
     fn put_string(&mut self, rhs: String) -> Self {
         match self.take() {
-            Some(v) if v.is_str() => Self::put_val(v, rhs),
+            Some(v) if v.is_str() || self.fresh => Self::put_val(v, rhs),
             _ => Self::none()
         }
     }
 
+    /// Update node by `op` for arithmetic assign, which return `None` when
+    /// type mismatch or overflow, then invalidate the pointer.
+    /// The created placeholder node is set to `init`.
+    fn update<F>(&mut self, init: Option<Value>, op: F) where F: FnOnce(&Value) -> Option<Value> {
+        let val = match self.valop.as_deref() {
+            Some(_) if self.fresh => init,
+            Some(v) => op(v),
+            None => None,
+        };
+        match (val, self.valop.as_deref_mut()) {
+            (Some(val), Some(v)) => {
+                *v = val;
+                self.fresh = false;
+            }
+            _ => self.valop = None,
+        }
+    }
+
     /// Implement for << i64.
     fn put_integer(&mut self, rhs: i64) -> Self {
         match self.take() {
-            Some(v) if v.is_integer() => Self::put_val(v, rhs),
+            Some(v) if v.is_integer() || self.fresh => Self::put_val(v, rhs),
             _ => Self::none()
         }
     }
@@ -335,7 +894,7 @@ impl<'tr> TomlPtrMut<'tr> {
     /// Implement for << f64.
     fn put_float(&mut self, rhs: f64) -> Self {
         match self.take() {
-            Some(v) if v.is_float() => Self::put_val(v, rhs),
+            Some(v) if v.is_float() || self.fresh => Self::put_val(v, rhs),
             _ => Self::none()
         }
     }
@@ -343,7 +902,15 @@ impl<'tr> TomlPtrMut<'tr> {
     /// Implement for << bool.
     fn put_bool(&mut self, rhs: bool) -> Self {
         match self.take() {
-            Some(v) if v.is_bool() => Self::put_val(v, rhs),
+            Some(v) if v.is_bool() || self.fresh => Self::put_val(v, rhs),
+            _ => Self::none()
+        }
+    }
+
+    /// Implement for << Datetime.
+    fn put_datetime(&mut self, rhs: Datetime) -> Self {
+        match self.take() {
+            Some(v) if v.is_datetime() || self.fresh => Self::put_val(v, rhs),
             _ => Self::none()
         }
     }
@@ -360,7 +927,13 @@ impl<'tr> TomlPtrMut<'tr> {
     }
 
     /// Implment for array << (val, ) << [item] .
+    /// The created placeholder node is turned to array.
     fn push_array<T>(&mut self, val: T) -> Self where Value: From<T> {
+        if self.fresh {
+            if let Some(ref mut v) = self.valop {
+                **v = Value::Array(Vec::new());
+            }
+        }
         match self.take() {
             Some(v) if v.is_array() => {
                 v.as_array_mut().unwrap().push(Value::from(val));
@@ -408,6 +981,15 @@ where Rhs: PathBuilder + Index + Copy
     }
 }
 
+/// Path operator `/` with parsed path.
+impl<'tr> Div<&TomlPath> for TomlPtrMut<'tr>
+{
+    type Output = Self;
+    fn div(mut self, rhs: &TomlPath) -> Self::Output {
+        self.path_to(rhs)
+    }
+}
+
 /// Pipe operator `|` with `String`, to get value from string node, 
 /// or return `rhs` as default value if pointer is invalid or type mistach.
 /// Note that the `rhs` string , as well as the pointer itself would be moved.
@@ -451,6 +1033,30 @@ impl<'tr> BitOr<bool> for TomlPtrMut<'tr> {
     }
 }
 
+/// Pipe operator to get datetime value or `rhs` as default.
+impl<'tr> BitOr<Datetime> for TomlPtrMut<'tr> {
+    type Output = Datetime;
+    fn bitor(mut self, rhs: Datetime) -> Self::Output {
+        self.immut().bitor(rhs)
+    }
+}
+
+/// Pipe operator to get a copy of table or `rhs` as default.
+impl<'tr> BitOr<Table> for TomlPtrMut<'tr> {
+    type Output = Table;
+    fn bitor(mut self, rhs: Table) -> Self::Output {
+        self.immut().bitor(rhs)
+    }
+}
+
+/// Pipe operator to get a copy of array or `rhs` as default.
+impl<'tr> BitOr<Vec<Value>> for TomlPtrMut<'tr> {
+    type Output = Vec<Value>;
+    fn bitor(mut self, rhs: Vec<Value>) -> Self::Output {
+        self.immut().bitor(rhs)
+    }
+}
+
 /// Operator `<<` to put a string into toml leaf node.
 /// While the data type mismatch the node, set self pointer to `None`.
 impl<'tr> Shl<&str> for TomlPtrMut<'tr> {
@@ -469,12 +1075,12 @@ impl<'tr> Shl<String> for TomlPtrMut<'tr> {
     }
 }
 
-/// Operator `<<` to put a integer value into toml leaf node.
+/// Operator `<<` to put a integer value into toml leaf node, any `TomlInteger`.
 /// While the data type mismatch the node, set self pointer to `None`.
-impl<'tr> Shl<i64> for TomlPtrMut<'tr> {
+impl<'tr, T: TomlInteger> Shl<T> for TomlPtrMut<'tr> {
     type Output = Self;
-    fn shl(mut self, rhs: i64) -> Self::Output {
-        self.put_integer(rhs)
+    fn shl(mut self, rhs: T) -> Self::Output {
+        self.put_integer(rhs.to_i64())
     }
 }
 
@@ -496,6 +1102,54 @@ impl<'tr> Shl<bool> for TomlPtrMut<'tr> {
     }
 }
 
+/// Operator `<<` to put a datetime value into toml leaf node.
+impl<'tr> Shl<Datetime> for TomlPtrMut<'tr> {
+    type Output = Self;
+    fn shl(mut self, rhs: Datetime) -> Self::Output {
+        self.put_datetime(rhs)
+    }
+}
+
+/// Operator `<<` to push all entries of table into toml table.
+/// The created placeholder node is turned to table.
+impl<'tr> Shl<Table> for TomlPtrMut<'tr> {
+    type Output = Self;
+    fn shl(mut self, rhs: Table) -> Self::Output {
+        if self.fresh {
+            if let Some(ref mut v) = self.valop {
+                **v = Value::Table(Table::new());
+            }
+        }
+        for (key, val) in rhs {
+            self = self.push_table(key, val);
+        }
+        match self.take() {
+            Some(v) if v.is_table() => Self::from(v),
+            _ => Self::none()
+        }
+    }
+}
+
+/// Operator `<<` to push all items of vector into toml array.
+/// The created placeholder node is turned to array.
+impl<'tr> Shl<Vec<Value>> for TomlPtrMut<'tr> {
+    type Output = Self;
+    fn shl(mut self, rhs: Vec<Value>) -> Self::Output {
+        if self.fresh {
+            if let Some(ref mut v) = self.valop {
+                **v = Value::Array(Vec::new());
+            }
+        }
+        for item in rhs {
+            self = self.push_array(item);
+        }
+        match self.take() {
+            Some(v) if v.is_array() => Self::from(v),
+            _ => Self::none()
+        }
+    }
+}
+
 /// Operator `<<` to push key-value pair (tuple) into toml table.
 /// eg: `toml/table/node << (k, v)` where the k v will be moved.
 impl<'tr, K: ToString, T> Shl<(K, T)> for TomlPtrMut<'tr> where Value: From<T> {
@@ -536,6 +1190,71 @@ impl<'tr, T: Copy> Shl<&[T]> for TomlPtrMut<'tr> where Value: From<T> {
     }
 }
 
+/// Operator `-` to remove sub node from table or array, return the removed value.
+/// eg: `let old = toml/table - "key";` or `let old = toml/array - 0;`
+impl<'tr, Rhs> Sub<Rhs> for TomlPtrMut<'tr>
+where Rhs: PathBuilder + Index + Copy
+{
+    type Output = Option<Value>;
+    fn sub(mut self, rhs: Rhs) -> Self::Output {
+        let v = self.valop.as_deref_mut()?;
+        Self::sub_path(v, rhs)?.remove_from(v)
+    }
+}
+
+/// Operator `+=` to deep merge another toml into the node, see `Merge`.
+/// eg: `base_ptr += &overlay;`
+impl<'tr> AddAssign<&Value> for TomlPtrMut<'tr> {
+    fn add_assign(&mut self, rhs: &Value) {
+        if let Some(ref mut v) = self.valop {
+            Merge::default().apply(v, rhs);
+        }
+    }
+}
+
+/// Operator `+=` to add integer node in place, invalidate the pointer if
+/// type mismatch or overflow. eg: `count_ptr += 1;`
+impl<'tr> AddAssign<i64> for TomlPtrMut<'tr> {
+    fn add_assign(&mut self, rhs: i64) {
+        self.update(Some(Value::from(rhs)), |v| v.as_integer()?.checked_add(rhs).map(Value::from));
+    }
+}
+
+/// Operator `+=` to add float node in place.
+impl<'tr> AddAssign<f64> for TomlPtrMut<'tr> {
+    fn add_assign(&mut self, rhs: f64) {
+        self.update(Some(Value::from(rhs)), |v| v.as_float().map(|f| Value::from(f + rhs)));
+    }
+}
+
+/// Operator `+=` to append to string node.
+impl<'tr> AddAssign<&str> for TomlPtrMut<'tr> {
+    fn add_assign(&mut self, rhs: &str) {
+        self.update(Some(Value::from(rhs)), |v| v.as_str().map(|s| Value::from(format!("{s}{rhs}"))));
+    }
+}
+
+impl<'tr> AddAssign<String> for TomlPtrMut<'tr> {
+    fn add_assign(&mut self, rhs: String) {
+        *self += rhs.as_str();
+    }
+}
+
+/// Operator `-=` to subtract integer node in place.
+impl<'tr> SubAssign<i64> for TomlPtrMut<'tr> {
+    fn sub_assign(&mut self, rhs: i64) {
+        let init = rhs.checked_neg().map(Value::from);
+        self.update(init, |v| v.as_integer()?.checked_sub(rhs).map(Value::from));
+    }
+}
+
+/// Operator `-=` to subtract float node in place.
+impl<'tr> SubAssign<f64> for TomlPtrMut<'tr> {
+    fn sub_assign(&mut self, rhs: f64) {
+        self.update(Some(Value::from(-rhs)), |v| v.as_float().map(|f| Value::from(f - rhs)));
+    }
+}
+
 /// Operator `<<=` re-assign to an node unconditionally, may change it data type.
 /// Note donot use chained `<<=` as `<<` can because `<<=` is right associated.
 impl<'tr, T> ShlAssign<T> for TomlPtrMut<'tr> where Value: From<T> {
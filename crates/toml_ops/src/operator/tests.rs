@@ -1,38 +1,50 @@
 // test private items
 use super::*;
 
+fn keys(path: &TomlPath) -> Vec<&str> {
+    path.items().iter().map(|p| match p {
+        PathItem::Key(key) => key.as_str(),
+        PathItem::Index(_) => "[]",
+    }).collect()
+}
+
 #[test]
 fn path_build_test() {
-    let pseg = "".build_path();
-    dbg!(&pseg.paths);
-    assert_eq!(pseg.paths.is_empty(), false);
-    assert_eq!(pseg.paths, vec![""]);
+    let pseg = "".build_path().unwrap();
+    dbg!(&pseg);
+    assert_eq!(pseg.is_empty(), true);
+
+    let pseg = "/".build_path().unwrap();
+    assert_eq!(pseg.is_empty(), true);
+
+    let pseg = "//".build_path().unwrap();
+    assert_eq!(pseg.is_empty(), true);
+
+    let pseg = "/path/to/leaf".build_path().unwrap();
+    assert_eq!(keys(&pseg), vec!["path", "to", "leaf"]);
 
-    let pseg = "/".build_path();
-    dbg!(&pseg.paths);
-    assert_eq!(pseg.paths.is_empty(), false);
-    assert_eq!(pseg.paths, vec!["", ""]);
+    let pseg = "path/to/leaf".build_path().unwrap();
+    assert_eq!(keys(&pseg), vec!["path", "to", "leaf"]);
 
-    let pseg = "//".build_path();
-    assert_eq!(pseg.paths, vec!["", "", ""]);
+    let pseg = "path/to//leaf".build_path().unwrap();
+    assert_eq!(keys(&pseg), vec!["path", "to", "leaf"]);
 
-    let pseg = "/path/to/leaf".build_path();
-    assert_eq!(pseg.paths, vec!["", "path", "to", "leaf"]);
+    let pseg = "path.to.leaf".build_path().unwrap();
+    assert_eq!(keys(&pseg), vec!["path", "to", "leaf"]);
 
-    let pseg = "path/to/leaf".build_path();
-    assert_eq!(pseg.paths, vec!["path", "to", "leaf"]);
+    let pseg = "path/to.leaf".build_path().unwrap();
+    assert_eq!(keys(&pseg), vec!["path", "to", "leaf"]);
 
-    let pseg = "path/to//leaf".build_path();
-    assert_eq!(pseg.paths, vec!["path", "to", "", "leaf"]);
+    let pseg = "path/to.leaf/".build_path().unwrap();
+    assert_eq!(keys(&pseg), vec!["path", "to", "leaf"]);
 
-    let pseg = "path.to.leaf".build_path();
-    assert_eq!(pseg.paths, vec!["path", "to", "leaf"]);
+    let pseg = "path[0].leaf".build_path().unwrap();
+    assert_eq!(keys(&pseg), vec!["path", "[]", "leaf"]);
 
-    let pseg = "path/to.leaf".build_path();
-    assert_eq!(pseg.paths, vec!["path", "to", "leaf"]);
+    let pseg = 3.build_path().unwrap();
+    assert_eq!(pseg.items(), &[PathItem::Index(3)]);
 
-    let pseg = "path/to.leaf/".build_path();
-    assert_eq!(pseg.paths, vec!["path", "to", "leaf", ""]);
+    assert_eq!("path[x]".build_path().is_none(), true);
 
     let path = "34ab";
     let index = path.parse::<usize>();
@@ -44,3 +56,24 @@ fn path_build_test() {
     assert_eq!(index, Ok(34));
 }
 
+#[test]
+fn path_apply_numeric_key_test() {
+    let mut v: Value = r#"
+    list = ["a", "b"]
+    [table]
+    0 = "zero"
+    "#.parse().unwrap();
+
+    let path = "table/0".build_path().unwrap();
+    assert_eq!(path.apply(&v).and_then(Value::as_str), Some("zero"));
+    assert_eq!(path.apply_mut(&mut v).is_some(), true);
+
+    let path = "table[0]".build_path().unwrap();
+    assert_eq!(path.apply(&v).is_none(), true);
+    assert_eq!(path.apply_mut(&mut v).is_none(), true);
+
+    let path = "list/1".build_path().unwrap();
+    assert_eq!(path.apply(&v).and_then(Value::as_str), Some("b"));
+    let path = "list[1]".build_path().unwrap();
+    assert_eq!(path.apply_mut(&mut v).map(|v| v.is_str()), Some(true));
+}
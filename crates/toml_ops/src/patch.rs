@@ -0,0 +1,241 @@
+use toml::Value;
+use toml::value::Table;
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::path::TomlPath;
+use crate::query::compare_value;
+
+/// One operation of RFC 6902 JSON Patch, the path is parsed from JSON Pointer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchOp {
+    /// Add to table, insert into array, or `-` to append.
+    Add { path: TomlPath, value: Value },
+    /// Remove existing node.
+    Remove { path: TomlPath },
+    /// Replace existing node.
+    Replace { path: TomlPath, value: Value },
+    /// Remove from one path and add to another.
+    Move { from: TomlPath, path: TomlPath },
+    /// Copy from one path and add to another.
+    Copy { from: TomlPath, path: TomlPath },
+    /// Check the node is equal to the value.
+    Test { path: TomlPath, value: Value },
+}
+
+/// Error when parse or apply patch, `index` is the position of the failed op.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchError {
+    /// Malformed patch document, or invalid op such as move into itself.
+    Invalid { index: usize, reason: String },
+    /// Path cannot resolve, or the parent to add into is missing.
+    NoPath { index: usize, path: TomlPath },
+    /// The `test` op find different value.
+    TestFailed { index: usize, path: TomlPath },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::Invalid { index, reason } => {
+                write!(f, "patch op {index} is invalid: {reason}")
+            }
+            PatchError::NoPath { index, path } => {
+                write!(f, "patch op {index} failed: path \"{}\" not found", path.to_pointer())
+            }
+            PatchError::TestFailed { index, path } => {
+                write!(f, "patch op {index} failed: test \"{}\" not equal", path.to_pointer())
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// JSON Patch document, a list of operations applied in order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Patch {
+    ops: Vec<PatchOp>,
+}
+
+impl Patch {
+    /// Construct patch from operations.
+    pub fn new(ops: Vec<PatchOp>) -> Self {
+        Self { ops }
+    }
+
+    /// All the operations.
+    pub fn ops(&self) -> &[PatchOp] {
+        &self.ops
+    }
+
+    /// Is empty patch that change nothing.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Parse patch document from array of tables, such as:
+    /// `[{ op = "add", path = "/a/b", value = 1 }]`.
+    /// JSON patch can be deserialized to `toml::Value` first.
+    pub fn from_value(v: &Value) -> Result<Self, PatchError> {
+        let array = match v.as_array() {
+            Some(array) => array,
+            None => return Err(PatchError::Invalid { index: 0, reason: "patch is not array".to_string() }),
+        };
+        let mut ops = Vec::with_capacity(array.len());
+        for (index, op) in array.iter().enumerate() {
+            ops.push(parse_op(index, op)?);
+        }
+        Ok(Self { ops })
+    }
+
+    /// Convert back to array of tables, the path is written as JSON Pointer.
+    pub fn to_value(&self) -> Value {
+        let array = self.ops.iter().map(|op| {
+            let mut table = Table::new();
+            let (name, path, from, value) = match op {
+                PatchOp::Add { path, value } => ("add", path, None, Some(value)),
+                PatchOp::Remove { path } => ("remove", path, None, None),
+                PatchOp::Replace { path, value } => ("replace", path, None, Some(value)),
+                PatchOp::Move { from, path } => ("move", path, Some(from), None),
+                PatchOp::Copy { from, path } => ("copy", path, Some(from), None),
+                PatchOp::Test { path, value } => ("test", path, None, Some(value)),
+            };
+            table.insert("op".to_string(), Value::from(name));
+            table.insert("path".to_string(), Value::from(path.to_pointer()));
+            if let Some(from) = from {
+                table.insert("from".to_string(), Value::from(from.to_pointer()));
+            }
+            if let Some(value) = value {
+                table.insert("value".to_string(), value.clone());
+            }
+            Value::Table(table)
+        }).collect();
+        Value::Array(array)
+    }
+
+    /// Apply all operations atomically, the value is left untouched if any fail.
+    pub fn apply(&self, v: &mut Value) -> Result<(), PatchError> {
+        let mut doc = v.clone();
+        for (index, op) in self.ops.iter().enumerate() {
+            apply_op(index, op, &mut doc)?;
+        }
+        *v = doc;
+        Ok(())
+    }
+
+    /// Generate patch that change `from` to `to`.
+    /// Array is compared item by item, extra items are removed from the end.
+    pub fn diff(from: &Value, to: &Value) -> Self {
+        let mut ops = Vec::new();
+        diff_value(&mut TomlPath::new(), from, to, &mut ops);
+        Self { ops }
+    }
+}
+
+fn parse_op(index: usize, op: &Value) -> Result<PatchOp, PatchError> {
+    let invalid = |reason: String| PatchError::Invalid { index, reason };
+    let get_path = |key: &str| -> Result<TomlPath, PatchError> {
+        match op.get(key).and_then(Value::as_str) {
+            Some(pointer) => TomlPath::from_pointer(pointer).map_err(|e| invalid(e.to_string())),
+            None => Err(invalid(format!("missing `{key}`"))),
+        }
+    };
+    let get_value = || -> Result<Value, PatchError> {
+        op.get("value").cloned().ok_or_else(|| invalid("missing `value`".to_string()))
+    };
+    let op = match op.get("op").and_then(Value::as_str) {
+        Some("add") => PatchOp::Add { path: get_path("path")?, value: get_value()? },
+        Some("remove") => PatchOp::Remove { path: get_path("path")? },
+        Some("replace") => PatchOp::Replace { path: get_path("path")?, value: get_value()? },
+        Some("move") => PatchOp::Move { from: get_path("from")?, path: get_path("path")? },
+        Some("copy") => PatchOp::Copy { from: get_path("from")?, path: get_path("path")? },
+        Some("test") => PatchOp::Test { path: get_path("path")?, value: get_value()? },
+        Some(name) => return Err(invalid(format!("unknown op `{name}`"))),
+        None => return Err(invalid("missing `op`".to_string())),
+    };
+    Ok(op)
+}
+
+/// Add value to path, the empty path replace the whole document.
+fn add(index: usize, path: &TomlPath, value: Value, doc: &mut Value) -> Result<(), PatchError> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    path.insert_into(doc, value).map_err(|_| PatchError::NoPath { index, path: path.clone() })
+}
+
+fn apply_op(index: usize, op: &PatchOp, doc: &mut Value) -> Result<(), PatchError> {
+    let no_path = |path: &TomlPath| PatchError::NoPath { index, path: path.clone() };
+    match op {
+        PatchOp::Add { path, value } => add(index, path, value.clone(), doc),
+        PatchOp::Remove { path } => path.remove_from(doc).map(|_| ()).ok_or_else(|| no_path(path)),
+        PatchOp::Replace { path, value } => match path.apply_mut(doc) {
+            Some(node) => { *node = value.clone(); Ok(()) }
+            None => Err(no_path(path)),
+        },
+        PatchOp::Move { from, path } => {
+            if from == path {
+                return from.apply(doc).map(|_| ()).ok_or_else(|| no_path(from));
+            }
+            if path.starts_with(from) {
+                let reason = "cannot move into itself".to_string();
+                return Err(PatchError::Invalid { index, reason });
+            }
+            let value = from.remove_from(doc).ok_or_else(|| no_path(from))?;
+            add(index, path, value, doc)
+        }
+        PatchOp::Copy { from, path } => {
+            let value = from.apply(doc).cloned().ok_or_else(|| no_path(from))?;
+            add(index, path, value, doc)
+        }
+        PatchOp::Test { path, value } => match path.apply(doc) {
+            Some(node) if compare_value(node, value) == Some(Ordering::Equal) => Ok(()),
+            _ => Err(PatchError::TestFailed { index, path: path.clone() }),
+        },
+    }
+}
+
+fn diff_value(path: &mut TomlPath, from: &Value, to: &Value, ops: &mut Vec<PatchOp>) {
+    match (from, to) {
+        (Value::Table(from), Value::Table(to)) => {
+            for (key, old) in from {
+                path.push_key(key);
+                match to.get(key) {
+                    Some(new) => diff_value(path, old, new, ops),
+                    None => ops.push(PatchOp::Remove { path: path.clone() }),
+                }
+                path.pop();
+            }
+            for (key, new) in to {
+                if !from.contains_key(key) {
+                    path.push_key(key);
+                    ops.push(PatchOp::Add { path: path.clone(), value: new.clone() });
+                    path.pop();
+                }
+            }
+        }
+        (Value::Array(from), Value::Array(to)) => {
+            for (index, (old, new)) in from.iter().zip(to.iter()).enumerate() {
+                path.push_index(index);
+                diff_value(path, old, new, ops);
+                path.pop();
+            }
+            for index in (to.len()..from.len()).rev() {
+                path.push_index(index);
+                ops.push(PatchOp::Remove { path: path.clone() });
+                path.pop();
+            }
+            for (index, new) in to.iter().enumerate().skip(from.len()) {
+                path.push_index(index);
+                ops.push(PatchOp::Add { path: path.clone(), value: new.clone() });
+                path.pop();
+            }
+        }
+        (from, to) if from != to => {
+            ops.push(PatchOp::Replace { path: path.clone(), value: to.clone() });
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,391 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// One step of toml path, either key of table or index of array.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PathItem {
+    /// Key of table, from bare or quoted segment.
+    /// When apply to array, numeric key is also used as index.
+    Key(String),
+    /// Explicit index of array, from `[n]` segment, never match table key.
+    Index(usize),
+}
+
+impl PathItem {
+    /// Array index of the step, numeric key is also index if in canonical form,
+    /// that is only digits without leading zero.
+    pub fn as_index(&self) -> Option<usize> {
+        match self {
+            PathItem::Index(index) => Some(*index),
+            PathItem::Key(key) => {
+                let canonical = key.bytes().all(|c| c.is_ascii_digit())
+                    && (key.len() == 1 || !key.starts_with('0'));
+                if canonical { key.parse::<usize>().ok() } else { None }
+            }
+        }
+    }
+}
+
+/// Parsed toml path, the sequence of key or index from root to some node.
+///
+/// The path syntax is roughly the toml dotted key extended with index:
+/// * segment is separated by slash `/` or dot `.`, continuous separator ignored.
+/// * bare segment can contain any char except `/ . [ ] " '`, which can be
+///   escaped by backslash as `\.`.
+/// * quoted segment in `"basic"` string with toml escape or `'literal'` string,
+///   eg: `servers."10.0.0.1".port`.
+/// * index segment `[n]` can directly follow the previous segment,
+///   eg: `servers[0].port` or `servers/[0]/port`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TomlPath {
+    items: Vec<PathItem>,
+}
+
+/// Syntax error of toml path, with the byte position where parsing failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathError {
+    pos: usize,
+    reason: &'static str,
+}
+
+impl PathError {
+    pub(crate) fn new(pos: usize, reason: &'static str) -> Self {
+        Self { pos, reason }
+    }
+
+    /// Byte offset of the offending char in the path string.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Short description of what is wrong.
+    pub fn reason(&self) -> &str {
+        self.reason
+    }
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid path at position {}: {}", self.pos, self.reason)
+    }
+}
+
+impl std::error::Error for PathError {}
+
+impl TomlPath {
+    /// Construct empty path that point to root.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse path string, report the position if syntax error.
+    pub fn parse(path: &str) -> Result<Self, PathError> {
+        PathParser::new(path).parse()
+    }
+
+    /// Parse RFC 6901 JSON Pointer, such as `/a~1b/0` for key `a/b` then `0`.
+    /// Empty string refer to root, otherwise must start with `/`.
+    /// Each token is taken as key, which also match array index when numeric.
+    pub fn from_pointer(pointer: &str) -> Result<Self, PathError> {
+        if pointer.is_empty() {
+            return Ok(Self::new());
+        }
+        if !pointer.starts_with('/') {
+            return Err(PathError::new(0, "pointer must start with `/`"));
+        }
+        let mut path = Self::new();
+        let mut pos = 1;
+        for token in pointer[1..].split('/') {
+            let mut key = String::with_capacity(token.len());
+            let mut chars = token.char_indices();
+            while let Some((i, c)) = chars.next() {
+                if c != '~' {
+                    key.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some((_, '0')) => key.push('~'),
+                    Some((_, '1')) => key.push('/'),
+                    _ => return Err(PathError::new(pos + i, "invalid `~` escape")),
+                }
+            }
+            path.items.push(PathItem::Key(key));
+            pos += token.len() + 1;
+        }
+        Ok(path)
+    }
+
+    /// Render as RFC 6901 JSON Pointer, escape `~` as `~0` and `/` as `~1`.
+    pub fn to_pointer(&self) -> String {
+        let mut pointer = String::new();
+        for item in &self.items {
+            pointer.push('/');
+            match item {
+                PathItem::Index(index) => pointer.push_str(&index.to_string()),
+                PathItem::Key(key) => pointer.push_str(&key.replace('~', "~0").replace('/', "~1")),
+            }
+        }
+        pointer
+    }
+
+    /// All the steps in path.
+    pub fn items(&self) -> &[PathItem] {
+        &self.items
+    }
+
+    /// Number of steps in path.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Is empty path that point to root.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Append a step.
+    pub fn push(&mut self, item: PathItem) {
+        self.items.push(item);
+    }
+
+    /// Append a table key step.
+    pub fn push_key<K: ToString>(&mut self, key: K) {
+        self.items.push(PathItem::Key(key.to_string()));
+    }
+
+    /// Append an array index step.
+    pub fn push_index(&mut self, index: usize) {
+        self.items.push(PathItem::Index(index));
+    }
+
+    /// Is the path start with `prefix`, or equal to it.
+    pub fn starts_with(&self, prefix: &TomlPath) -> bool {
+        self.items.starts_with(&prefix.items)
+    }
+
+    /// The last step.
+    pub fn last(&self) -> Option<&PathItem> {
+        self.items.last()
+    }
+
+    /// Path of the parent node, `None` for root.
+    pub fn parent(&self) -> Option<TomlPath> {
+        let (_, parent) = self.items.split_last()?;
+        Some(TomlPath::from(parent.to_vec()))
+    }
+
+    /// Remove the last step and return it.
+    pub fn pop(&mut self) -> Option<PathItem> {
+        self.items.pop()
+    }
+}
+
+impl From<Vec<PathItem>> for TomlPath {
+    fn from(items: Vec<PathItem>) -> Self {
+        Self { items }
+    }
+}
+
+impl FromStr for TomlPath {
+    type Err = PathError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Is the key can be written as bare segment without quote.
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Write key in basic string with toml escape.
+fn write_quoted(f: &mut fmt::Formatter<'_>, key: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in key.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\t' => f.write_str("\\t")?,
+            '\r' => f.write_str("\\r")?,
+            c if c.is_control() => write!(f, "\\u{:04X}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    f.write_str("\"")
+}
+
+/// Display in canonical syntax that can be parsed back, eg: `servers[0]."10.0.0.1"`.
+impl fmt::Display for TomlPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, item) in self.items.iter().enumerate() {
+            match item {
+                PathItem::Index(index) => write!(f, "[{index}]")?,
+                PathItem::Key(key) => {
+                    if i > 0 {
+                        f.write_str(".")?;
+                    }
+                    if is_bare_key(key) {
+                        f.write_str(key)?;
+                    }
+                    else {
+                        write_quoted(f, key)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Hand written parser for toml path syntax.
+/// Also used as char cursor by the query parser.
+pub(crate) struct PathParser<'a> {
+    pub(crate) input: &'a str,
+    pub(crate) pos: usize,
+}
+
+impl<'a> PathParser<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    pub(crate) fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    pub(crate) fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    pub(crate) fn error<T>(&self, reason: &'static str) -> Result<T, PathError> {
+        Err(PathError::new(self.pos, reason))
+    }
+
+    fn parse(mut self) -> Result<TomlPath, PathError> {
+        let mut path = TomlPath::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '/' | '.' => { self.bump(); continue; }
+                '[' => path.items.push(self.parse_index()?),
+                '"' => path.items.push(PathItem::Key(self.parse_basic()?)),
+                '\'' => path.items.push(PathItem::Key(self.parse_literal()?)),
+                ']' => return self.error("unmatched `]`"),
+                _ => path.items.push(PathItem::Key(self.parse_bare()?)),
+            }
+            // each segment must end with separator, index or end of path.
+            match self.peek() {
+                None | Some('/') | Some('.') | Some('[') => {}
+                Some(_) => return self.error("expected separator after segment"),
+            }
+        }
+        Ok(path)
+    }
+
+    fn parse_index(&mut self) -> Result<PathItem, PathError> {
+        self.bump();
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            self.bump();
+        }
+        if start == self.pos {
+            return self.error("expected array index");
+        }
+        let index = match self.input[start..self.pos].parse::<usize>() {
+            Ok(index) => index,
+            Err(_) => return Err(PathError::new(start, "array index overflow")),
+        };
+        if self.peek() != Some(']') {
+            return self.error("expected `]`");
+        }
+        self.bump();
+        Ok(PathItem::Index(index))
+    }
+
+    fn parse_bare(&mut self) -> Result<String, PathError> {
+        let mut key = String::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '/' | '.' | '[' => break,
+                ']' => return self.error("unmatched `]`"),
+                '"' | '\'' => return self.error("unexpected quote in bare segment"),
+                '\\' => {
+                    self.bump();
+                    match self.bump() {
+                        Some(c) => key.push(c),
+                        None => return self.error("incomplete escape"),
+                    }
+                }
+                _ => { key.push(c); self.bump(); }
+            }
+        }
+        Ok(key)
+    }
+
+    pub(crate) fn parse_literal(&mut self) -> Result<String, PathError> {
+        let open = self.pos;
+        self.bump();
+        let start = self.pos;
+        match self.input[start..].find('\'') {
+            Some(len) => {
+                self.pos = start + len + 1;
+                Ok(self.input[start..start + len].to_string())
+            }
+            None => Err(PathError::new(open, "unterminated quoted segment")),
+        }
+    }
+
+    pub(crate) fn parse_basic(&mut self) -> Result<String, PathError> {
+        let open = self.pos;
+        self.bump();
+        let mut key = String::new();
+        loop {
+            let c = match self.bump() {
+                Some(c) => c,
+                None => return Err(PathError::new(open, "unterminated quoted segment")),
+            };
+            match c {
+                '"' => return Ok(key),
+                '\\' => key.push(self.parse_escape()?),
+                _ => key.push(c),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<char, PathError> {
+        let at = self.pos - 1;
+        let c = match self.bump() {
+            Some('b') => '\u{8}',
+            Some('t') => '\t',
+            Some('n') => '\n',
+            Some('f') => '\u{c}',
+            Some('r') => '\r',
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('u') => self.parse_unicode(at, 4)?,
+            Some('U') => self.parse_unicode(at, 8)?,
+            _ => return Err(PathError::new(at, "invalid escape")),
+        };
+        Ok(c)
+    }
+
+    fn parse_unicode(&mut self, at: usize, len: usize) -> Result<char, PathError> {
+        let hex = self.input.get(self.pos..self.pos + len)
+            .filter(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()));
+        let c = hex
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .and_then(char::from_u32);
+        match c {
+            Some(c) => { self.pos += len; Ok(c) }
+            None => Err(PathError::new(at, "invalid unicode escape")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests; // { move to tests.rs }
@@ -0,0 +1,130 @@
+// test path syntax parser
+use super::*;
+
+fn key(s: &str) -> PathItem {
+    PathItem::Key(s.to_string())
+}
+
+#[test]
+fn parse_bare_test() {
+    let path = TomlPath::parse("a.b/c").unwrap();
+    assert_eq!(path.items(), &[key("a"), key("b"), key("c")]);
+
+    let path = TomlPath::parse("/a//b/").unwrap();
+    assert_eq!(path.items(), &[key("a"), key("b")]);
+
+    let path = TomlPath::parse("no-key.some_key.34").unwrap();
+    assert_eq!(path.items(), &[key("no-key"), key("some_key"), key("34")]);
+
+    let path = TomlPath::parse(r"example\.com.a\/b").unwrap();
+    assert_eq!(path.items(), &[key("example.com"), key("a/b")]);
+
+    let path = TomlPath::parse("").unwrap();
+    assert_eq!(path.is_empty(), true);
+}
+
+#[test]
+fn parse_quoted_test() {
+    let path = TomlPath::parse(r#"servers."10.0.0.1".port"#).unwrap();
+    assert_eq!(path.items(), &[key("servers"), key("10.0.0.1"), key("port")]);
+
+    let path = TomlPath::parse(r#"'a/b'/"c\"d\u00e9\n""#).unwrap();
+    assert_eq!(path.items(), &[key("a/b"), key("c\"d\u{e9}\n")]);
+
+    let path = TomlPath::parse(r#""""#).unwrap();
+    assert_eq!(path.items(), &[key("")]);
+
+    let path = TomlPath::parse(r#"'C:\path'"#).unwrap();
+    assert_eq!(path.items(), &[key(r"C:\path")]);
+}
+
+#[test]
+fn parse_index_test() {
+    let path = TomlPath::parse("servers[0].port").unwrap();
+    assert_eq!(path.items(), &[key("servers"), PathItem::Index(0), key("port")]);
+
+    let path = TomlPath::parse("[1][2]/[3]").unwrap();
+    assert_eq!(path.items(), &[PathItem::Index(1), PathItem::Index(2), PathItem::Index(3)]);
+
+    let path = TomlPath::parse(r#""a"[10]"#).unwrap();
+    assert_eq!(path.items(), &[key("a"), PathItem::Index(10)]);
+}
+
+#[test]
+fn parse_error_test() {
+    let err = TomlPath::parse("a[x]").unwrap_err();
+    assert_eq!(err.pos(), 2);
+    assert_eq!(err.to_string(), "invalid path at position 2: expected array index");
+
+    let err = TomlPath::parse("a[12").unwrap_err();
+    assert_eq!(err.pos(), 4);
+    assert_eq!(err.reason(), "expected `]`");
+
+    let err = TomlPath::parse("a.b]").unwrap_err();
+    assert_eq!(err.pos(), 3);
+
+    let err = TomlPath::parse(r#"a."b.c"#).unwrap_err();
+    assert_eq!(err.pos(), 2);
+    assert_eq!(err.reason(), "unterminated quoted segment");
+
+    let err = TomlPath::parse(r#"a."b"c"#).unwrap_err();
+    assert_eq!(err.pos(), 5);
+
+    let err = TomlPath::parse(r#"ab"c""#).unwrap_err();
+    assert_eq!(err.pos(), 2);
+
+    let err = TomlPath::parse(r#""\x""#).unwrap_err();
+    assert_eq!(err.pos(), 1);
+    assert_eq!(err.reason(), "invalid escape");
+
+    let err = TomlPath::parse(r"a\").unwrap_err();
+    assert_eq!(err.reason(), "incomplete escape");
+}
+
+#[test]
+fn display_test() {
+    let path = TomlPath::parse(r#"servers[0]."10.0.0.1".port"#).unwrap();
+    assert_eq!(path.to_string(), r#"servers[0]."10.0.0.1".port"#);
+
+    let mut path = TomlPath::new();
+    path.push_index(2);
+    path.push_key("a\"b");
+    path.push_key("");
+    path.push_key(3);
+    assert_eq!(path.to_string(), r#"[2]."a\"b"."".3"#);
+    assert_eq!(TomlPath::parse(&path.to_string()), Ok(path.clone()));
+
+    assert_eq!(path.pop(), Some(key("3")));
+    assert_eq!(path.len(), 3);
+}
+
+#[test]
+fn pointer_test() {
+    let path = TomlPath::from_pointer("/a~1b/0/m~0n/").unwrap();
+    assert_eq!(path.items(), &[key("a/b"), key("0"), key("m~n"), key("")]);
+    assert_eq!(path.to_pointer(), "/a~1b/0/m~0n/");
+
+    assert_eq!(TomlPath::from_pointer("").unwrap().is_empty(), true);
+    assert_eq!(TomlPath::from_pointer("/").unwrap().items(), &[key("")]);
+    assert_eq!(TomlPath::from_pointer("/~01").unwrap().items(), &[key("~1")]);
+
+    let err = TomlPath::from_pointer("a/b").unwrap_err();
+    assert_eq!(err.pos(), 0);
+    let err = TomlPath::from_pointer("/ab/c~2").unwrap_err();
+    assert_eq!(err.pos(), 5);
+    assert_eq!(err.reason(), "invalid `~` escape");
+    assert_eq!(TomlPath::from_pointer("/a~").is_err(), true);
+
+    let path = TomlPath::parse("servers[1].port").unwrap();
+    assert_eq!(path.to_pointer(), "/servers/1/port");
+}
+
+#[test]
+fn as_index_test() {
+    assert_eq!(key("0").as_index(), Some(0));
+    assert_eq!(key("12").as_index(), Some(12));
+    assert_eq!(key("012").as_index(), None);
+    assert_eq!(key("+1").as_index(), None);
+    assert_eq!(key("-").as_index(), None);
+    assert_eq!(PathItem::Index(3).as_index(), Some(3));
+}
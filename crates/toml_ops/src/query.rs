@@ -0,0 +1,620 @@
+use toml::Value;
+use toml::value::{Date, Datetime, Offset, Time};
+use std::cmp::Ordering;
+use std::str::FromStr;
+use crate::path::{TomlPath, PathError, PathParser};
+
+/// Compiled JSONPath-style query, which may match many nodes in toml tree.
+///
+/// Supported syntax, where the leading `$` for root is optional:
+/// * `.key` or `['key']` child of table, `[n]` item of array, negative from end.
+/// * `.*` or `[*]` all children of table or array.
+/// * `..key`, `..*` or `..[...]` recursive descent into all levels.
+/// * `[start:end:step]` slice of array, `['a', 'b']` or `[0, 2]` union.
+/// * `[?(@.optional == true)]` children satisfy filter predicate, which
+///   support `== != < <= > >=`, `&& || !`, and bare `@.key` to test existence.
+///
+/// eg: `$.servers[*].port` or `dependencies[?(@.optional == true)]`.
+#[derive(Debug, Clone)]
+pub struct Query {
+    selectors: Vec<Selector>,
+}
+
+/// Iterator of matched nodes and their concrete path from the query root.
+pub struct QueryIter<'tr> {
+    nodes: std::vec::IntoIter<(TomlPath, &'tr Value)>,
+}
+
+impl<'tr> Iterator for QueryIter<'tr> {
+    type Item = (TomlPath, &'tr Value);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next()
+    }
+}
+
+impl Query {
+    /// Parse query string, report the position if syntax error.
+    pub fn parse(query: &str) -> Result<Self, PathError> {
+        QueryParser::new(query).parse()
+    }
+
+    /// Select all nodes match the query under `v`, in document order.
+    pub fn select<'tr>(&self, v: &'tr Value) -> QueryIter<'tr> {
+        let mut nodes = vec![(TomlPath::new(), v)];
+        for sel in &self.selectors {
+            let mut next = Vec::new();
+            for (path, node) in &nodes {
+                sel.select(path, node, &mut next);
+            }
+            nodes = next;
+        }
+        QueryIter { nodes: nodes.into_iter() }
+    }
+
+    /// Mutable version of `select()`, call `f` on each matched node for bulk
+    /// update, and return the number of visited nodes.
+    /// Node no longer existed after previous update is skipped.
+    pub fn select_mut<F>(&self, v: &mut Value, mut f: F) -> usize
+    where F: FnMut(&TomlPath, &mut Value)
+    {
+        let paths: Vec<TomlPath> = self.select(v).map(|(path, _)| path).collect();
+        let mut count = 0;
+        for path in &paths {
+            if let Some(node) = path.apply_mut(v) {
+                f(path, node);
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+impl FromStr for Query {
+    type Err = PathError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// One step of query, select some nodes from current node.
+#[derive(Debug, Clone)]
+enum Selector {
+    Key(String),
+    Index(i64),
+    Wildcard,
+    Slice(Option<i64>, Option<i64>, i64),
+    Union(Vec<Selector>),
+    Filter(Filter),
+    Descendant(Box<Selector>),
+}
+
+/// Push all the direct children of table or array.
+fn children<'tr>(path: &TomlPath, v: &'tr Value, out: &mut Vec<(TomlPath, &'tr Value)>) {
+    match v {
+        Value::Table(table) => {
+            for (key, child) in table {
+                let mut path = path.clone();
+                path.push_key(key);
+                out.push((path, child));
+            }
+        }
+        Value::Array(array) => {
+            for (index, child) in array.iter().enumerate() {
+                let mut path = path.clone();
+                path.push_index(index);
+                out.push((path, child));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Convert index that may be negative to position in array.
+fn normalize_index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { index + len as i64 } else { index };
+    if index >= 0 && (index as usize) < len {
+        Some(index as usize)
+    }
+    else {
+        None
+    }
+}
+
+/// Positions selected by python style slice.
+fn slice_indexes(start: Option<i64>, end: Option<i64>, step: i64, len: usize) -> Vec<usize> {
+    let len = len as i64;
+    let bound = |i: i64, low: i64, high: i64| {
+        let i = if i < 0 { i + len } else { i };
+        i.clamp(low, high)
+    };
+    let mut indexes = Vec::new();
+    if step > 0 {
+        let mut i = start.map_or(0, |i| bound(i, 0, len));
+        let end = end.map_or(len, |i| bound(i, 0, len));
+        while i < end {
+            indexes.push(i as usize);
+            i = match i.checked_add(step) {
+                Some(i) => i,
+                None => break,
+            };
+        }
+    }
+    else if step < 0 {
+        let mut i = start.map_or(len - 1, |i| bound(i, -1, len - 1));
+        let end = end.map_or(-1, |i| bound(i, -1, len - 1));
+        while i > end {
+            indexes.push(i as usize);
+            i = match i.checked_add(step) {
+                Some(i) => i,
+                None => break,
+            };
+        }
+    }
+    indexes
+}
+
+impl Selector {
+    fn select<'tr>(&self, path: &TomlPath, v: &'tr Value, out: &mut Vec<(TomlPath, &'tr Value)>) {
+        match (self, v) {
+            (Selector::Key(key), Value::Table(table)) => {
+                if let Some(child) = table.get(key) {
+                    let mut path = path.clone();
+                    path.push_key(key);
+                    out.push((path, child));
+                }
+            }
+            (Selector::Index(index), Value::Array(array)) => {
+                if let Some(index) = normalize_index(*index, array.len()) {
+                    let mut path = path.clone();
+                    path.push_index(index);
+                    out.push((path, &array[index]));
+                }
+            }
+            (Selector::Slice(start, end, step), Value::Array(array)) => {
+                for index in slice_indexes(*start, *end, *step, array.len()) {
+                    let mut path = path.clone();
+                    path.push_index(index);
+                    out.push((path, &array[index]));
+                }
+            }
+            (Selector::Wildcard, _) => children(path, v, out),
+            (Selector::Filter(filter), _) => {
+                let mut all = Vec::new();
+                children(path, v, &mut all);
+                out.extend(all.into_iter().filter(|(_, child)| filter.test(child)));
+            }
+            (Selector::Union(list), _) => {
+                for sel in list {
+                    sel.select(path, v, out);
+                }
+            }
+            (Selector::Descendant(sel), _) => {
+                sel.select(path, v, out);
+                let mut all = Vec::new();
+                children(path, v, &mut all);
+                for (path, child) in &all {
+                    self.select(path, child, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Predicate in filter selector, `@` refer to the node to be tested.
+#[derive(Debug, Clone)]
+enum Filter {
+    Exists(TomlPath),
+    Compare(Operand, CmpOp, Operand),
+    Not(Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    Node(TomlPath),
+    Literal(Value),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Operand {
+    fn eval<'a>(&'a self, v: &'a Value) -> Option<&'a Value> {
+        match self {
+            Operand::Node(path) => path.apply(v),
+            Operand::Literal(lit) => Some(lit),
+        }
+    }
+}
+
+/// Compare values, integer and float can compare with each other by number.
+/// Tables and arrays are only equal when all the children are equal, as
+/// RFC 6902 `test` operation does. Values of different type are never equal.
+pub(crate) fn compare_value(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Integer(a), Value::Integer(b)) => a.partial_cmp(b),
+        (Value::Integer(a), Value::Float(b)) => compare_number(*a, *b),
+        (Value::Float(a), Value::Integer(b)) => compare_number(*b, *a).map(Ordering::reverse),
+        (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+        (Value::String(a), Value::String(b)) => a.partial_cmp(b),
+        (Value::Datetime(a), Value::Datetime(b)) => compare_datetime(a, b),
+        (Value::Boolean(a), Value::Boolean(b)) if a == b => Some(Ordering::Equal),
+        (Value::Array(a), Value::Array(b)) => {
+            let equal = a.len() == b.len()
+                && a.iter().zip(b).all(|(a, b)| compare_value(a, b) == Some(Ordering::Equal));
+            equal.then(|| Ordering::Equal)
+        }
+        (Value::Table(a), Value::Table(b)) => {
+            let equal = a.len() == b.len()
+                && a.iter().all(|(key, a)| {
+                    b.get(key).and_then(|b| compare_value(a, b)) == Some(Ordering::Equal)
+                });
+            equal.then(|| Ordering::Equal)
+        }
+        _ => None,
+    }
+}
+
+/// Compare datetimes of the same kind, offset datetimes by the instant in UTC.
+fn compare_datetime(a: &Datetime, b: &Datetime) -> Option<Ordering> {
+    if let (Some(da), Some(ta), Some(oa), Some(db), Some(tb), Some(ob)) =
+        (a.date, a.time, a.offset, b.date, b.time, b.offset) {
+        let ord = utc_seconds(da, ta, oa).cmp(&utc_seconds(db, tb, ob));
+        return Some(ord.then(ta.nanosecond.cmp(&tb.nanosecond)));
+    }
+    let kind = |d: &Datetime| (d.date.is_some(), d.time.is_some(), d.offset.is_some());
+    if kind(a) != kind(b) {
+        return None;
+    }
+    Some((a.date, a.time).cmp(&(b.date, b.time)))
+}
+
+/// Seconds of datetime in UTC, from the day 0000-03-01.
+fn utc_seconds(date: Date, time: Time, offset: Offset) -> i64 {
+    // days from civil date, counting years from March to put leap day last
+    let (year, month, day) = (date.year as i64, date.month as i64, date.day as i64);
+    let year = if month <= 2 { year - 1 } else { year };
+    let month = (month + 9) % 12;
+    let days = year * 365 + year.div_euclid(4) - year.div_euclid(100) + year.div_euclid(400)
+        + (153 * month + 2) / 5 + day - 1;
+    let minutes = match offset {
+        Offset::Z => 0,
+        Offset::Custom { minutes } => minutes as i64,
+    };
+    days * 86400 + time.hour as i64 * 3600 + (time.minute as i64 - minutes) * 60 + time.second as i64
+}
+
+/// Compare integer with float exactly, not lose precision above 2^53.
+fn compare_number(a: i64, b: f64) -> Option<Ordering> {
+    match (a as f64).partial_cmp(&b)? {
+        // the rounded integer equal to float, which is then integral
+        Ordering::Equal if b >= i64::MAX as f64 => Some(Ordering::Less),
+        Ordering::Equal => Some(a.cmp(&(b as i64))),
+        ord => Some(ord),
+    }
+}
+
+impl CmpOp {
+    /// Missing node only equal to missing node.
+    fn test(self, a: Option<&Value>, b: Option<&Value>) -> bool {
+        let ord = match (a, b) {
+            (Some(a), Some(b)) => compare_value(a, b),
+            (None, None) => Some(Ordering::Equal),
+            _ => None,
+        };
+        match self {
+            CmpOp::Eq => ord == Some(Ordering::Equal),
+            CmpOp::Ne => ord != Some(Ordering::Equal),
+            CmpOp::Lt => ord == Some(Ordering::Less),
+            CmpOp::Le => matches!(ord, Some(Ordering::Less | Ordering::Equal)),
+            CmpOp::Gt => ord == Some(Ordering::Greater),
+            CmpOp::Ge => matches!(ord, Some(Ordering::Greater | Ordering::Equal)),
+        }
+    }
+}
+
+impl Filter {
+    fn test(&self, v: &Value) -> bool {
+        match self {
+            Filter::Exists(path) => path.apply(v).is_some(),
+            Filter::Compare(left, op, right) => op.test(left.eval(v), right.eval(v)),
+            Filter::Not(filter) => !filter.test(v),
+            Filter::And(left, right) => left.test(v) && right.test(v),
+            Filter::Or(left, right) => left.test(v) || right.test(v),
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Parser for query syntax, based on the cursor of path parser.
+struct QueryParser<'a> {
+    cur: PathParser<'a>,
+}
+
+impl<'a> QueryParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { cur: PathParser::new(input) }
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(c) = self.cur.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.cur.bump();
+        }
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.cur.input[self.cur.pos..].starts_with(s) {
+            self.cur.pos += s.len();
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, c: char, reason: &'static str) -> Result<(), PathError> {
+        self.skip_ws();
+        if self.cur.peek() != Some(c) {
+            return self.cur.error(reason);
+        }
+        self.cur.bump();
+        Ok(())
+    }
+
+    fn parse(mut self) -> Result<Query, PathError> {
+        let mut selectors = Vec::new();
+        match self.cur.peek() {
+            Some('$') => { self.cur.bump(); }
+            Some(c) if is_name_char(c) => selectors.push(Selector::Key(self.parse_name()?)),
+            _ => {}
+        }
+        while let Some(c) = self.cur.peek() {
+            let sel = match c {
+                '.' if self.eat("..") => {
+                    let sel = match self.cur.peek() {
+                        Some('[') => self.parse_bracket()?,
+                        Some('*') => { self.cur.bump(); Selector::Wildcard }
+                        _ => Selector::Key(self.parse_name()?),
+                    };
+                    Selector::Descendant(Box::new(sel))
+                }
+                '.' => {
+                    self.cur.bump();
+                    if self.eat("*") {
+                        Selector::Wildcard
+                    }
+                    else {
+                        Selector::Key(self.parse_name()?)
+                    }
+                }
+                '[' => self.parse_bracket()?,
+                _ => return self.cur.error("expected `.` or `[`"),
+            };
+            selectors.push(sel);
+        }
+        Ok(Query { selectors })
+    }
+
+    fn parse_name(&mut self) -> Result<String, PathError> {
+        let start = self.cur.pos;
+        while let Some(c) = self.cur.peek() {
+            if !is_name_char(c) {
+                break;
+            }
+            self.cur.bump();
+        }
+        if start == self.cur.pos {
+            return self.cur.error("expected key name");
+        }
+        Ok(self.cur.input[start..self.cur.pos].to_string())
+    }
+
+    fn parse_quoted(&mut self) -> Result<String, PathError> {
+        match self.cur.peek() {
+            Some('"') => self.cur.parse_basic(),
+            _ => self.cur.parse_literal(),
+        }
+    }
+
+    fn parse_int(&mut self) -> Result<i64, PathError> {
+        let start = self.cur.pos;
+        self.eat("-");
+        while let Some(c) = self.cur.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            self.cur.bump();
+        }
+        match self.cur.input[start..self.cur.pos].parse::<i64>() {
+            Ok(i) => Ok(i),
+            Err(_) => Err(PathError::new(start, "expected integer")),
+        }
+    }
+
+    fn parse_opt_int(&mut self) -> Result<Option<i64>, PathError> {
+        self.skip_ws();
+        match self.cur.peek() {
+            Some(c) if c == '-' || c.is_ascii_digit() => Ok(Some(self.parse_int()?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_bracket(&mut self) -> Result<Selector, PathError> {
+        self.cur.bump();
+        self.skip_ws();
+        let sel = match self.cur.peek() {
+            Some('*') => {
+                self.cur.bump();
+                Selector::Wildcard
+            }
+            Some('?') => {
+                self.cur.bump();
+                Selector::Filter(self.parse_or()?)
+            }
+            _ => {
+                let mut list = Vec::new();
+                loop {
+                    list.push(self.parse_union_item()?);
+                    self.skip_ws();
+                    if !self.eat(",") {
+                        break;
+                    }
+                }
+                if list.len() == 1 { list.pop().unwrap() } else { Selector::Union(list) }
+            }
+        };
+        self.expect(']', "expected `]`")?;
+        Ok(sel)
+    }
+
+    fn parse_union_item(&mut self) -> Result<Selector, PathError> {
+        self.skip_ws();
+        if let Some('"' | '\'') = self.cur.peek() {
+            return Ok(Selector::Key(self.parse_quoted()?));
+        }
+        let start = self.parse_opt_int()?;
+        self.skip_ws();
+        if !self.eat(":") {
+            return match start {
+                Some(index) => Ok(Selector::Index(index)),
+                None => self.cur.error("expected index, slice or quoted key"),
+            };
+        }
+        let end = self.parse_opt_int()?;
+        self.skip_ws();
+        let mut step = 1;
+        if self.eat(":") {
+            step = self.parse_opt_int()?.unwrap_or(1);
+        }
+        Ok(Selector::Slice(start, end, step))
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, PathError> {
+        let mut left = self.parse_and()?;
+        loop {
+            self.skip_ws();
+            if !self.eat("||") {
+                return Ok(left);
+            }
+            let right = self.parse_and()?;
+            left = Filter::Or(Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, PathError> {
+        let mut left = self.parse_unary()?;
+        loop {
+            self.skip_ws();
+            if !self.eat("&&") {
+                return Ok(left);
+            }
+            let right = self.parse_unary()?;
+            left = Filter::And(Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Filter, PathError> {
+        self.skip_ws();
+        if self.eat("(") {
+            let filter = self.parse_or()?;
+            self.expect(')', "expected `)`")?;
+            return Ok(filter);
+        }
+        if self.eat("!") {
+            return Ok(Filter::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_compare()
+    }
+
+    fn parse_compare(&mut self) -> Result<Filter, PathError> {
+        let start = self.cur.pos;
+        let left = self.parse_operand()?;
+        self.skip_ws();
+        let op = [("==", CmpOp::Eq), ("!=", CmpOp::Ne), ("<=", CmpOp::Le),
+            (">=", CmpOp::Ge), ("<", CmpOp::Lt), (">", CmpOp::Gt)]
+            .into_iter()
+            .find(|(s, _)| self.eat(s))
+            .map(|(_, op)| op);
+        match (op, left) {
+            (Some(op), left) => Ok(Filter::Compare(left, op, self.parse_operand()?)),
+            (None, Operand::Node(path)) => Ok(Filter::Exists(path)),
+            (None, Operand::Literal(_)) => Err(PathError::new(start, "expected comparison")),
+        }
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, PathError> {
+        self.skip_ws();
+        match self.cur.peek() {
+            Some('@') => {
+                self.cur.bump();
+                Ok(Operand::Node(self.parse_relative()?))
+            }
+            Some('"' | '\'') => Ok(Operand::Literal(Value::String(self.parse_quoted()?))),
+            _ if self.eat("true") => Ok(Operand::Literal(Value::Boolean(true))),
+            _ if self.eat("false") => Ok(Operand::Literal(Value::Boolean(false))),
+            Some(c) if c == '-' || c == '+' || c.is_ascii_digit() => self.parse_number(),
+            _ => self.cur.error("expected `@` or literal"),
+        }
+    }
+
+    /// Number literal, in toml syntax with optional `_` separator.
+    fn parse_number(&mut self) -> Result<Operand, PathError> {
+        let start = self.cur.pos;
+        while let Some(c) = self.cur.peek() {
+            if !(c.is_ascii_alphanumeric() || "+-._".contains(c)) {
+                break;
+            }
+            self.cur.bump();
+        }
+        let text = self.cur.input[start..self.cur.pos].replace('_', "");
+        if let Ok(i) = text.parse::<i64>() {
+            return Ok(Operand::Literal(Value::Integer(i)));
+        }
+        match text.parse::<f64>() {
+            Ok(f) => Ok(Operand::Literal(Value::Float(f))),
+            Err(_) => Err(PathError::new(start, "invalid number")),
+        }
+    }
+
+    /// Path relative to `@`, as `.key`, `['key']` or `[n]`.
+    fn parse_relative(&mut self) -> Result<TomlPath, PathError> {
+        let mut path = TomlPath::new();
+        loop {
+            match self.cur.peek() {
+                Some('.') => {
+                    self.cur.bump();
+                    path.push_key(self.parse_name()?);
+                }
+                Some('[') => {
+                    self.cur.bump();
+                    self.skip_ws();
+                    match self.cur.peek() {
+                        Some('"' | '\'') => path.push_key(self.parse_quoted()?),
+                        _ => {
+                            let start = self.cur.pos;
+                            match usize::try_from(self.parse_int()?) {
+                                Ok(index) => path.push_index(index),
+                                Err(_) => return Err(PathError::new(start, "expected array index")),
+                            }
+                        }
+                    }
+                    self.expect(']', "expected `]`")?;
+                }
+                _ => return Ok(path),
+            }
+        }
+    }
+}
@@ -0,0 +1,576 @@
+use toml::Value;
+use regex::Regex;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::path::TomlPath;
+use crate::diag::At;
+use crate::query::compare_value;
+
+/// Error in the schema itself, `path` is where in the schema document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaError {
+    path: TomlPath,
+    reason: String,
+}
+
+impl SchemaError {
+    fn new(path: &TomlPath, reason: impl ToString) -> Self {
+        Self { path: path.clone(), reason: reason.to_string() }
+    }
+
+    /// Where the error is in the schema document.
+    pub fn path(&self) -> &TomlPath {
+        &self.path
+    }
+
+    /// Description of the error.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid schema at {}: {}", At(&self.path), self.reason)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// One violation found by `Schema::validate()`, with the path of the node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// The node is not any of the expected types.
+    Type { path: TomlPath, expected: String, found: &'static str },
+    /// Required key is missing, the path is to the missing key.
+    Required { path: TomlPath },
+    /// Key is not described in schema and additional key is not allowed.
+    Unknown { path: TomlPath },
+    /// The value is not one of the enum.
+    Enum { path: TomlPath },
+    /// Number is out of the minimum or maximum.
+    Range { path: TomlPath, reason: String },
+    /// Length of string or array is out of range.
+    Length { path: TomlPath, reason: String },
+    /// String not match the pattern.
+    Pattern { path: TomlPath, pattern: String },
+}
+
+impl Violation {
+    /// Path of the node that violate the schema.
+    pub fn path(&self) -> &TomlPath {
+        match self {
+            Violation::Type { path, .. } => path,
+            Violation::Required { path } => path,
+            Violation::Unknown { path } => path,
+            Violation::Enum { path } => path,
+            Violation::Range { path, .. } => path,
+            Violation::Length { path, .. } => path,
+            Violation::Pattern { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Type { path, expected, found } => {
+                write!(f, "expect {expected}, found {found} at {}", At(path))
+            }
+            Violation::Required { path } => write!(f, "missing required key at {}", At(path)),
+            Violation::Unknown { path } => write!(f, "unknown key at {}", At(path)),
+            Violation::Enum { path } => write!(f, "value not in enum at {}", At(path)),
+            Violation::Range { path, reason } => write!(f, "{reason} at {}", At(path)),
+            Violation::Length { path, reason } => write!(f, "{reason} at {}", At(path)),
+            Violation::Pattern { path, pattern } => {
+                write!(f, "string not match pattern `{pattern}` at {}", At(path))
+            }
+        }
+    }
+}
+
+/// Expected type of node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    String,
+    Integer,
+    Float,
+    Number,
+    Boolean,
+    Datetime,
+    Table,
+    Array,
+}
+
+impl Kind {
+    fn parse(name: &str) -> Option<Self> {
+        let kind = match name {
+            "string" => Kind::String,
+            "integer" => Kind::Integer,
+            "float" => Kind::Float,
+            "number" => Kind::Number,
+            "boolean" | "bool" => Kind::Boolean,
+            "datetime" => Kind::Datetime,
+            "table" | "object" => Kind::Table,
+            "array" => Kind::Array,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Kind::String => "string",
+            Kind::Integer => "integer",
+            Kind::Float => "float",
+            Kind::Number => "number",
+            Kind::Boolean => "boolean",
+            Kind::Datetime => "datetime",
+            Kind::Table => "table",
+            Kind::Array => "array",
+        }
+    }
+
+    fn matches(&self, v: &Value) -> bool {
+        match self {
+            Kind::String => v.is_str(),
+            Kind::Integer => v.is_integer(),
+            Kind::Float => v.is_float(),
+            Kind::Number => v.is_integer() || v.is_float(),
+            Kind::Boolean => v.is_bool(),
+            Kind::Datetime => v.is_datetime(),
+            Kind::Table => v.is_table(),
+            Kind::Array => v.is_array(),
+        }
+    }
+}
+
+/// Bound of number, integer or float, and whether it is exclusive.
+/// Integer bound is kept as integer to compare large value exactly.
+#[derive(Debug, Clone, PartialEq)]
+struct Bound {
+    value: Value,
+    exclusive: bool,
+}
+
+/// Schema of one node, empty means any value.
+#[derive(Debug, Clone, Default)]
+struct Node {
+    kinds: Vec<Kind>,
+    properties: BTreeMap<String, Node>,
+    required: Vec<String>,
+    additional: Option<Box<Node>>,
+    items: Option<Box<Node>>,
+    choices: Option<Vec<Value>>,
+    minimum: Option<Bound>,
+    maximum: Option<Bound>,
+    min_len: Option<usize>,
+    max_len: Option<usize>,
+    pattern: Option<Regex>,
+}
+
+/// Describe the expected shape of toml, and validate `toml::Value` against it,
+/// report all the violations with their path.
+///
+/// The schema can be written in compact DSL by `Schema::parse()`, or JSON Schema
+/// by `Schema::from_json_schema()`, which support a common subset: `type`,
+/// `properties`, `required`, `additionalProperties`, `items`, `enum`, `const`,
+/// `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`, `minLength`,
+/// `maxLength`, `minItems`, `maxItems`, `pattern`, and `format = "date-time"`.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    root: Node,
+}
+
+impl Schema {
+    /// Parse schema DSL, which is toml that mirror the expected shape:
+    /// * table describe table, the keys are required and unknown key is
+    ///   reported, unless key `"*"` give the schema for any other key.
+    /// * key with suffix `?` such as `"tls?"` is optional.
+    /// * array with one item describe array of such items, `[]` for any items.
+    /// * string describe leaf node, as type name followed by constraints:
+    ///   `"integer 1..65535"`, `"string 1.. /^[a-z]+$/"`, `"float? ..1.0"`.
+    ///   Type name is one of `string integer float number boolean datetime
+    ///   table array any`, suffix `?` is optional, range `min..max` is for
+    ///   number value or string and array length, `/regex/` for string.
+    ///   Or `enum(a, b, c)` for one of the values in toml syntax or bare word.
+    pub fn parse(dsl: &str) -> Result<Self, SchemaError> {
+        let v: Value = dsl.parse().map_err(|e: toml::de::Error| SchemaError::new(&TomlPath::new(), e.message()))?;
+        Self::from_dsl(&v)
+    }
+
+    /// Build schema from DSL already parsed as toml.
+    pub fn from_dsl(v: &Value) -> Result<Self, SchemaError> {
+        let (root, _) = dsl_node(v, &mut TomlPath::new())?;
+        Ok(Self { root })
+    }
+
+    /// Build schema from JSON Schema, which can be deserialized to
+    /// `toml::Value` first from `.json` file.
+    pub fn from_json_schema(v: &Value) -> Result<Self, SchemaError> {
+        let root = json_node(v, &mut TomlPath::new())?;
+        Ok(Self { root })
+    }
+
+    /// Parse JSON Schema text, such as read from `.json` file.
+    /// Since toml has no `null`, it is dropped from object and array,
+    /// as `"default": null` or `"enum": ["a", null]`.
+    #[cfg(feature = "json")]
+    pub fn from_json_str(json: &str) -> Result<Self, SchemaError> {
+        let v: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| SchemaError::new(&TomlPath::new(), e))?;
+        let v = json_to_toml(v).unwrap_or(Value::Boolean(false));
+        Self::from_json_schema(&v)
+    }
+
+    /// Validate the value, return all the violations, empty if the value is
+    /// valid. The nodes are visited depth first, and for table the missing
+    /// required keys are reported before the violations of its keys, which
+    /// are in the key order of `toml::value::Table`.
+    pub fn validate(&self, v: &Value) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        validate_node(&self.root, v, &mut TomlPath::new(), &mut violations);
+        violations
+    }
+
+    /// Is the value valid.
+    pub fn is_valid(&self, v: &Value) -> bool {
+        self.validate(v).is_empty()
+    }
+}
+
+fn validate_node(node: &Node, v: &Value, path: &mut TomlPath, out: &mut Vec<Violation>) {
+    if !node.kinds.is_empty() && !node.kinds.iter().any(|kind| kind.matches(v)) {
+        let expected: Vec<&str> = node.kinds.iter().map(Kind::name).collect();
+        out.push(Violation::Type { path: path.clone(), expected: expected.join(" or "), found: v.type_str() });
+        return;
+    }
+    if let Some(choices) = &node.choices {
+        if !choices.contains(v) {
+            out.push(Violation::Enum { path: path.clone() });
+        }
+    }
+
+    if v.is_integer() || v.is_float() {
+        if let Some(min) = &node.minimum {
+            let ord = compare_value(v, &min.value);
+            if ord == Some(Ordering::Less) || (min.exclusive && ord == Some(Ordering::Equal)) {
+                let op = if min.exclusive { "greater than" } else { "at least" };
+                out.push(Violation::Range { path: path.clone(), reason: format!("value {v} must be {op} {}", min.value) });
+            }
+        }
+        if let Some(max) = &node.maximum {
+            let ord = compare_value(v, &max.value);
+            if ord == Some(Ordering::Greater) || (max.exclusive && ord == Some(Ordering::Equal)) {
+                let op = if max.exclusive { "less than" } else { "at most" };
+                out.push(Violation::Range { path: path.clone(), reason: format!("value {v} must be {op} {}", max.value) });
+            }
+        }
+    }
+
+    let len = match v {
+        Value::String(s) => Some(s.chars().count()),
+        Value::Array(array) => Some(array.len()),
+        _ => None,
+    };
+    if let Some(len) = len {
+        if node.min_len.map_or(false, |min| len < min) {
+            let reason = format!("length {len} less than minimum {}", node.min_len.unwrap_or_default());
+            out.push(Violation::Length { path: path.clone(), reason });
+        }
+        if node.max_len.map_or(false, |max| len > max) {
+            let reason = format!("length {len} greater than maximum {}", node.max_len.unwrap_or_default());
+            out.push(Violation::Length { path: path.clone(), reason });
+        }
+    }
+
+    match v {
+        Value::String(s) => {
+            if let Some(pattern) = &node.pattern {
+                if !pattern.is_match(s) {
+                    out.push(Violation::Pattern { path: path.clone(), pattern: pattern.as_str().to_string() });
+                }
+            }
+        }
+        Value::Array(array) => {
+            if let Some(items) = &node.items {
+                for (index, item) in array.iter().enumerate() {
+                    path.push_index(index);
+                    validate_node(items, item, path, out);
+                    path.pop();
+                }
+            }
+        }
+        Value::Table(table) => {
+            for key in &node.required {
+                if !table.contains_key(key) {
+                    path.push_key(key);
+                    out.push(Violation::Required { path: path.clone() });
+                    path.pop();
+                }
+            }
+            for (key, child) in table {
+                path.push_key(key);
+                match node.properties.get(key).or(node.additional.as_deref()) {
+                    Some(schema) => validate_node(schema, child, path, out),
+                    None => out.push(Violation::Unknown { path: path.clone() }),
+                }
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Parse DSL node, also return whether it is optional.
+fn dsl_node(v: &Value, path: &mut TomlPath) -> Result<(Node, bool), SchemaError> {
+    let mut node = Node::default();
+    match v {
+        Value::Table(table) => {
+            node.kinds.push(Kind::Table);
+            for (key, spec) in table {
+                path.push_key(key);
+                let (child, optional) = dsl_node(spec, path)?;
+                path.pop();
+                if key == "*" {
+                    node.additional = Some(Box::new(child));
+                    continue;
+                }
+                let (key, optional) = match key.strip_suffix('?') {
+                    Some(key) => (key.to_string(), true),
+                    None => (key.clone(), optional),
+                };
+                if !optional {
+                    node.required.push(key.clone());
+                }
+                node.properties.insert(key, child);
+            }
+            Ok((node, false))
+        }
+        Value::Array(array) => {
+            node.kinds.push(Kind::Array);
+            match array.as_slice() {
+                [] => {}
+                [item] => {
+                    path.push_index(0);
+                    let (items, _) = dsl_node(item, path)?;
+                    path.pop();
+                    node.items = Some(Box::new(items));
+                }
+                _ => return Err(SchemaError::new(path, "array schema should have one item")),
+            }
+            Ok((node, false))
+        }
+        Value::String(spec) => dsl_spec(spec, path),
+        _ => Err(SchemaError::new(path, format!("expect string spec, found {}", v.type_str()))),
+    }
+}
+
+/// Parse leaf spec such as `"integer? 1..65535"`.
+fn dsl_spec(spec: &str, path: &TomlPath) -> Result<(Node, bool), SchemaError> {
+    let mut node = Node::default();
+    let mut spec = spec.trim().to_string();
+
+    // before pattern, as choices may contain `/`
+    if let Some(rest) = spec.strip_prefix("enum(") {
+        let end = rest.rfind(')').ok_or_else(|| SchemaError::new(path, "unclosed `enum(`"))?;
+        node.choices = Some(parse_choices(&rest[..end]));
+        let optional = rest[end + 1..].trim() == "?";
+        return Ok((node, optional));
+    }
+
+    if let (Some(start), Some(end)) = (spec.find('/'), spec.rfind('/')) {
+        if start == end {
+            return Err(SchemaError::new(path, "unclosed `/` of pattern"));
+        }
+        let pattern = Regex::new(&spec[start + 1..end]).map_err(|e| SchemaError::new(path, e))?;
+        node.pattern = Some(pattern);
+        spec.replace_range(start..=end, "");
+    }
+
+    let mut words = spec.split_whitespace();
+    let name = words.next().ok_or_else(|| SchemaError::new(path, "empty spec"))?;
+    let (name, optional) = match name.strip_suffix('?') {
+        Some(name) => (name, true),
+        None => (name, false),
+    };
+    if name != "any" {
+        let kind = Kind::parse(name).ok_or_else(|| SchemaError::new(path, format!("unknown type `{name}`")))?;
+        node.kinds.push(kind);
+    }
+    for word in words {
+        let (min, max) = word.split_once("..").ok_or_else(|| {
+            SchemaError::new(path, format!("unknown constraint `{word}`"))
+        })?;
+        let bad_range = || SchemaError::new(path, format!("invalid range `{word}`"));
+        match node.kinds.first() {
+            Some(Kind::String) | Some(Kind::Array) => {
+                node.min_len = parse_bound(min, bad_range)?;
+                node.max_len = parse_bound(max, bad_range)?;
+            }
+            _ => {
+                node.minimum = parse_number(min, bad_range)?.map(|value| Bound { value, exclusive: false });
+                node.maximum = parse_number(max, bad_range)?.map(|value| Bound { value, exclusive: false });
+            }
+        }
+    }
+    Ok((node, optional))
+}
+
+fn parse_bound<T: std::str::FromStr, F>(s: &str, err: F) -> Result<Option<T>, SchemaError>
+where F: Fn() -> SchemaError
+{
+    if s.is_empty() {
+        return Ok(None);
+    }
+    s.parse().map(Some).map_err(|_| err())
+}
+
+/// Parse number bound, as integer if it can.
+fn parse_number<F>(s: &str, err: F) -> Result<Option<Value>, SchemaError>
+where F: Fn() -> SchemaError
+{
+    if let Ok(Some(i)) = parse_bound::<i64, _>(s, &err) {
+        return Ok(Some(Value::Integer(i)));
+    }
+    Ok(parse_bound::<f64, _>(s, err)?.map(Value::Float))
+}
+
+/// Parse enum choices as toml array, or bare words as string.
+fn parse_choices(list: &str) -> Vec<Value> {
+    let text = format!("choices = [{list}]");
+    if let Ok(Value::Table(mut table)) = text.parse::<Value>() {
+        if let Some(Value::Array(choices)) = table.remove("choices") {
+            return choices;
+        }
+    }
+    list.split(',').map(|word| Value::from(word.trim())).collect()
+}
+
+/// Parse JSON Schema node.
+fn json_node(v: &Value, path: &mut TomlPath) -> Result<Node, SchemaError> {
+    let mut node = Node { additional: Some(Box::default()), ..Node::default() };
+    let table = match v {
+        Value::Table(table) => table,
+        Value::Boolean(true) => return Ok(node),
+        _ => return Err(SchemaError::new(path, "schema should be table")),
+    };
+    let invalid = |path: &mut TomlPath, key: &str, reason: &str| {
+        path.push_key(key);
+        let err = SchemaError::new(path, reason);
+        path.pop();
+        err
+    };
+
+    let datetime = matches!(table.get("format").and_then(Value::as_str), Some("date-time" | "date" | "time"));
+    let types: Vec<&Value> = match table.get("type") {
+        Some(Value::Array(types)) => types.iter().collect(),
+        Some(t) => vec![t],
+        None => Vec::new(),
+    };
+    for t in types {
+        let name = t.as_str().ok_or_else(|| invalid(path, "type", "expect string"))?;
+        match (name, Kind::parse(name)) {
+            ("null", _) => {}
+            ("string", _) if datetime => node.kinds.push(Kind::Datetime),
+            (_, Some(kind)) => node.kinds.push(kind),
+            (_, None) => return Err(invalid(path, "type", &format!("unknown type `{name}`"))),
+        }
+    }
+
+    if let Some(properties) = table.get("properties") {
+        let properties = properties.as_table().ok_or_else(|| invalid(path, "properties", "expect table"))?;
+        path.push_key("properties");
+        for (key, schema) in properties {
+            path.push_key(key);
+            let child = json_node(schema, path)?;
+            path.pop();
+            node.properties.insert(key.clone(), child);
+        }
+        path.pop();
+    }
+    if let Some(required) = table.get("required") {
+        let required = required.as_array().ok_or_else(|| invalid(path, "required", "expect array"))?;
+        for key in required {
+            let key = key.as_str().ok_or_else(|| invalid(path, "required", "expect string key"))?;
+            node.required.push(key.to_string());
+        }
+    }
+    match table.get("additionalProperties") {
+        Some(Value::Boolean(true)) | None => {}
+        Some(Value::Boolean(false)) => node.additional = None,
+        Some(schema) => {
+            path.push_key("additionalProperties");
+            node.additional = Some(Box::new(json_node(schema, path)?));
+            path.pop();
+        }
+    }
+    if let Some(items) = table.get("items") {
+        path.push_key("items");
+        node.items = Some(Box::new(json_node(items, path)?));
+        path.pop();
+    }
+
+    if let Some(choices) = table.get("enum") {
+        let choices = choices.as_array().ok_or_else(|| invalid(path, "enum", "expect array"))?;
+        node.choices = Some(choices.clone());
+    }
+    if let Some(value) = table.get("const") {
+        node.choices = Some(vec![value.clone()]);
+    }
+
+    let number = |path: &mut TomlPath, key: &str| -> Result<Option<Value>, SchemaError> {
+        match table.get(key) {
+            Some(v @ (Value::Integer(_) | Value::Float(_))) => Ok(Some(v.clone())),
+            Some(_) => Err(invalid(path, key, "expect number")),
+            None => Ok(None),
+        }
+    };
+    node.minimum = number(path, "minimum")?.map(|value| Bound { value, exclusive: false });
+    node.maximum = number(path, "maximum")?.map(|value| Bound { value, exclusive: false });
+    if let Some(value) = number(path, "exclusiveMinimum")? {
+        node.minimum = Some(Bound { value, exclusive: true });
+    }
+    if let Some(value) = number(path, "exclusiveMaximum")? {
+        node.maximum = Some(Bound { value, exclusive: true });
+    }
+
+    let length = |path: &mut TomlPath, key: &str| -> Result<Option<usize>, SchemaError> {
+        match table.get(key) {
+            Some(Value::Integer(i)) if *i >= 0 => Ok(Some(*i as usize)),
+            Some(_) => Err(invalid(path, key, "expect non-negative integer")),
+            None => Ok(None),
+        }
+    };
+    node.min_len = length(path, "minLength")?.or(length(path, "minItems")?);
+    node.max_len = length(path, "maxLength")?.or(length(path, "maxItems")?);
+
+    if let Some(pattern) = table.get("pattern") {
+        let pattern = pattern.as_str().ok_or_else(|| invalid(path, "pattern", "expect string"))?;
+        let pattern = Regex::new(pattern).map_err(|e| invalid(path, "pattern", &e.to_string()))?;
+        node.pattern = Some(pattern);
+    }
+    Ok(node)
+}
+
+/// Convert JSON to toml, `None` for `null` which is dropped from object and array.
+#[cfg(feature = "json")]
+fn json_to_toml(v: serde_json::Value) -> Option<Value> {
+    use serde_json::Value as Json;
+    let v = match v {
+        Json::Null => return None,
+        Json::Bool(b) => Value::Boolean(b),
+        Json::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Float(n.as_f64()?),
+        },
+        Json::String(s) => Value::String(s),
+        Json::Array(array) => Value::Array(array.into_iter().filter_map(json_to_toml).collect()),
+        Json::Object(map) => Value::Table(
+            map.into_iter().filter_map(|(key, v)| Some((key, json_to_toml(v)?))).collect(),
+        ),
+    };
+    Some(v)
+}
@@ -0,0 +1,76 @@
+use toml::Value;
+use toml::de::Error;
+use serde::de::{DeserializeOwned, Error as _};
+use std::ops::BitOr;
+
+use crate::operator::{TomlPtr, TomlPtrMut};
+use crate::diag::{DiagPtr, DiagPtrMut};
+
+/// Wrapper of default value for pipe operator `|` to extract any type that
+/// can deserialize from toml, such as user struct, `Vec<T>`, `HashMap<String, T>`,
+/// `Datetime`, or narrower integer.
+/// eg: `tomlptr / "server" | Typed(ServerConfig::default())`.
+///
+/// Note that cannot directly use `| ServerConfig::default()` because it would
+/// conflict with the builtin `| 0` or `| ""`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Typed<T>(pub T);
+
+/// Deserialize from a copy of the node, report error when `None`.
+fn extract<T: DeserializeOwned>(valop: Option<&Value>, missing: &str) -> Result<T, Error> {
+    match valop {
+        Some(v) => v.clone().try_into(),
+        None => Err(Error::custom(missing)),
+    }
+}
+
+impl<'tr> TomlPtr<'tr> {
+    /// Strict version of `| Typed(default)`, return the deserialize error.
+    pub fn extract<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        extract(**self, "node not found")
+    }
+}
+
+impl<'tr> TomlPtrMut<'tr> {
+    /// Strict version of `| Typed(default)`, return the deserialize error.
+    pub fn extract<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        extract(self.as_deref(), "node not found")
+    }
+}
+
+impl<'tr> DiagPtr<'tr> {
+    /// Strict version of `| Typed(default)`, the path failure is also reported.
+    pub fn extract<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        match self.error() {
+            Some(err) => Err(Error::custom(err)),
+            None => extract(**self, "node not found"),
+        }
+    }
+}
+
+impl<'tr> DiagPtrMut<'tr> {
+    /// Strict version of `| Typed(default)`, the path failure is also reported.
+    pub fn extract<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        match self.error() {
+            Some(err) => Err(Error::custom(err)),
+            None => extract(self.as_deref(), "node not found"),
+        }
+    }
+}
+
+/// Pipe operator `|` to deserialize any type or `rhs` as default if pointer
+/// is invalid or type mismatch.
+impl<'tr, T: DeserializeOwned> BitOr<Typed<T>> for TomlPtr<'tr> {
+    type Output = T;
+    fn bitor(self, rhs: Typed<T>) -> Self::Output {
+        self.extract().unwrap_or(rhs.0)
+    }
+}
+
+/// Pipe operator `|` to deserialize any type or `rhs` as default.
+impl<'tr, T: DeserializeOwned> BitOr<Typed<T>> for TomlPtrMut<'tr> {
+    type Output = T;
+    fn bitor(self, rhs: Typed<T>) -> Self::Output {
+        self.extract().unwrap_or(rhs.0)
+    }
+}
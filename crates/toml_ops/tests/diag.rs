@@ -0,0 +1,90 @@
+use toml::Value;
+use toml_ops::{PathOperator, PathItem, PtrError, TomlPath};
+
+fn load_test_toml() -> Value
+{
+    let str_toml = include_str!("../examples/sample.toml");
+    str_toml.parse().unwrap()
+}
+
+#[test]
+fn diag_path_test() {
+    let v = load_test_toml();
+
+    let port = v.path_diag() / "host" / "port";
+    assert_eq!(port.error().is_none(), true);
+    assert_eq!(port.location().to_string(), "host.port");
+    assert_eq!(port | 0, 8080);
+
+    let ptr = v.path_diag() / "host" / "nokey" / "deeper";
+    assert_eq!(!ptr.clone(), true);
+    assert_eq!(ptr.location().to_string(), "host");
+    let err = ptr.error().unwrap();
+    assert_eq!(err, &PtrError::MissingKey { path: TomlPath::parse("host").unwrap(), key: "nokey".to_string() });
+    assert_eq!(err.to_string(), "missing key `nokey` in table at `host`");
+    assert_eq!(ptr | "default", "default");
+
+    let ptr = v.path_diag() / "host/protocol/5";
+    assert_eq!(ptr.error().unwrap().to_string(), "index 5 out of range for array of length 3 at `host.protocol`");
+
+    let ptr = v.path_diag() / "host" / "port" / "x";
+    match ptr.error() {
+        Some(PtrError::NotIndexable { path, step, found }) => {
+            assert_eq!(path.to_string(), "host.port");
+            assert_eq!(step, &PathItem::Key("x".to_string()));
+            assert_eq!(*found, "integer");
+        }
+        _ => panic!("expect NotIndexable error"),
+    }
+
+    let ptr = v.path_diag() / "service[x]";
+    assert_eq!(matches!(ptr.error(), Some(PtrError::Syntax(_))), true);
+    let ptr = v.path_diag() / "nokey";
+    assert_eq!(ptr.error().unwrap().to_string(), "missing key `nokey` in table at root");
+
+    // the first failure is kept
+    let ptr = v.path_diag() / "misc" / "nokey" / "other";
+    assert_eq!(ptr.result().unwrap_err().to_string(), "missing key `nokey` in table at `misc`");
+}
+
+#[test]
+fn diag_put_test() {
+    let mut v = load_test_toml();
+
+    let node = v.path_diag_mut() / "host" / "port" << 8989;
+    assert_eq!(node.error().is_none(), true);
+    assert_eq!(v.path() / "host" / "port" | 0, 8989);
+
+    let node = v.path_diag_mut() / "host" / "port" << "8080";
+    assert_eq!(node.error().unwrap().to_string(), "cannot put string to integer at `host.port`");
+    assert_eq!(v.path() / "host" / "port" | 0, 8989);
+
+    let node = v.path_diag_mut() / "host" / "ip" << ("k", 1);
+    assert_eq!(node.error().unwrap().to_string(), "cannot put table to string at `host.ip`");
+
+    let node = v.path_diag_mut() / "host" / "protocol" << ("tls",);
+    assert_eq!(node.error().is_none(), true);
+    let node = v.path_diag_mut() / "host" / "protocol" / 9 << "x";
+    assert_eq!(matches!(node.error(), Some(PtrError::OutOfRange { index: 9, len: 4, .. })), true);
+
+    let mut node = v.path_diag_mut() / "misc" / "bool";
+    node <<= "not bool";
+    assert_eq!(node | "", "not bool");
+
+    let node = v.path_diag_mut() / "misc" / "int" << 7u8;
+    assert_eq!(node.error().is_none(), true);
+    let node = v.path_diag_mut() / "host" / "protocol" << vec![Value::from("quic")];
+    assert_eq!(node.error().is_none(), true);
+    let node = v.path_diag_mut() / "host" / "port" << toml::value::Table::new();
+    assert_eq!(node.error().unwrap().to_string(), "cannot put table to integer at `host.port`");
+
+    let node = v.path_diag_mut() / "host" / "protocol" << ["h2"];
+    assert_eq!(node.error().is_none(), true);
+    let node = v.path_diag_mut() / "host" / "protocol" << &["h3", "ws"][..];
+    assert_eq!(node.error().is_none(), true);
+    assert_eq!(v.path() / "host" / "protocol" / 7 | "", "ws");
+    let node = v.path_diag_mut() / "host" / "port" << [1];
+    assert_eq!(node.error().unwrap().to_string(), "cannot put array to integer at `host.port`");
+    let node = v.path_diag_mut() / "host" / "ip" << &[1, 2][..];
+    assert_eq!(node.error().unwrap().to_string(), "cannot put array to string at `host.ip`");
+}
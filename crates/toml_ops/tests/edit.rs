@@ -0,0 +1,155 @@
+use toml_edit::Document;
+use toml_ops::EditOperator;
+
+fn load_test_doc() -> Document
+{
+    let str_toml = include_str!("../examples/sample.toml");
+    str_toml.parse().unwrap()
+}
+
+#[test]
+fn path_test() {
+    let doc = load_test_doc();
+
+    let ip = doc.path() / "ip" | "";
+    assert_eq!(ip, "127.0.0.1");
+
+    let host = doc.path() / "host";
+    let ip = host / "ip" | "";
+    assert_eq!(ip, "127.0.1.1");
+    let port = host / "port" | 0;
+    assert_eq!(port, 8080);
+
+    let proto = host / "protocol" / 1 | "";
+    assert_eq!(proto, "udp");
+    let proto = doc.path() / "host/protocol/2" | "";
+    assert_eq!(proto, "mmp");
+    let proto = doc.pathto("host.protocol.2") | "";
+    assert_eq!(proto, "mmp");
+
+    // array of tables
+    let name = doc.path() / "service" / 1 / "name" | "";
+    assert_eq!(name, "serv_2");
+    let name = doc.pathto("/service/0/name") | String::new();
+    assert_eq!(name, "serv_1");
+
+    let value = doc.path() / "misc" / "float" | 0.0;
+    assert_eq!(value, 3.14);
+    let value = doc.path() / "misc" / "bool" | false;
+    assert_eq!(value, true);
+
+    let node = doc.path() / "host" / "no-key";
+    assert_eq!(!node, true);
+    let node = doc.path() / "host" / "protocol" / 3;
+    assert_eq!(node.is_none(), true);
+    let node = doc.path() / "service" / 2;
+    assert_eq!(node.is_none(), true);
+}
+
+#[test]
+fn path_mut_test() {
+    let mut doc = load_test_doc();
+
+    let port = doc.path_mut() / "host" / "port" | 0;
+    assert_eq!(port, 8080);
+    let name = doc.path_mut() / "service" / 0 / "name" | "";
+    assert_eq!(name, "serv_1");
+
+    // missing key must not be inserted as `Item::get_mut()` does.
+    let node = doc.path_mut() / "host" / "no-key";
+    assert_eq!(node.is_none(), true);
+    assert_eq!(doc.to_string(), include_str!("../examples/sample.toml"));
+}
+
+#[test]
+fn push_keep_decor_test() {
+    let tv = r#"# head comment
+[host]
+ip = "127.0.0.1"   # listen ip
+port = 8080 # listen port
+proto = ["tcp", "udp"]
+inline = { a = 1, b = "x" }
+"#;
+    let mut doc: Document = tv.parse().unwrap();
+
+    let node = doc.path_mut() / "host" / "port" << 8989;
+    assert_eq!(node | 0, 8989);
+    let _ = doc.path_mut() / "host" / "ip" << "127.0.0.2";
+
+    // mistype put has no effect
+    let node = doc.path_mut() / "host" / "ip" << 1;
+    assert_eq!(node.is_none(), true);
+
+    let _ = doc.path_mut() / "host" / "proto" << ("json",) << ["rpc"];
+    let _ = doc.path_mut() / "host" / "inline" << ("a", 2);
+
+    let expected = r#"# head comment
+[host]
+ip = "127.0.0.2"   # listen ip
+port = 8989 # listen port
+proto = ["tcp", "udp", "json", "rpc"]
+inline = { a = 2, b = "x" }
+"#;
+    assert_eq!(doc.to_string(), expected);
+
+    let mut node = doc.path_mut() / "host" / "port";
+    node <<= "8080";
+    let port = doc.path() / "host" / "port" | "";
+    assert_eq!(port, "8080");
+    assert_eq!(doc.to_string(), expected.replace("8989", "\"8080\""));
+}
+
+#[test]
+fn push_table_test() {
+    let mut doc = load_test_doc();
+
+    let _ = doc.path_mut() / "host" << ("newkey", 1) << ("port", 80);
+    let val = doc.path() / "host" / "newkey" | 0;
+    assert_eq!(val, 1);
+    let val = doc.path() / "host" / "port" | 0;
+    assert_eq!(val, 80);
+
+    // push to array of tables only accept inline table
+    let item: toml_edit::InlineTable = [("name", "serv_3")].into_iter().collect();
+    let node = doc.path_mut() / "service" << (item,);
+    assert_eq!(node.is_none(), false);
+    let name = doc.path() / "service" / 2 / "name" | "";
+    assert_eq!(name, "serv_3");
+    let node = doc.path_mut() / "service" << ("serv_4",);
+    assert_eq!(node.is_none(), true);
+}
+
+#[test]
+fn remove_keep_decor_test() {
+    let tv = r#"# head comment
+[host]
+ip = "127.0.0.1"   # listen ip
+port = 8080 # listen port
+proto = ["tcp", "udp"]
+
+[[service]]
+name = "serv_1"
+
+[[service]]
+name = "serv_2"
+"#;
+    let mut doc: Document = tv.parse().unwrap();
+
+    let port = doc.path_mut() / "host" - "port";
+    assert_eq!(port.unwrap().as_integer(), Some(8080));
+    let proto = doc.path_mut() / "host" / "proto" - 0;
+    assert_eq!(proto.unwrap().as_str(), Some("tcp"));
+    let serv = doc.path_mut() - "service.0";
+    assert_eq!(serv.unwrap().is_table(), true);
+    assert_eq!((doc.path_mut() - "nokey").is_none(), true);
+    assert_eq!((doc.path_mut() / "host" / "proto" - 5).is_none(), true);
+
+    assert_eq!(doc.to_string(), r#"# head comment
+[host]
+ip = "127.0.0.1"   # listen ip
+proto = ["udp"]
+
+[[service]]
+name = "serv_2"
+"#);
+}
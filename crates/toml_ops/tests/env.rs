@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use toml::Value;
+use toml_ops::{PathOperator, EnvOverlay, EnvError, TomlPath};
+
+fn load_test_toml() -> Value
+{
+    let str_toml = include_str!("../examples/sample.toml");
+    str_toml.parse().unwrap()
+}
+
+#[test]
+fn overlay_test() {
+    let mut v = load_test_toml();
+    let mut env = HashMap::new();
+    env.insert("APP_HOST__PORT", "9090");
+    env.insert("APP_HOST__PROTOCOL", "tcp, quic");
+    env.insert("APP_MISC__FLOAT", "2.5");
+    env.insert("APP_MISC__BOOL", "no");
+    env.insert("APP_SERVICE__1__DESC", "changed");
+    env.insert("APP_NEW__KEY", "value");
+    env.insert("OTHER_HOST__PORT", "1");
+
+    let count = EnvOverlay::new("APP_").apply_from(&mut v, env).unwrap();
+    assert_eq!(count, 6);
+    assert_eq!(v.path() / "host.port" | 0, 9090);
+    assert_eq!(v.path() / "host.protocol[1]" | "", "quic");
+    assert_eq!((v.path() / "host.protocol").unwrap().as_array().unwrap().len(), 2);
+    assert_eq!(v.path() / "misc.float" | 0.0, 2.5);
+    assert_eq!(v.path() / "misc.bool" | true, false);
+    assert_eq!(v.path() / "service[1].desc" | "", "changed");
+    assert_eq!(v.path() / "new.key" | "", "value");
+}
+
+#[test]
+fn strict_test() {
+    let origin = load_test_toml();
+    let mut v = origin.clone();
+    let overlay = EnvOverlay::new("APP").separator("_").strict(true);
+
+    let vars = [("APP_HOST_PORT", "9090"), ("APP_HOST_NOKEY", "1")];
+    let err = overlay.apply_from(&mut v, vars).unwrap_err();
+    assert_eq!(err, EnvError::UnknownKey { var: "APP_HOST_NOKEY".to_string(), path: TomlPath::parse("host.nokey").unwrap() });
+    assert_eq!(err.to_string(), "environment variable APP_HOST_NOKEY: unknown key `host.nokey`");
+    assert_eq!(v, origin);
+
+    let vars = [("APP_HOST_PORT", "port")];
+    let err = overlay.apply_from(&mut v, vars).unwrap_err();
+    assert_eq!(err.to_string(), "environment variable APP_HOST_PORT: expect integer for `host.port`");
+    assert_eq!(v, origin);
+
+    let vars = [("APP_HOST", "table")];
+    assert_eq!(matches!(overlay.apply_from(&mut v, vars), Err(EnvError::Coerce { .. })), true);
+    let vars = [("APP_IP_INNER", "1")];
+    assert_eq!(matches!(overlay.clone().strict(false).apply_from(&mut v, vars), Err(EnvError::UnknownKey { .. })), true);
+    assert_eq!(v, origin);
+}
+
+#[test]
+fn prefix_boundary_test() {
+    let origin = load_test_toml();
+    let vars = [("APPDATA", "C:\\Users"), ("APPLICATION_ID", "1"), ("APP_HOST_PORT", "9090")];
+
+    let mut v = origin.clone();
+    let count = EnvOverlay::new("APP").separator("_").apply_from(&mut v, vars).unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(v.path() / "host.port" | 0, 9090);
+    assert_eq!(v.get("data"), None);
+    assert_eq!(v.get("lication"), None);
+
+    let mut v = origin.clone();
+    let count = EnvOverlay::new("APP").separator("_").strict(true).apply_from(&mut v, vars).unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(v.path() / "host.port" | 0, 9090);
+
+    let mut v = origin;
+    let count = EnvOverlay::new("APP_").apply_from(&mut v, [("APPDATA", "x"), ("APP_HOST__PORT", "9090")]).unwrap();
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn datetime_test() {
+    let mut v: Value = "time = 1979-05-27T07:32:00Z\nmy-key = 1".parse().unwrap();
+    let vars = [("CFG_TIME", "2020-01-02T03:04:05Z"), ("CFG_MY_KEY", "2")];
+    EnvOverlay::new("CFG_").apply_from(&mut v, vars).unwrap();
+    assert_eq!(v["time"].as_datetime().unwrap().to_string(), "2020-01-02T03:04:05Z");
+    assert_eq!(v["my-key"].as_integer(), Some(2));
+}
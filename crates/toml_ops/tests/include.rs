@@ -0,0 +1,64 @@
+use std::path::Path;
+use toml_ops::{PathOperator, Loader, IncludeError, TomlPath};
+
+fn source_name(loaded: &toml_ops::Loaded, path: &str) -> String {
+    let source = loaded.source(&TomlPath::parse(path).unwrap()).unwrap();
+    source.file_name().unwrap().to_string_lossy().to_string()
+}
+
+#[test]
+fn load_test() {
+    let loaded = Loader::new().load("tests/include/main.toml").unwrap();
+    let v = loaded.value();
+    assert_eq!(v.path() / "include" | "", "");
+    assert_eq!(v.path() / "name" | "", "main");
+    assert_eq!(v.path() / "host.ip" | "", "127.0.0.1");
+    assert_eq!(v.path() / "host.port" | 0, 9090);
+    assert_eq!(v.path() / "log.level" | "", "debug");
+    assert_eq!(v.path() / "service[0].name" | "", "serv_1");
+    assert_eq!(v.path() / "service[0].desc" | "", "included service");
+    assert_eq!((v.path() / "service[0].include").is_none(), true);
+
+    assert_eq!(loaded.files().len(), 5);
+    assert_eq!(source_name(&loaded, "name"), "main.toml");
+    assert_eq!(source_name(&loaded, "host.ip"), "base.toml");
+    assert_eq!(source_name(&loaded, "host.port"), "main.toml");
+    assert_eq!(source_name(&loaded, "log.level"), "10-log.toml");
+    assert_eq!(source_name(&loaded, "log.file"), "10-log.toml");
+    assert_eq!(source_name(&loaded, "service[0].name"), "20-service.toml");
+    assert_eq!(source_name(&loaded, "service[0].desc"), "service.toml");
+}
+
+#[test]
+fn special_dir_test() {
+    let loaded = Loader::new().load("tests/include/[x]/main.toml").unwrap();
+    let v = loaded.value();
+    assert_eq!(v.path() / "name" | "", "bracket");
+    assert_eq!(v.path() / "part" | "", "a");
+    assert_eq!((v.path() / "decoy").is_none(), true);
+    assert_eq!(loaded.files().len(), 2);
+}
+
+#[test]
+fn custom_key_test() {
+    let loaded = Loader::new().key("import").load("tests/include/main.toml").unwrap();
+    assert_eq!(loaded.files().len(), 1);
+    assert_eq!(loaded.value().path() / "include" / 0 | "", "base.toml");
+    assert_eq!(loaded.value().path() / "host.ip" | "", "");
+}
+
+#[test]
+fn error_test() {
+    let err = Loader::new().load("tests/include/cycle_a.toml").unwrap_err();
+    match &err {
+        IncludeError::Cycle { files } => {
+            assert_eq!(files.len(), 2);
+            assert_eq!(files[0].ends_with("cycle_a.toml"), true);
+        }
+        _ => panic!("expect cycle error: {err}"),
+    }
+    assert_eq!(err.to_string().starts_with("include cycle:"), true);
+
+    let err = Loader::new().load(Path::new("tests/include/nofile.toml")).unwrap_err();
+    assert_eq!(matches!(err, IncludeError::Io { .. }), true);
+}
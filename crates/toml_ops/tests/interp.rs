@@ -0,0 +1,76 @@
+use toml::Value;
+use toml_ops::{PathOperator, Interpolate, InterpError, TomlPath};
+
+#[test]
+fn expand_test() {
+    let v: Value = r#"
+    addr = "${host.ip}:${host.port}"
+    url = "http://${addr}/${service[0].name}?t=${misc.time}"
+    port = "${host.port}"
+    proto = "${host.protocol}"
+    price = "$$5 and $${literal} and ${misc.float}$"
+    [host]
+    ip = "127.0.1.1"
+    port = 8080
+    protocol = ["tcp", "${service.0.name}"]
+    [[service]]
+    name = "serv_1"
+    [misc]
+    float = 3.5
+    time = 1979-05-27
+    "#.parse().unwrap();
+
+    let out = Interpolate::new().resolve(&v).unwrap();
+    assert_eq!(out.path() / "addr" | "", "127.0.1.1:8080");
+    assert_eq!(out.path() / "url" | "", "http://127.0.1.1:8080/serv_1?t=1979-05-27");
+    assert_eq!(out.path() / "port" | 0, 8080);
+    assert_eq!(out.path() / "proto" / 1 | "", "serv_1");
+    assert_eq!(out.path() / "host.protocol[1]" | "", "serv_1");
+    assert_eq!(out.path() / "price" | "", "$$5 and ${literal} and 3.5$");
+    assert_eq!(v.path() / "addr" | "", "${host.ip}:${host.port}");
+}
+
+#[test]
+fn env_test() {
+    let v: Value = r#"
+    home = "${env:HOME}/.config"
+    tmp = "${env:TMPDIR:-/tmp}"
+    "#.parse().unwrap();
+
+    let out = Interpolate::new().env_vars([("HOME", "/home/me")]).resolve(&v).unwrap();
+    assert_eq!(out.path() / "home" | "", "/home/me/.config");
+    assert_eq!(out.path() / "tmp" | "", "/tmp");
+
+    let err = Interpolate::new().resolve(&v).unwrap_err();
+    assert_eq!(err, InterpError::Env { path: TomlPath::parse("home").unwrap(), name: "HOME".to_string() });
+}
+
+#[test]
+fn error_test() {
+    let v: Value = r#"
+    a = "${b}"
+    b = "x${c.d}"
+    c = { d = "${a}" }
+    "#.parse().unwrap();
+    let err = Interpolate::new().resolve(&v).unwrap_err();
+    assert_eq!(err.to_string(), "reference cycle: `a` -> `b` -> `c.d` -> `a`");
+
+    let v: Value = r#"
+    a = ["${a[0]}"]
+    "#.parse().unwrap();
+    assert_eq!(matches!(Interpolate::new().resolve(&v), Err(InterpError::Cycle { .. })), true);
+
+    let v: Value = r#"a = "${nokey}""#.parse().unwrap();
+    let err = Interpolate::new().resolve(&v).unwrap_err();
+    assert_eq!(err.to_string(), "reference `nokey` at `a` not found");
+
+    let v: Value = r#"a = "x ${b"
+    "#.parse().unwrap();
+    assert_eq!(matches!(Interpolate::new().resolve(&v), Err(InterpError::Syntax { .. })), true);
+
+    let v: Value = r#"
+    a = "list: ${b}"
+    b = [1, 2]
+    "#.parse().unwrap();
+    assert_eq!(matches!(Interpolate::new().resolve(&v), Err(InterpError::NotScalar { .. })), true);
+}
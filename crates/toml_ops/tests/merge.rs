@@ -0,0 +1,129 @@
+use toml::Value;
+use toml_ops::{PathOperator, Merge, ArrayMerge, Layers, TomlPath};
+
+fn parse(text: &str) -> Value {
+    text.parse().unwrap()
+}
+
+#[test]
+fn merge_operator_test() {
+    let mut base = parse(r#"
+    name = "app"
+    [server]
+    host = "0.0.0.0"
+    port = 8080
+    tags = ["a", "b"]
+    "#);
+    let overlay = parse(r#"
+    log = "debug"
+    [server]
+    port = 9090
+    tags = ["c"]
+    [server.tls]
+    cert = "x.pem"
+    "#);
+
+    let mut root = base.path_mut();
+    root += &overlay;
+    assert_eq!(base.path() / "name" | "", "app");
+    assert_eq!(base.path() / "log" | "", "debug");
+    assert_eq!(base.path() / "server" / "host" | "", "0.0.0.0");
+    assert_eq!(base.path() / "server" / "port" | 0, 9090);
+    assert_eq!(base.path() / "server" / "tls" / "cert" | "", "x.pem");
+    assert_eq!(base["server"]["tags"], parse("t = ['c']")["t"]);
+
+    // merge into sub node, table replace scalar
+    let mut node = base.path_mut() / "name";
+    node += &parse("first = 'my'");
+    assert_eq!(base.path() / "name" / "first" | "", "my");
+}
+
+#[test]
+fn merge_option_test() {
+    let base = parse(r#"
+    keep = 1
+    drop = 2
+    list = [1, 2]
+    [[servers]]
+    name = "a"
+    port = 1
+    [[servers]]
+    name = "b"
+    port = 2
+    "#);
+    let overlay = parse(r#"
+    drop = "~"
+    nokey = "~"
+    list = [2, 3]
+    [[servers]]
+    name = "b"
+    port = 20
+    [[servers]]
+    name = "c"
+    port = 3
+    "#);
+
+    let mut v = base.clone();
+    Merge::new().array(ArrayMerge::Append).delete_marker("~").apply(&mut v, &overlay);
+    assert_eq!(v.get("drop").is_none(), true);
+    assert_eq!(v.get("nokey").is_none(), true);
+    assert_eq!(v.path() / "keep" | 0, 1);
+    assert_eq!(v["list"].as_array().unwrap().len(), 4);
+    assert_eq!(v["servers"].as_array().unwrap().len(), 4);
+
+    let mut v = base.clone();
+    Merge::new().array(ArrayMerge::Union).apply(&mut v, &overlay);
+    assert_eq!(v["list"], parse("t = [1, 2, 3]")["t"]);
+    assert_eq!(v.path() / "drop" | "", "~");
+
+    let mut v = base.clone();
+    Merge::new().array(ArrayMerge::UnionBy("name".to_string())).apply(&mut v, &overlay);
+    assert_eq!(v["servers"].as_array().unwrap().len(), 3);
+    assert_eq!(v.path() / "servers" / 1 / "port" | 0, 20);
+    assert_eq!(v.path() / "servers" / 2 / "name" | "", "c");
+}
+
+#[test]
+fn layers_test() {
+    let defaults = parse(r#"
+    [server]
+    host = "0.0.0.0"
+    port = 8080
+    [log]
+    level = "info"
+    file = "app.log"
+    "#);
+    let site = parse(r#"
+    [server]
+    port = 80
+    [log]
+    file = "~"
+    "#);
+    let user = parse(r#"
+    log = { level = "debug" }
+    extra = { a = 1 }
+    "#);
+
+    let mut layers = Layers::new(Merge::new().delete_marker("~"));
+    layers.push("defaults", &defaults).push("site", &site).push("user", &user);
+
+    let v = layers.value();
+    assert_eq!(v.path() / "server" / "port" | 0, 80);
+    assert_eq!(v.path() / "log" / "file" | "", "");
+
+    let origin = |path: &str| layers.origin(&TomlPath::parse(path).unwrap());
+    assert_eq!(origin("server.host"), Some("defaults"));
+    assert_eq!(origin("server.port"), Some("site"));
+    assert_eq!(origin("log.level"), Some("user"));
+    assert_eq!(origin("log.file"), None);
+    assert_eq!(origin("extra.a"), Some("user"));
+    assert_eq!(origin("server"), None);
+    assert_eq!(layers.origins().count(), 4);
+
+    // replaced subtree take the origin as a whole
+    let mut layers = Layers::default();
+    layers.push("defaults", &defaults).push("env", &parse("log = 'off'"));
+    assert_eq!(layers.origin(&TomlPath::parse("log").unwrap()), Some("env"));
+    assert_eq!(layers.origins().count(), 3);
+    assert_eq!(layers.into_value().path() / "log" | "", "off");
+}
@@ -1,6 +1,9 @@
 use toml::Value;
 use toml_ops::PathOperator;
 use toml_ops::TomlPtr;
+use toml_ops::TomlPath;
+use toml_ops::PathItem;
+use toml_ops::Typed;
 //use toml_ops::TomlPtrMut;
 
 fn load_test_toml() -> Value
@@ -16,16 +19,16 @@ fn path_test() {
     assert_eq!(v["ip"].as_str(), Some("127.0.0.1"));
 
     let op = TomlPtr::from(&v);
-    let ip = op / "ip";
+    let ip = op.clone() / "ip";
     assert_eq!(ip.unwrap().as_str(), Some("127.0.0.1"));
 
     let ip = op / "host" / "ip";
     assert_eq!(ip.unwrap().as_str(), Some("127.0.1.1"));
 
     let host = TomlPtr::from(&v) / "host";
-    let ip = host / "ip";
+    let ip = host.clone() / "ip";
     assert_eq!(ip.unwrap().as_str(), Some("127.0.1.1"));
-    let port = host / "port";
+    let port = host.clone() / "port";
     assert_eq!(port.unwrap().as_integer(), Some(8080));
 
     let proto = host / "protocol" / 1;
@@ -59,27 +62,27 @@ fn path_none_test() {
 
     let root = v.path();
     assert_eq!(root.is_none(), false);
-    assert_eq!(!root, false);
+    assert_eq!(!root.clone(), false);
 
-    let node = root / "ip";
+    let node = root.clone() / "ip";
     assert_eq!(node.is_none(), false);
     assert_eq!(!node, false);
-    let node = root / "IP";
+    let node = root.clone() / "IP";
     assert_eq!(node.is_none(), true);
     assert_eq!(!node, true);
 
-    let node = root / "host" /"protocol";
+    let node = root.clone() / "host" /"protocol";
     assert_eq!(node.is_none(), false);
-    let node = root / "host" /"protocol" / 1;
+    let node = root.clone() / "host" /"protocol" / 1;
     assert_eq!(node.is_none(), false);
-    let node = root / "host" /"protocol" / 3;
+    let node = root.clone() / "host" /"protocol" / 3;
     assert_eq!(node.is_none(), true);
 
-    let node = root / "service" / 0;
+    let node = root.clone() / "service" / 0;
     assert_eq!(node.is_none(), false);
-    let node = root / "service" / 0 / "description";
+    let node = root.clone() / "service" / 0 / "description";
     assert_eq!(node.is_none(), true);
-    let node = root / "service" / 0 / "desc";
+    let node = root.clone() / "service" / 0 / "desc";
     assert_eq!(node.is_none(), false);
     let node = root / "service" / 2;
     assert_eq!(node.is_none(), true);
@@ -92,17 +95,17 @@ fn path_mut_test() {
 
     let root = v.path();
     assert_eq!(root.is_none(), false);
-    assert_eq!(!root, false);
+    assert_eq!(!root.clone(), false);
 
-    let node = root / "ip";
+    let node = root.clone() / "ip";
     assert_eq!(node.is_none(), false);
-    assert_eq!(!node, false);
-    assert_eq!(!!node, true);
+    assert_eq!(!node.clone(), false);
+    assert_eq!(!!node.clone(), true);
     assert_eq!(node.is_none(), false);
 
     let node = root / "IP";
     assert_eq!(node.is_none(), true);
-    assert_eq!(!node, true);
+    assert_eq!(!node.clone(), true);
     assert_eq!((*node).is_none(), true);
 
     let node = v.path_mut() / "ip";
@@ -148,9 +151,9 @@ fn pipe_test() {
 
     // can save intermedia tmp value
     let misc = v.path() / "misc";
-    let value = misc / "int" | 0;
+    let value = misc.clone() / "int" | 0;
     assert_eq!(value, 1234);
-    let value = misc / "float" | 0.0;
+    let value = misc.clone() / "float" | 0.0;
     assert_eq!(value, 3.14);
     let value = misc / "bool" | false;
     assert_eq!(value, true);
@@ -339,20 +342,20 @@ fn path_if_test() {
 
     let node = v.path() / "ip";
     if node.is_some() {
-        let ip = node | "";
+        let ip = node.clone() | "";
         assert_eq!(ip, "127.0.0.1");
     }
-    if !!node {
+    if !!node.clone() {
         let ip = node | "";
         assert_eq!(ip, "127.0.0.1");
     }
 
     let node = v.path() / "IP";
     if node.is_none() {
-        let ip = node | "";
+        let ip = node.clone() | "";
         assert_eq!(ip, "");
     }
-    if !node {
+    if !node.clone() {
         let ip = node | "";
         assert_eq!(ip, "");
     }
@@ -380,3 +383,352 @@ fn path_if_test() {
     }
 }
 
+
+#[test]
+fn path_syntax_test() {
+    let tv = r#"
+    [servers."10.0.0.1"]
+    port = 8080
+    [servers."a/b"]
+    port = 8081
+    [codes]
+    404 = "not found"
+    [[list]]
+    name = "first"
+    "#;
+    let mut v: Value = tv.parse().unwrap();
+
+    let port = v.path() / r#"servers."10.0.0.1".port"# | 0;
+    assert_eq!(port, 8080);
+    let port = v.pathto(r"servers/10\.0\.0\.1/port") | 0;
+    assert_eq!(port, 8080);
+    let port = v.pathto("servers.'a/b'.port") | 0;
+    assert_eq!(port, 8081);
+
+    // numeric key of table is not array index, even in mutable pointer
+    let msg = v.path() / "codes.404" | "";
+    assert_eq!(msg, "not found");
+    let msg = v.pathto_mut("codes/404") | "";
+    assert_eq!(msg, "not found");
+    let msg = v.path() / "codes[404]" | "";
+    assert_eq!(msg, "");
+
+    let name = v.path() / "list[0].name" | "";
+    assert_eq!(name, "first");
+    let name = v.pathto_mut("list/0/name") | "";
+    assert_eq!(name, "first");
+
+    // syntax error is reported by parsed path, or just invalid pointer
+    let err = TomlPath::parse("list[x].name").unwrap_err();
+    assert_eq!(err.pos(), 5);
+    assert_eq!(v.pathto("list[x].name").is_none(), true);
+
+    let path = TomlPath::parse("servers.'a/b'.port").unwrap();
+    let port = v.path() / &path | 0;
+    assert_eq!(port, 8081);
+    let node = v.path_mut() / &path << 9090;
+    assert_eq!(node | 0, 9090);
+}
+
+#[test]
+fn json_pointer_test() {
+    let tv = r#"
+    [servers."a/b"]
+    port = 8081
+    "m~n" = "tilde"
+    [[list]]
+    name = "first"
+    [[list]]
+    name = "second"
+    "#;
+    let mut v: Value = tv.parse().unwrap();
+
+    let port = v.pointer("/servers/a~1b/port") | 0;
+    assert_eq!(port, 8081);
+    let tilde = v.pointer("/servers/a~1b/m~0n") | "";
+    assert_eq!(tilde, "tilde");
+    let name = v.pointer("/list/1/name") | "";
+    assert_eq!(name, "second");
+    assert_eq!(v.pointer("").unwrap().is_table(), true);
+
+    // not valid pointer or index
+    assert_eq!(v.pointer("list/1").is_none(), true);
+    assert_eq!(v.pointer("/list/01").is_none(), true);
+    assert_eq!(v.pointer("/list/-").is_none(), true);
+    assert_eq!(v.pointer("/list/~2").is_none(), true);
+
+    // numeric key must be canonical in path as well
+    assert_eq!((v.path() / "list" / "01").is_none(), true);
+    assert_eq!(v.pathto("list/+1").is_none(), true);
+    assert_eq!(v.pathto("list/1/name") | "", "second");
+
+    let node = v.pointer_mut("/list/0/name") << "renamed";
+    assert_eq!(node | "", "renamed");
+
+    // render path back to pointer
+    let ptr = v.path() / "servers" / "a/b" / "m~n";
+    assert_eq!(ptr.to_pointer().unwrap(), "/servers/a~1b/m~0n");
+    let ptr = v.pathto("list[1].name");
+    assert_eq!(ptr.to_pointer().unwrap(), "/list/1/name");
+    assert_eq!(ptr.location().unwrap().to_string(), "list[1].name");
+    assert_eq!(v.path().to_pointer().unwrap(), "");
+    assert_eq!((v.path() / "nokey").to_pointer(), None);
+
+    // relative to the node where start from
+    let list = v.get("list").unwrap();
+    let ptr = list.path() / 0 / "name";
+    assert_eq!(ptr.to_pointer().unwrap(), "/0/name");
+}
+
+#[test]
+fn path_create_test() {
+    let mut v: Value = r#"
+    [a]
+    x = 1
+    "#.parse().unwrap();
+
+    // normal mutable pointer cannot write missing node
+    let node = v.path_mut() / "a" / "b" << ("k", 1);
+    assert_eq!(node.is_none(), true);
+
+    let node = v.path_create() / "a" / "b" << ("k", 1);
+    assert_eq!(node.is_some(), true);
+    assert_eq!(v.path() / "a/b/k" | 0, 1);
+
+    let _ = v.path_create() / "a/b/c/d" << 1;
+    assert_eq!(v.path() / "a.b.c.d" | 0, 1);
+    let _ = v.path_create() / "s" << "str";
+    assert_eq!(v.path() / "s" | "", "str");
+
+    // index segment create array, and index equal to length append
+    let _ = v.path_create() / "list[0].name" << "first";
+    let _ = v.path_create() / "list" / 1 / "name" << "second";
+    assert_eq!(v.path() / "list/1/name" | "", "second");
+    assert_eq!(v.path_create() / "list[5]" << 1 | 0, 0);
+
+    // failed walk leave the tree unchanged
+    let before = v.clone();
+    assert_eq!((v.path_create() / "newlist[5]").is_none(), true);
+    assert_eq!((v.path_create() / "new/deep[0].x[2]").is_none(), true);
+    assert_eq!((v.path_create() / "list[0].name.x").is_none(), true);
+    assert_eq!(v, before);
+    let _ = v.path_create() / "tags" << ("x",) << ("y",);
+    assert_eq!(v.path() / "tags" / 1 | "", "y");
+
+    // refuse to overwrite existing scalar or mismatch type
+    let node = v.path_create() / "a" / "x" / "y" << 2;
+    assert_eq!(node.is_none(), true);
+    let node = v.path_create() / "a" / "x" << "str";
+    assert_eq!(node.is_none(), true);
+    assert_eq!(v.path() / "a/x" | 0, 1);
+
+    let path = toml_ops::TomlPath::parse("p.q").unwrap();
+    let _ = v.path_create() / &path << true;
+    assert_eq!(v.path() / "p/q" | false, true);
+}
+
+#[test]
+fn remove_test() {
+    let mut v = load_test_toml();
+
+    let port = v.path_mut() / "host" - "port";
+    assert_eq!(port, Some(Value::Integer(8080)));
+    assert_eq!(v.path() / "host" / "port" | 0, 0);
+    assert_eq!(v.path_mut() / "host" - "port", None);
+
+    let proto = v.path_mut() / "host" / "protocol" - 0;
+    assert_eq!(proto.unwrap().as_str(), Some("tcp"));
+    assert_eq!(v.path() / "host" / "protocol" / 0 | "", "udp");
+
+    let name = v.path_mut() - "service[1].name";
+    assert_eq!(name.unwrap().as_str(), Some("serv_2"));
+    let service = v.path_mut().remove("service/0");
+    assert_eq!(service.is_some(), true);
+    assert_eq!(v.path() / "service" / 0 / "desc" | "", "another server");
+
+    assert_eq!(v.path_mut() / "nokey" - "x", None);
+    assert_eq!(v.path_mut() / "misc" / "int" - "x", None);
+}
+
+#[test]
+fn rename_move_test() {
+    let mut v = load_test_toml();
+
+    assert_eq!((v.path_mut() / "misc").rename("int", "integer"), true);
+    assert_eq!(v.path() / "misc" / "integer" | 0, 1234);
+    assert_eq!((v.path_mut() / "misc").rename("int", "x"), false);
+    assert_eq!((v.path_mut() / "misc").rename("float", "bool"), false);
+    assert_eq!(v.path() / "misc" / "float" | 0.0, 3.14);
+
+    let mut root = v.path_mut();
+    assert_eq!(root.move_path("misc.bool", "host.bool"), true);
+    assert_eq!(root.copy_path("host.protocol[2]", "host.protocol[0]"), true);
+    assert_eq!(root.copy_path("host", "service[2]"), true);
+    assert_eq!(v.path() / "host" / "bool" | false, true);
+    assert_eq!(v.path() / "misc" / "bool" | false, false);
+    assert_eq!(v.path() / "host" / "protocol" / 0 | "", "mmp");
+    assert_eq!(v.path() / "host" / "protocol" / 3 | "", "mmp");
+    assert_eq!(v.path() / "service" / 2 / "ip" | "", "127.0.1.1");
+
+    // parent of target is missing, or move into itself, nothing changed
+    let mut root = v.path_mut();
+    assert_eq!(root.move_path("host.ip", "nokey.ip"), false);
+    assert_eq!(root.move_path("host", "host.inner"), false);
+    assert_eq!(root.copy_path("host", "service[9]"), false);
+    assert_eq!(v.path() / "host" / "ip" | "", "127.0.1.1");
+}
+
+#[test]
+fn traverse_test() {
+    let v = load_test_toml();
+    let host = v.path() / "host";
+    assert_eq!(host.path(), TomlPath::parse("host").ok());
+    assert_eq!(host.keys().collect::<Vec<_>>(), vec![
+        PathItem::Key("ip".into()), PathItem::Key("port".into()), PathItem::Key("protocol".into())]);
+    assert_eq!(host.children().count(), 3);
+
+    let proto = host.clone() / "protocol";
+    assert_eq!(proto.keys().collect::<Vec<_>>(), vec![PathItem::Index(0), PathItem::Index(1), PathItem::Index(2)]);
+    let names: Vec<&str> = proto.children().map(|p| p | "").collect();
+    assert_eq!(names, vec!["tcp", "udp", "mmp"]);
+    assert_eq!((proto.clone() / 1).path().unwrap().to_string(), "host.protocol[1]");
+    assert_eq!((proto / 1).parent().parent().path(), host.path());
+    assert_eq!(v.path().parent().is_none(), true);
+    assert_eq!((host.clone() / "port").keys().count(), 0);
+    assert_eq!((host / "nokey").children().count(), 0);
+
+    let paths: Vec<String> = (v.path() / "service").walk().map(|(path, _)| path.to_string()).collect();
+    assert_eq!(paths, vec!["service[0]", "service[0].desc", "service[0].name",
+        "service[1]", "service[1].desc", "service[1].name"]);
+    let strings = v.path().walk().filter(|(_, p)| p.unwrap().is_str()).count();
+    assert_eq!(strings, 9);
+    for (path, ptr) in v.path().walk() {
+        assert_eq!(ptr.path(), Some(path));
+    }
+
+    // the path is the one walked, numeric key of array recorded as index
+    let name = v.pathto("service/1/name");
+    assert_eq!(name.path().unwrap().to_string(), "service[1].name");
+    assert_eq!(name.parent().path().unwrap().to_string(), "service[1]");
+    assert_eq!((name.parent() / "desc").path().unwrap().to_string(), "service[1].desc");
+    assert_eq!(v.pointer("/service/0").to_pointer(), Some("/service/0".to_string()));
+    assert_eq!((name / "nokey").path(), None);
+}
+
+#[test]
+fn compare_test() {
+    let v = load_test_toml();
+    assert_eq!(v.path() / "host" / "port" == 8080, true);
+    assert_eq!(v.path() / "host" / "port" != 8081, true);
+    assert_eq!(v.path() / "host" / "port" > 8000, true);
+    assert_eq!(v.path() / "host" / "port" < 8000, false);
+    assert_eq!(v.path() / "host" / "port" == 8080.0, false);
+    assert_eq!(v.path() / "misc" / "float" < 4.0, true);
+    assert_eq!(v.path() / "misc" / "bool" == true, true);
+    assert_eq!(v.path() / "ip" == "127.0.0.1", true);
+    assert_eq!(v.path() / "ip" == String::from("127.0.0.1"), true);
+    assert_eq!(v.path() / "ip" < "127.0.1.1", true);
+    assert_eq!(v.path() / "nokey" == 0, false);
+    assert_eq!(v.path() / "nokey" != 0, true);
+    assert_eq!(v.path() / "nokey" < 0 || v.path() / "nokey" >= 0, false);
+
+    let serv = v.path() / "service";
+    assert_eq!(serv.clone() / 0 / "name" < serv.clone() / 1 / "name", true);
+    assert_eq!(serv.clone() / 0 == serv.clone() / 0, true);
+    assert_eq!(serv.clone() / 0 == serv.clone() / 1, false);
+    assert_eq!(serv / 0 / "name" < v.path() / "misc" / "int", false);
+    assert_eq!(v.path() / "nokey" == v.path() / "nokey", false);
+
+    // equal nodes are also `Equal` in order, number compare across int and float
+    let w: Value = r#"
+    a = { x = 1, y = [2.0] }
+    b = { x = 1.0, y = [2] }
+    n = 3
+    f = 2.5
+    "#.parse().unwrap();
+    assert_eq!(w.path() / "a" == w.path() / "b", true);
+    assert_eq!((w.path() / "a").partial_cmp(&(w.path() / "b")), Some(std::cmp::Ordering::Equal));
+    assert_eq!((w.path() / "a").partial_cmp(&(w.path() / "n")), None);
+    assert_eq!(w.path() / "n" > w.path() / "f", true);
+}
+
+#[test]
+fn arithmetic_test() {
+    let mut v = load_test_toml();
+
+    let mut port = v.path_mut() / "host" / "port";
+    port += 10;
+    port -= 1;
+    assert_eq!(port.is_none(), false);
+    assert_eq!(v.path() / "host" / "port" | 0, 8089);
+
+    let mut float = v.path_mut() / "misc" / "float";
+    float += 1.0;
+    assert_eq!(v.path() / "misc" / "float" | 0.0, 3.14 + 1.0);
+
+    let mut ip = v.path_mut() / "ip";
+    ip += ":8080";
+    ip += String::from("/");
+    assert_eq!(v.path() / "ip" | "", "127.0.0.1:8080/");
+
+    let mut mismatch = v.path_mut() / "misc" / "int";
+    mismatch += 1.0;
+    assert_eq!(mismatch.is_none(), true);
+    assert_eq!(v.path() / "misc" / "int" | 0, 1234);
+
+    let mut overflow = v.path_mut() / "misc" / "int";
+    overflow += i64::MAX;
+    assert_eq!(overflow.is_none(), true);
+    assert_eq!(v.path() / "misc" / "int" | 0, 1234);
+
+    let mut count = v.path_create() / "stat" / "count";
+    count -= 2;
+    count += 5;
+    assert_eq!(v.path() / "stat" / "count" | 0, 3);
+}
+
+#[test]
+fn value_kind_test() {
+    use toml::value::{Datetime, Table};
+    let mut v = load_test_toml();
+
+    let _ = v.path_mut() / "misc" / "int" << 42u8;
+    assert_eq!(v.path() / "misc" / "int" | 0, 42);
+    let _ = v.path_mut() / "misc" / "int" << 65535u16 << -7i32;
+    assert_eq!(v.path() / "misc" / "int" | 0, -7);
+    let node = v.path_mut() / "misc" / "float" << 1u8;
+    assert_eq!(node.is_none(), true);
+    assert_eq!(v.path() / "misc" / "int" | Typed(0u8), 0);
+    assert_eq!(v.path() / "host" / "port" | Typed(0u16), 8080);
+
+    let dt: Datetime = "1979-05-27T07:32:00Z".parse().unwrap();
+    let node = v.path_create() / "misc" / "time" << dt;
+    assert_eq!(node.is_none(), false);
+    assert_eq!(v.path() / "misc" / "time" | Datetime::from(dt), dt);
+    let later: Datetime = "2000-01-01".parse().unwrap();
+    let _ = v.path_mut() / "misc" / "time" << later;
+    assert_eq!(v.path() / "misc" / "time" | dt, later);
+    let node = v.path_mut() / "misc" / "int" << dt;
+    assert_eq!(node.is_none(), true);
+    assert_eq!(v.path() / "ip" | dt, dt);
+
+    let mut extra = Table::new();
+    extra.insert("timeout".to_string(), Value::from(30));
+    extra.insert("ip".to_string(), Value::from("0.0.0.0"));
+    let node = v.path_mut() / "host" << extra.clone();
+    assert_eq!(node.is_none(), false);
+    assert_eq!(v.path() / "host" / "timeout" | 0, 30);
+    assert_eq!(v.path() / "host" / "ip" | "", "0.0.0.0");
+    assert_eq!(v.path() / "host" / "port" | 0, 8080);
+    let node = v.path_mut() / "ip" << extra.clone();
+    assert_eq!(node.is_none(), true);
+    let _ = v.path_create() / "new" / "table" << extra;
+    assert_eq!((v.path() / "new" / "table" | Table::new()).len(), 2);
+
+    let items = vec![Value::from("tls"), Value::from("quic")];
+    let _ = v.path_mut() / "host" / "protocol" << items.clone();
+    assert_eq!((v.path() / "host" / "protocol" | Vec::new()).len(), 5);
+    let _ = v.path_create() / "new" / "array" << items;
+    assert_eq!(v.path() / "new" / "array" / 1 | "", "quic");
+    assert_eq!((v.path() / "ip" | vec![Value::from(1)]).len(), 1);
+}
@@ -0,0 +1,148 @@
+use toml::Value;
+use toml_ops::{PathOperator, Patch, PatchError, PatchOp, TomlPath};
+
+fn load_test_toml() -> Value
+{
+    let str_toml = include_str!("../examples/sample.toml");
+    str_toml.parse().unwrap()
+}
+
+fn parse_patch(text: &str) -> Patch {
+    let v: Value = text.parse().unwrap();
+    Patch::from_value(&v["patch"]).unwrap()
+}
+
+#[test]
+fn apply_test() {
+    let mut v = load_test_toml();
+    let patch = parse_patch(r#"
+    patch = [
+        { op = "test", path = "/host/port", value = 8080 },
+        { op = "replace", path = "/host/port", value = 8989 },
+        { op = "add", path = "/host/protocol/0", value = "tls" },
+        { op = "add", path = "/host/protocol/-", value = "quic" },
+        { op = "add", path = "/misc/a~1b", value = { x = 1 } },
+        { op = "remove", path = "/service/1" },
+        { op = "copy", from = "/host/ip", path = "/service/0/ip" },
+        { op = "move", from = "/misc/int", path = "/host/int" },
+    ]
+    "#);
+    assert_eq!(patch.ops().len(), 8);
+    patch.apply(&mut v).unwrap();
+
+    assert_eq!(v.path() / "host" / "port" | 0, 8989);
+    assert_eq!(v.pointer("/host/protocol/0") | "", "tls");
+    assert_eq!(v.pointer("/host/protocol/4") | "", "quic");
+    assert_eq!(v.pointer("/misc/a~1b/x") | 0, 1);
+    assert_eq!((v.path() / "service").unwrap().as_array().unwrap().len(), 1);
+    assert_eq!(v.pointer("/service/0/ip") | "", "127.0.1.1");
+    assert_eq!(v.pointer("/host/int") | 0, 1234);
+    assert_eq!(v.pointer("/misc/int").is_none(), true);
+}
+
+#[test]
+fn atomic_test() {
+    let origin = load_test_toml();
+    let mut v = origin.clone();
+
+    let patch = parse_patch(r#"
+    patch = [
+        { op = "replace", path = "/host/port", value = 8989 },
+        { op = "remove", path = "/nokey" },
+    ]
+    "#);
+    let err = patch.apply(&mut v).unwrap_err();
+    assert_eq!(err, PatchError::NoPath { index: 1, path: TomlPath::from_pointer("/nokey").unwrap() });
+    assert_eq!(err.to_string(), "patch op 1 failed: path \"/nokey\" not found");
+    assert_eq!(v, origin);
+
+    let patch = parse_patch(r#"
+    patch = [
+        { op = "add", path = "/new", value = true },
+        { op = "test", path = "/host/port", value = "8080" },
+    ]
+    "#);
+    let err = patch.apply(&mut v).unwrap_err();
+    assert_eq!(matches!(err, PatchError::TestFailed { index: 1, .. }), true);
+    assert_eq!(v, origin);
+
+    let patch = parse_patch(r#"patch = [{ op = "move", from = "/host", path = "/host/inner" }]"#);
+    assert_eq!(matches!(patch.apply(&mut v), Err(PatchError::Invalid { index: 0, .. })), true);
+    let patch = parse_patch(r#"patch = [{ op = "add", path = "/host/protocol/9", value = 1 }]"#);
+    assert_eq!(patch.apply(&mut v).is_err(), true);
+    assert_eq!(v, origin);
+}
+
+#[test]
+fn test_number_test() {
+    let mut v: Value = r#"
+    a = 1
+    b = 1.5
+    c = { x = [1, 2.0] }
+    big = 9007199254740993
+    "#.parse().unwrap();
+
+    let patch = parse_patch(r#"
+    patch = [
+        { op = "test", path = "/a", value = 1.0 },
+        { op = "test", path = "/b", value = 1.5 },
+        { op = "test", path = "/c", value = { x = [1.0, 2] } },
+    ]
+    "#);
+    patch.apply(&mut v).unwrap();
+
+    let patch = parse_patch(r#"patch = [{ op = "test", path = "/a", value = 1.5 }]"#);
+    assert_eq!(patch.apply(&mut v).is_err(), true);
+    let patch = parse_patch(r#"patch = [{ op = "test", path = "/a", value = "1" }]"#);
+    assert_eq!(patch.apply(&mut v).is_err(), true);
+    let patch = parse_patch(r#"patch = [{ op = "test", path = "/c", value = { x = [1, 2], y = 3 } }]"#);
+    assert_eq!(patch.apply(&mut v).is_err(), true);
+    // not equal to the nearest float 2^53
+    let patch = parse_patch(r#"patch = [{ op = "test", path = "/big", value = 9007199254740992.0 }]"#);
+    assert_eq!(patch.apply(&mut v).is_err(), true);
+}
+
+#[test]
+fn parse_error_test() {
+    let v: Value = r#"
+    a = [{ op = "add", path = "/x" }]
+    b = [{ op = "remove", path = "/x" }, { op = "bad", path = "/x" }]
+    c = [{ op = "remove", path = "x" }]
+    "#.parse().unwrap();
+
+    let err = Patch::from_value(&v["a"]).unwrap_err();
+    assert_eq!(err.to_string(), "patch op 0 is invalid: missing `value`");
+    let err = Patch::from_value(&v["b"]).unwrap_err();
+    assert_eq!(err.to_string(), "patch op 1 is invalid: unknown op `bad`");
+    assert_eq!(Patch::from_value(&v["c"]).is_err(), true);
+    assert_eq!(Patch::from_value(&v).is_err(), true);
+}
+
+#[test]
+fn diff_test() {
+    let from = load_test_toml();
+    let mut to = from.clone();
+    let _ = to.path_mut() / "host" / "port" << 9090;
+    let _ = to.path_mut() / "host" / "protocol" - 2;
+    let _ = to.path_mut() / "misc" << ("new", "value");
+    let _ = to.path_mut() / "service" << (toml::toml!{ name = "serv_3" },);
+    let _ = to.path_mut() - "ip";
+
+    let patch = Patch::diff(&from, &to);
+    assert_eq!(patch.ops().len(), 5);
+    assert_eq!(patch.ops().contains(&PatchOp::Remove { path: TomlPath::parse("host.protocol[2]").unwrap() }), true);
+
+    let mut v = from.clone();
+    patch.apply(&mut v).unwrap();
+    assert_eq!(v, to);
+
+    // round trip through value
+    let patch = Patch::from_value(&patch.to_value()).unwrap();
+    let mut v = from.clone();
+    patch.apply(&mut v).unwrap();
+    assert_eq!(v, to);
+
+    assert_eq!(Patch::diff(&from, &from).is_empty(), true);
+    let patch = Patch::diff(&Value::from(1), &Value::from("x"));
+    assert_eq!(patch.ops(), &[PatchOp::Replace { path: TomlPath::new(), value: Value::from("x") }]);
+}
@@ -0,0 +1,140 @@
+use toml::Value;
+use toml_ops::Query;
+
+fn load_test_toml() -> Value
+{
+    let str_toml = include_str!("../examples/sample.toml");
+    str_toml.parse().unwrap()
+}
+
+fn select_paths(v: &Value, query: &str) -> Vec<String> {
+    let query = Query::parse(query).unwrap();
+    query.select(v).map(|(path, _)| path.to_string()).collect()
+}
+
+#[test]
+fn select_test() {
+    let v = load_test_toml();
+
+    let query = Query::parse("$.service[*].name").unwrap();
+    let names: Vec<&str> = query.select(&v).filter_map(|(_, v)| v.as_str()).collect();
+    assert_eq!(names, vec!["serv_1", "serv_2"]);
+
+    assert_eq!(select_paths(&v, "service[*].name"), vec!["service[0].name", "service[1].name"]);
+    assert_eq!(select_paths(&v, "$['host']['port']"), vec!["host.port"]);
+    assert_eq!(select_paths(&v, "$.host.protocol[-1]"), vec!["host.protocol[2]"]);
+    assert_eq!(select_paths(&v, "$.host.protocol[0, 2]"), vec!["host.protocol[0]", "host.protocol[2]"]);
+    assert_eq!(select_paths(&v, "$.host.protocol[1:]"), vec!["host.protocol[1]", "host.protocol[2]"]);
+    assert_eq!(select_paths(&v, "$.host.protocol[::-2]"), vec!["host.protocol[2]", "host.protocol[0]"]);
+    assert_eq!(select_paths(&v, "$.host.protocol[1::9223372036854775807]"), vec!["host.protocol[1]"]);
+    assert_eq!(select_paths(&v, "$.host.protocol[::-9223372036854775808]"), vec!["host.protocol[2]"]);
+    assert_eq!(select_paths(&v, "$.misc.*").len(), 3);
+    assert_eq!(select_paths(&v, "$"), vec![""]);
+
+    // no match is not error
+    assert_eq!(select_paths(&v, "$.nokey[*]").is_empty(), true);
+    assert_eq!(select_paths(&v, "$.host.protocol[5]").is_empty(), true);
+}
+
+#[test]
+fn descendant_test() {
+    let v = load_test_toml();
+
+    assert_eq!(select_paths(&v, "$..ip"), vec!["ip", "host.ip"]);
+    assert_eq!(select_paths(&v, "..desc"), vec!["service[0].desc", "service[1].desc"]);
+    assert_eq!(select_paths(&v, "$.service..*").len(), 6);
+}
+
+#[test]
+fn filter_test() {
+    let v: Value = r#"
+    [dependencies]
+    serde = { version = "1.0", optional = true }
+    toml = { version = "0.7" }
+    log = { version = "0.4", optional = false }
+    [[servers]]
+    name = "alpha"
+    port = 8080
+    [[servers]]
+    name = "beta"
+    port = 9090
+    tags = ["web"]
+    "#.parse().unwrap();
+
+    assert_eq!(select_paths(&v, "dependencies[?(@.optional == true)]"), vec!["dependencies.serde"]);
+    assert_eq!(select_paths(&v, "dependencies[?(@.optional)]").len(), 2);
+    assert_eq!(select_paths(&v, "dependencies[?(!@.optional)]"), vec!["dependencies.toml"]);
+    assert_eq!(select_paths(&v, "dependencies[?(@.optional != true)]").len(), 2);
+
+    assert_eq!(select_paths(&v, "servers[?(@.port > 8080.5)].name"), vec!["servers[1].name"]);
+    assert_eq!(select_paths(&v, "servers[?(@.port >= 8080 && @.name == 'alpha')]"), vec!["servers[0]"]);
+    assert_eq!(select_paths(&v, r#"servers[?(@.name == "x" || (@.tags[0] == "web"))]"#), vec!["servers[1]"]);
+    assert_eq!(select_paths(&v, "servers[?(@.port < 1_0000)]").len(), 2);
+}
+
+#[test]
+fn datetime_filter_test() {
+    let v: Value = r#"
+    [[events]]
+    start = 1979-05-27T07:32:00.5Z
+    end = 1979-05-27T07:32:00Z
+    [[events]]
+    start = 1979-05-27T07:32:00+00:00
+    end = 1979-05-27T07:32:00Z
+    [[events]]
+    start = 1979-05-27T00:32:00-07:00
+    end = 1979-05-27T07:32:00Z
+    [[events]]
+    start = 1979-05-27T08:32:00+02:00
+    end = 1979-05-27T07:32:00Z
+    [[events]]
+    start = 1979-05-27
+    end = 1979-05-27T07:32:00Z
+    [[events]]
+    start = 1979-05-27T07:32:00
+    end = 1979-05-27T07:32:00.999
+    "#.parse().unwrap();
+
+    assert_eq!(select_paths(&v, "events[?(@.start > @.end)]"), vec!["events[0]"]);
+    assert_eq!(select_paths(&v, "events[?(@.start == @.end)]"), vec!["events[1]", "events[2]"]);
+    assert_eq!(select_paths(&v, "events[?(@.start < @.end)]"), vec!["events[3]", "events[5]"]);
+    assert_eq!(select_paths(&v, "events[?(@.start != @.end)]").len(), 4);
+}
+
+#[test]
+fn select_mut_test() {
+    let mut v = load_test_toml();
+
+    let query: Query = "service[*].desc".parse().unwrap();
+    let count = query.select_mut(&mut v, |path, node| {
+        *node = Value::String(path.to_string());
+    });
+    assert_eq!(count, 2);
+
+    let query = Query::parse("..desc").unwrap();
+    let desc: Vec<&str> = query.select(&v).filter_map(|(_, v)| v.as_str()).collect();
+    assert_eq!(desc, vec!["service[0].desc", "service[1].desc"]);
+}
+
+#[test]
+fn parse_error_test() {
+    let err = Query::parse("$.a[").unwrap_err();
+    assert_eq!(err.pos(), 4);
+
+    let err = Query::parse("$.a[1").unwrap_err();
+    assert_eq!(err.reason(), "expected `]`");
+    assert_eq!(err.pos(), 5);
+
+    let err = Query::parse("$.a.").unwrap_err();
+    assert_eq!(err.reason(), "expected key name");
+
+    let err = Query::parse("$a").unwrap_err();
+    assert_eq!(err.pos(), 1);
+
+    let err = Query::parse("$.a[?(@.b == )]").unwrap_err();
+    assert_eq!(err.pos(), 13);
+    assert_eq!(err.to_string(), "invalid path at position 13: expected `@` or literal");
+
+    let err = Query::parse("$['a").unwrap_err();
+    assert_eq!(err.reason(), "unterminated quoted segment");
+}
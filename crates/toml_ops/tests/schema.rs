@@ -0,0 +1,168 @@
+use toml::Value;
+use toml_ops::{PathOperator, Schema, Violation, TomlPath};
+
+fn load_test_toml() -> Value
+{
+    let str_toml = include_str!("../examples/sample.toml");
+    str_toml.parse().unwrap()
+}
+
+const SAMPLE_DSL: &str = r#"
+ip = "string /^\\d+(\\.\\d+){3}$/"
+[host]
+ip = "string"
+port = "integer 1..65535"
+"protocol?" = ['enum(tcp, udp, mmp)']
+[[service]]
+name = "string 1.."
+desc = "string?"
+[misc]
+"*" = "any"
+"#;
+
+fn paths(violations: &[Violation]) -> Vec<String> {
+    violations.iter().map(|v| v.path().to_string()).collect()
+}
+
+#[test]
+fn dsl_test() {
+    let schema = Schema::parse(SAMPLE_DSL).unwrap();
+    let mut v = load_test_toml();
+    assert_eq!(schema.validate(&v), vec![]);
+
+    v["host"]["port"] = Value::from("8080");
+    let _ = v.path_mut() / "host" / "protocol" << ("quic",);
+    v["service"][1]["name"] = Value::from("");
+    let _ = v.path_mut() / "service" / 0 - "name";
+    let _ = v.path_mut() / "host" << ("extra", true);
+    v["ip"] = Value::from("localhost");
+
+    let violations = schema.validate(&v);
+    assert_eq!(paths(&violations), vec!["host.extra", "host.port", "host.protocol[3]",
+        "ip", "service[0].name", "service[1].name"]);
+    assert_eq!(violations[1], Violation::Type {
+        path: TomlPath::parse("host.port").unwrap(), expected: "integer".to_string(), found: "string" });
+    assert_eq!(violations[1].to_string(), "expect integer, found string at `host.port`");
+    assert_eq!(violations[0].to_string(), "unknown key at `host.extra`");
+    assert_eq!(violations[4].to_string(), "missing required key at `service[0].name`");
+    assert_eq!(violations[5].to_string(), "length 0 less than minimum 1 at `service[1].name`");
+
+    v["host"]["port"] = Value::from(70000);
+    let violations = schema.validate(&v);
+    assert_eq!(violations.iter().any(|v| v.to_string() == "value 70000 must be at most 65535 at `host.port`"), true);
+}
+
+#[test]
+fn dsl_optional_test() {
+    let schema = Schema::parse(r#"
+    name = "string"
+    ratio = "float? 0.0..1.0"
+    "tls?" = { cert = "string" }
+    level = 'enum("debug", "info")?'
+    "#).unwrap();
+
+    let v: Value = r#"name = "a""#.parse().unwrap();
+    assert_eq!(schema.is_valid(&v), true);
+    let v: Value = r#"
+    ratio = 1.5
+    level = "trace"
+    tls = {}
+    "#.parse().unwrap();
+    let violations = schema.validate(&v);
+    assert_eq!(paths(&violations), vec!["name", "level", "ratio", "tls.cert"]);
+    assert_eq!(schema.validate(&Value::from(1)).len(), 1);
+}
+
+#[test]
+fn dsl_enum_slash_test() {
+    let schema = Schema::parse(r#"
+    mime = "enum(text/plain, text/html)"
+    "type?" = 'enum("a/b", "c")?'
+    name = "string /^[a-z]+$/"
+    "#).unwrap();
+
+    let v: Value = r#"mime = "text/html""#.parse().unwrap();
+    assert_eq!(paths(&schema.validate(&v)), vec!["name"]);
+    let v: Value = r#"
+    mime = "text/plain"
+    type = "a/b"
+    name = "abc"
+    "#.parse().unwrap();
+    assert_eq!(schema.validate(&v), vec![]);
+    let v: Value = r#"
+    mime = "plain, text"
+    type = "b"
+    name = "ABC"
+    "#.parse().unwrap();
+    assert_eq!(paths(&schema.validate(&v)), vec!["mime", "name", "type"]);
+}
+
+#[test]
+fn json_schema_test() {
+    let json = include_str!("schema/sample.schema.json");
+    let schema: Value = serde_json::from_str(json).unwrap();
+    let schema = Schema::from_json_schema(&schema).unwrap();
+
+    let mut v = load_test_toml();
+    assert_eq!(schema.validate(&v), vec![]);
+
+    let _ = v.path_mut() / "host" / "port" << 0;
+    let _ = v.path_mut() / "host" / "protocol" / 0 << "http";
+    let _ = v.path_mut() << ("unknown", 1);
+    let violations = schema.validate(&v);
+    assert_eq!(paths(&violations), vec!["host.port", "host.protocol[0]", "unknown"]);
+    assert_eq!(violations[1], Violation::Enum { path: TomlPath::parse("host.protocol[0]").unwrap() });
+}
+
+#[test]
+fn large_integer_bound_test() {
+    let schema = Schema::parse(r#"n = "integer ..9007199254740993""#).unwrap();
+    let v: Value = "n = 9007199254740993".parse().unwrap();
+    assert_eq!(schema.validate(&v), vec![]);
+    let v: Value = "n = 9007199254740994".parse().unwrap();
+    assert_eq!(schema.validate(&v)[0].to_string(),
+        "value 9007199254740994 must be at most 9007199254740993 at `n`");
+
+    let schema: Value = r#"properties = { n = { exclusiveMinimum = 9007199254740992 } }"#.parse().unwrap();
+    let schema = Schema::from_json_schema(&schema).unwrap();
+    let v: Value = "n = 9007199254740993".parse().unwrap();
+    assert_eq!(schema.validate(&v), vec![]);
+    let v: Value = "n = 9007199254740992.0".parse().unwrap();
+    assert_eq!(schema.validate(&v).len(), 1);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_str_test() {
+    let schema = Schema::from_json_str(r#"{
+        "type": "object",
+        "default": null,
+        "properties": {
+            "level": { "enum": ["debug", "info", null], "default": null },
+            "port": { "type": ["integer", "null"], "maximum": 65535 }
+        }
+    }"#).unwrap();
+    let v: Value = r#"level = "info""#.parse().unwrap();
+    assert_eq!(schema.validate(&v), vec![]);
+    let v: Value = r#"level = "trace"
+    port = 70000"#.parse().unwrap();
+    assert_eq!(paths(&schema.validate(&v)), vec!["level", "port"]);
+
+    let schema = Schema::from_json_str(include_str!("schema/sample.schema.json")).unwrap();
+    assert_eq!(schema.validate(&load_test_toml()), vec![]);
+    assert_eq!(Schema::from_json_str("{ bad json").is_err(), true);
+}
+
+#[test]
+fn schema_error_test() {
+    let err = Schema::parse(r#"a = { b = "strng" }"#).unwrap_err();
+    assert_eq!(err.to_string(), "invalid schema at `a.b`: unknown type `strng`");
+    assert_eq!(Schema::parse(r#"a = "integer 1-2""#).is_err(), true);
+    assert_eq!(Schema::parse(r#"a = "string /(/""#).is_err(), true);
+    assert_eq!(Schema::parse(r#"a = ["string", "integer"]"#).is_err(), true);
+
+    let schema: Value = r#"type = "object"
+    properties = { port = { type = "integer", minimum = "1" } }"#.parse().unwrap();
+    let err = Schema::from_json_schema(&schema).unwrap_err();
+    assert_eq!(err.to_string(), "invalid schema at `properties.port.minimum`: expect number");
+}
@@ -0,0 +1,94 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Copy the sample toml to a temp file for each test to edit.
+fn sample_file(name: &str) -> PathBuf
+{
+    let file = std::env::temp_dir().join(format!("tomlq_{}_{}.toml", name, std::process::id()));
+    std::fs::write(&file, include_str!("../examples/sample.toml")).unwrap();
+    file
+}
+
+/// Run tomlq and return exit code and stdout.
+fn tomlq(args: &[&str]) -> (i32, String)
+{
+    let output = Command::new(env!("CARGO_BIN_EXE_tomlq")).args(args).output().unwrap();
+    (output.status.code().unwrap(), String::from_utf8(output.stdout).unwrap())
+}
+
+#[test]
+fn get_test() {
+    let file = sample_file("get");
+    let file = file.to_str().unwrap();
+
+    assert_eq!(tomlq(&["get", file, "host.port"]), (0, "8080\n".to_string()));
+    assert_eq!(tomlq(&["get", file, "host.ip"]), (0, "127.0.1.1\n".to_string()));
+    assert_eq!(tomlq(&["get", file, "/service/1/name"]), (0, "serv_2\n".to_string()));
+    assert_eq!(tomlq(&["get", file, "host.protocol"]), (0, "[\"tcp\", \"udp\", \"mmp\"]\n".to_string()));
+    assert_eq!(tomlq(&["get", file, "misc"]), (0, "int = 1234\nfloat = 3.14\nbool = true\n".to_string()));
+    assert_eq!(tomlq(&["keys", file, "host"]), (0, "ip\nport\nprotocol\n".to_string()));
+    assert_eq!(tomlq(&["keys", file, "service"]), (0, "0\n1\n".to_string()));
+
+    assert_eq!(tomlq(&["get", file, "host.nokey"]).0, 2);
+    assert_eq!(tomlq(&["keys", file, "host.port"]).0, 3);
+    assert_eq!(tomlq(&["get", file]).0, 1);
+    assert_eq!(tomlq(&["get", "no-such-file.toml", "host"]).0, 1);
+
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn edit_test() {
+    let file = sample_file("edit");
+    let file = file.to_str().unwrap();
+    let origin = std::fs::read_to_string(file).unwrap();
+
+    assert_eq!(tomlq(&["set", file, "host.port", "9090"]).0, 0);
+    assert_eq!(tomlq(&["get", file, "host.port"]).1, "9090\n");
+    assert_eq!(tomlq(&["set", file, "host.port", "abc"]).0, 3);
+    assert_eq!(tomlq(&["set", file, "host.port", "abc", "--type", "str"]).0, 0);
+    assert_eq!(tomlq(&["get", file, "host.port"]).1, "abc\n");
+    assert_eq!(tomlq(&["set", file, "misc.float", "3"]).0, 0);
+    assert_eq!(tomlq(&["get", file, "misc.float"]).1, "3.0\n");
+    assert_eq!(tomlq(&["set", file, "misc.name", "\"new\""]).0, 0);
+    assert_eq!(tomlq(&["set", file, "misc.size", "10"]).0, 0);
+    assert_eq!(tomlq(&["get", file, "misc.size"]).1, "10\n");
+    assert_eq!(tomlq(&["set", file, "nokey.size", "10"]).0, 2);
+    assert_eq!(tomlq(&["set", file, "misc.int.sub", "10"]).0, 3);
+
+    assert_eq!(tomlq(&["push", file, "host.protocol", "http"]).0, 0);
+    assert_eq!(tomlq(&["get", file, "host.protocol"]).1, "[\"tcp\", \"udp\", \"mmp\", \"http\"]\n");
+    assert_eq!(tomlq(&["push", file, "host.ip", "http"]).0, 3);
+    assert_eq!(tomlq(&["push", file, "service", "{ name = \"serv_3\" }", "--type=toml"]).0, 0);
+    assert_eq!(tomlq(&["keys", file, "service"]).1, "0\n1\n2\n");
+
+    assert_eq!(tomlq(&["del", file, "misc.name"]).0, 0);
+    assert_eq!(tomlq(&["del", file, "service.2"]).0, 0);
+    assert_eq!(tomlq(&["del", file, "misc.name"]).0, 2);
+    assert_eq!(tomlq(&["get", file, "misc.name"]).0, 2);
+
+    // comments and other lines untouched
+    let text = std::fs::read_to_string(file).unwrap();
+    let mut diff: Vec<&str> = text.lines().filter(|line| !origin.lines().any(|o| o == *line)).collect();
+    diff.sort();
+    assert_eq!(diff, vec!["float = 3.0", "port = \"abc\"", "protocol = [\"tcp\", \"udp\", \"mmp\", \"http\"]", "size = 10"]);
+
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn save_test() {
+    // the file is replaced by rename, no temporary file left
+    let dir = std::env::temp_dir().join(format!("tomlq_save_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("config.toml");
+    std::fs::write(&file, include_str!("../examples/sample.toml")).unwrap();
+    let file = file.to_str().unwrap();
+
+    assert_eq!(tomlq(&["set", file, "host.port", "9090"]).0, 0);
+    assert_eq!(tomlq(&["get", file, "host.port"]).1, "9090\n");
+    let names: Vec<_> = std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name()).collect();
+    assert_eq!(names, vec!["config.toml"]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
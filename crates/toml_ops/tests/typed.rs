@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use toml::Value;
+use toml::value::Datetime;
+use toml_ops::{PathOperator, Typed};
+
+#[derive(Debug, Default, PartialEq, Deserialize)]
+struct HostConfig {
+    ip: String,
+    port: u16,
+    #[serde(default)]
+    protocol: Vec<String>,
+}
+
+fn load_test_toml() -> Value
+{
+    let str_toml = include_str!("../examples/sample.toml");
+    str_toml.parse().unwrap()
+}
+
+#[test]
+fn typed_pipe_test() {
+    let mut v = load_test_toml();
+
+    let host = v.path() / "host" | Typed(HostConfig::default());
+    assert_eq!(host.ip, "127.0.1.1");
+    assert_eq!(host.port, 8080);
+    assert_eq!(host.protocol, vec!["tcp", "udp", "mmp"]);
+
+    let port = v.path() / "host" / "port" | Typed(0u16);
+    assert_eq!(port, 8080);
+    let port = v.path() / "host" / "port" | Typed(0u8);
+    assert_eq!(port, 0);
+    let int = v.path() / "misc" / "int" | Typed(0u32);
+    assert_eq!(int, 1234);
+
+    let protocol = v.path() / "host" / "protocol" | Typed(Vec::<String>::new());
+    assert_eq!(protocol.len(), 3);
+    let misc = v.path() / "service" / 0 | Typed(HashMap::<String, String>::new());
+    assert_eq!(misc["name"], "serv_1");
+
+    // missing node or type mismatch fall back to default
+    let host = v.path() / "nokey" | Typed(HostConfig::default());
+    assert_eq!(host, HostConfig::default());
+    let host = v.path() / "misc" | Typed(HostConfig::default());
+    assert_eq!(host, HostConfig::default());
+
+    let dt: Value = "dt = 1979-05-27T07:32:00Z".parse().unwrap();
+    let default: Datetime = "1970-01-01T00:00:00Z".parse().unwrap();
+    let time = dt.path() / "dt" | Typed(default);
+    assert_eq!(time.to_string(), "1979-05-27T07:32:00Z");
+
+    let port = v.path_mut() / "host" / "port" | Typed(0u16);
+    assert_eq!(port, 8080);
+    let port = v.path_diag() / "host" / "port" | Typed(0u16);
+    assert_eq!(port, 8080);
+}
+
+#[test]
+fn extract_test() {
+    let v = load_test_toml();
+
+    let host: HostConfig = (v.path() / "host").extract().unwrap();
+    assert_eq!(host.port, 8080);
+
+    let err = (v.path() / "misc").extract::<HostConfig>().unwrap_err();
+    assert_eq!(err.to_string().contains("missing field `ip`"), true);
+
+    let err = (v.path() / "ip").extract::<u16>().unwrap_err();
+    assert_eq!(err.to_string().contains("invalid type"), true);
+
+    let err = (v.path() / "nokey").extract::<u16>().unwrap_err();
+    assert_eq!(err.to_string().contains("node not found"), true);
+
+    let err = (v.path_diag() / "host" / "nokey").extract::<u16>().unwrap_err();
+    assert_eq!(err.to_string().contains("missing key `nokey` in table at `host`"), true);
+}